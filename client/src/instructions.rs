@@ -0,0 +1,1379 @@
+//! Builders for every nftamm instruction.
+//!
+//! Builders derive every PDA, associated token account and program account an instruction needs,
+//! so callers only pass the keys that identify the pair, the trader and the nft. Trades that owe
+//! royalties take the creator accounts built by [crate::royalty].
+
+use crate::{pda::*, state::Pair};
+use anchor_lang::{
+    prelude::Pubkey,
+    solana_program::{
+        instruction::{AccountMeta, Instruction},
+        system_program, sysvar,
+    },
+    InstructionData, ToAccountMetas,
+};
+use nftamm::{accounts, instruction as ix};
+
+/// Parameters of a new pair, see [nftamm::state::Pair] for their meaning
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct PairParams {
+    /// 0 for token pairs, 1 for nft pairs, 2 for trade pairs
+    pub pair_type: u8,
+    /// 0 for linear, 1 for exponential, 2 for fixed, 3 for xyk
+    pub bonding_curve: u8,
+    /// Bonding curve delta
+    pub delta: u64,
+    /// Pair fee in basis points, trade pairs only
+    pub fee: u16,
+    /// Initial spot price
+    pub spot_price: u64,
+    /// 0 for none, 1 for full, 2 for percentage of royalties, 3 for capped
+    pub royalty_policy: u8,
+    /// Percentage or cap of the royalty policy in basis points
+    pub royalty_bps: u16,
+    /// Merkle root of allowed nft mints, None for any
+    pub mint_allowlist_root: Option<[u8; 32]>,
+    /// 0 for verified collection, 1 for first verified creator, 2 for any verified creator
+    pub collection_mode: u8,
+}
+
+/// An nft bought by [buy_nfts]
+#[derive(Clone, Debug, PartialEq)]
+pub struct BuyItem {
+    /// Mint of the nft
+    pub nft_token_mint: Pubkey,
+    /// Creator stored in the nft's [nftamm::state::PairMetadata]
+    pub pair_metadata_creator: Pubkey,
+    /// Creator accounts when royalties are owed, see [crate::royalty::creator_accounts]
+    pub royalty_accounts: Vec<AccountMeta>,
+}
+
+/// An nft sold by [sell_nfts]
+#[derive(Clone, Debug, PartialEq)]
+pub struct SellItem {
+    /// Mint of the nft
+    pub nft_token_mint: Pubkey,
+    /// Mint allowlist proof, empty when the pair has no allowlist
+    pub proof: Vec<[u8; 32]>,
+    /// Creator accounts when royalties are owed, see [crate::royalty::creator_accounts]
+    pub royalty_accounts: Vec<AccountMeta>,
+}
+
+fn instruction(
+    accounts: impl ToAccountMetas,
+    data: impl InstructionData,
+    remaining_accounts: Vec<AccountMeta>,
+) -> Instruction {
+    let mut metas = accounts.to_account_metas(None);
+    metas.extend(remaining_accounts);
+
+    Instruction {
+        program_id: nftamm::ID,
+        accounts: metas,
+        data: data.data(),
+    }
+}
+
+fn metadata(mint: &Pubkey) -> Pubkey {
+    find_metadata(mint).0
+}
+
+fn program_as_signer() -> Pubkey {
+    find_program_as_signer().0
+}
+
+/// [nftamm::nftamm::initialize_pair_authority], `pair_authority` must sign
+pub fn initialize_pair_authority(
+    payer: Pubkey,
+    pair_authority: Pubkey,
+    fee_recipient: Pubkey,
+    fees: [u16; 3],
+) -> Instruction {
+    instruction(
+        accounts::InitializePairAuthority {
+            payer,
+            pair_authority,
+            fee_recipient,
+            system_program: system_program::ID,
+        },
+        ix::InitializePairAuthority { fees },
+        vec![],
+    )
+}
+
+/// [nftamm::nftamm::transfer_pair_authority]
+pub fn transfer_pair_authority(
+    payer: Pubkey,
+    pair_authority: Pubkey,
+    pending_authority: Pubkey,
+) -> Instruction {
+    instruction(
+        accounts::TransferPairAuthority {
+            payer,
+            pair_authority,
+            pending_authority,
+        },
+        ix::TransferPairAuthority {},
+        vec![],
+    )
+}
+
+/// [nftamm::nftamm::accept_pair_authority]
+pub fn accept_pair_authority(payer: Pubkey, pair_authority: Pubkey) -> Instruction {
+    instruction(
+        accounts::AcceptPairAuthority {
+            payer,
+            pair_authority,
+        },
+        ix::AcceptPairAuthority {},
+        vec![],
+    )
+}
+
+/// [nftamm::nftamm::change_protocol_fee]
+pub fn change_protocol_fee(
+    payer: Pubkey,
+    pair_authority: Pubkey,
+    pair_type: u8,
+    new_fee: u16,
+) -> Instruction {
+    instruction(
+        accounts::ChangeProtocolFee {
+            payer,
+            pair_authority,
+        },
+        ix::ChangeProtocolFee { pair_type, new_fee },
+        vec![],
+    )
+}
+
+/// [nftamm::nftamm::change_fee_recipient]
+pub fn change_fee_recipient(
+    payer: Pubkey,
+    pair_authority: Pubkey,
+    new_fee_recipient: Pubkey,
+) -> Instruction {
+    instruction(
+        accounts::ChangeFeeRecipient {
+            payer,
+            pair_authority,
+            new_fee_recipient,
+        },
+        ix::ChangeFeeRecipient {},
+        vec![],
+    )
+}
+
+/// [nftamm::nftamm::set_protocol_fee_timelock]
+pub fn set_protocol_fee_timelock(
+    payer: Pubkey,
+    pair_authority: Pubkey,
+    new_timelock_slots: u64,
+) -> Instruction {
+    instruction(
+        accounts::SetProtocolFeeTimelock {
+            payer,
+            pair_authority,
+        },
+        ix::SetProtocolFeeTimelock { new_timelock_slots },
+        vec![],
+    )
+}
+
+/// [nftamm::nftamm::queue_protocol_fee_change]
+pub fn queue_protocol_fee_change(
+    payer: Pubkey,
+    pair_authority: Pubkey,
+    new_fees: Option<[u16; 3]>,
+    new_fee_recipient: Option<Pubkey>,
+    new_timelock_slots: Option<u64>,
+) -> Instruction {
+    instruction(
+        accounts::QueueProtocolFeeChange {
+            payer,
+            pair_authority,
+        },
+        ix::QueueProtocolFeeChange {
+            new_fees,
+            new_fee_recipient,
+            new_timelock_slots,
+        },
+        vec![],
+    )
+}
+
+/// [nftamm::nftamm::apply_protocol_fee_change]
+pub fn apply_protocol_fee_change(payer: Pubkey, pair_authority: Pubkey) -> Instruction {
+    instruction(
+        accounts::ApplyProtocolFeeChange {
+            payer,
+            pair_authority,
+        },
+        ix::ApplyProtocolFeeChange {},
+        vec![],
+    )
+}
+
+/// [nftamm::nftamm::cancel_protocol_fee_change]
+pub fn cancel_protocol_fee_change(payer: Pubkey, pair_authority: Pubkey) -> Instruction {
+    instruction(
+        accounts::CancelProtocolFeeChange {
+            payer,
+            pair_authority,
+        },
+        ix::CancelProtocolFeeChange {},
+        vec![],
+    )
+}
+
+/// [nftamm::nftamm::initialize_protocol_fee_vault]
+pub fn initialize_protocol_fee_vault(
+    payer: Pubkey,
+    pair_authority: Pubkey,
+    quote_token_mint: Pubkey,
+) -> Instruction {
+    instruction(
+        accounts::InitializeProtocolFeeVault {
+            payer,
+            pair_authority,
+            quote_token_mint,
+            protocol_fee_vault: find_protocol_fee_vault(&pair_authority, &quote_token_mint).0,
+            program_as_signer: program_as_signer(),
+            system_program: system_program::ID,
+            token_program: anchor_spl::token::ID,
+            rent: sysvar::rent::ID,
+        },
+        ix::InitializeProtocolFeeVault {},
+        vec![],
+    )
+}
+
+/// [nftamm::nftamm::initialize_protocol_sol_fee_vault]
+pub fn initialize_protocol_sol_fee_vault(payer: Pubkey, pair_authority: Pubkey) -> Instruction {
+    instruction(
+        accounts::InitializeProtocolSolFeeVault {
+            payer,
+            pair_authority,
+            protocol_sol_fee_vault: find_protocol_sol_fee_vault(&pair_authority).0,
+            system_program: system_program::ID,
+            rent: sysvar::rent::ID,
+        },
+        ix::InitializeProtocolSolFeeVault {},
+        vec![],
+    )
+}
+
+/// [nftamm::nftamm::withdraw_protocol_fees]
+pub fn withdraw_protocol_fees(
+    payer: Pubkey,
+    pair_authority: Pubkey,
+    fee_recipient: Pubkey,
+    quote_token_mint: Pubkey,
+    amount: u64,
+) -> Instruction {
+    instruction(
+        accounts::WithdrawProtocolFees {
+            payer,
+            pair_authority,
+            fee_recipient,
+            fee_recipient_quote_token_account: associated_token_address(
+                &fee_recipient,
+                &quote_token_mint,
+            ),
+            quote_token_mint,
+            protocol_fee_vault: find_protocol_fee_vault(&pair_authority, &quote_token_mint).0,
+            program_as_signer: program_as_signer(),
+            system_program: system_program::ID,
+            token_program: anchor_spl::token::ID,
+            associated_token_program: anchor_spl::associated_token::ID,
+            rent: sysvar::rent::ID,
+        },
+        ix::WithdrawProtocolFees { amount },
+        vec![],
+    )
+}
+
+/// [nftamm::nftamm::withdraw_protocol_sol_fees]
+pub fn withdraw_protocol_sol_fees(
+    payer: Pubkey,
+    pair_authority: Pubkey,
+    fee_recipient: Pubkey,
+    amount: u64,
+) -> Instruction {
+    instruction(
+        accounts::WithdrawProtocolSolFees {
+            payer,
+            pair_authority,
+            fee_recipient,
+            protocol_sol_fee_vault: find_protocol_sol_fee_vault(&pair_authority).0,
+            system_program: system_program::ID,
+        },
+        ix::WithdrawProtocolSolFees { amount },
+        vec![],
+    )
+}
+
+/// [nftamm::nftamm::initialize_pair], `pair` must sign
+pub fn initialize_pair(
+    payer: Pubkey,
+    pair: Pubkey,
+    pair_authority: Pubkey,
+    nft_collection_mint: Pubkey,
+    quote_token_mint: Pubkey,
+    params: PairParams,
+) -> Instruction {
+    instruction(
+        accounts::InitializePair {
+            payer,
+            pair,
+            pair_authority,
+            nft_collection_mint,
+            nft_collection_metadata: metadata(&nft_collection_mint),
+            quote_token_mint,
+            quote_token_vault: find_quote_token_vault(&pair).0,
+            fee_vault: find_quote_fee_vault(&pair).0,
+            protocol_fee_vault: find_protocol_fee_vault(&pair_authority, &quote_token_mint).0,
+            system_program: system_program::ID,
+            token_program: anchor_spl::token::ID,
+            rent: sysvar::rent::ID,
+            program_as_signer: program_as_signer(),
+        },
+        ix::InitializePair {
+            pair_type: params.pair_type,
+            bonding_curve: params.bonding_curve,
+            delta: params.delta,
+            fee: params.fee,
+            spot_price: params.spot_price,
+            royalty_policy: params.royalty_policy,
+            royalty_bps: params.royalty_bps,
+            mint_allowlist_root: params.mint_allowlist_root,
+            collection_mode: params.collection_mode,
+        },
+        vec![],
+    )
+}
+
+/// [nftamm::nftamm::initialize_sol_pair], `pair` must sign
+pub fn initialize_sol_pair(
+    payer: Pubkey,
+    pair: Pubkey,
+    pair_authority: Pubkey,
+    nft_collection_mint: Pubkey,
+    params: PairParams,
+) -> Instruction {
+    instruction(
+        accounts::InitializeSolPair {
+            payer,
+            pair,
+            pair_authority,
+            nft_collection_mint,
+            nft_collection_metadata: metadata(&nft_collection_mint),
+            sol_vault: find_sol_vault(&pair).0,
+            sol_fee_vault: find_sol_fee_vault(&pair).0,
+            protocol_sol_fee_vault: find_protocol_sol_fee_vault(&pair_authority).0,
+            system_program: system_program::ID,
+            rent: sysvar::rent::ID,
+        },
+        ix::InitializeSolPair {
+            pair_type: params.pair_type,
+            bonding_curve: params.bonding_curve,
+            delta: params.delta,
+            fee: params.fee,
+            spot_price: params.spot_price,
+            royalty_policy: params.royalty_policy,
+            royalty_bps: params.royalty_bps,
+            mint_allowlist_root: params.mint_allowlist_root,
+            collection_mode: params.collection_mode,
+        },
+        vec![],
+    )
+}
+
+/// [nftamm::nftamm::fund_token_pair], from the payer's quote token ATA
+pub fn fund_token_pair(
+    payer: Pubkey,
+    pair_key: Pubkey,
+    pair: &Pair,
+    amount_to_send: u64,
+) -> Instruction {
+    instruction(
+        accounts::FundTokenPair {
+            payer,
+            pair_authority: pair.pair_authority,
+            pair: pair_key,
+            quote_token_mint: pair.quote_token_mint,
+            quote_token_vault: pair.quote_token_vault,
+            owner_quote_token_account: associated_token_address(&payer, &pair.quote_token_mint),
+            program_as_signer: program_as_signer(),
+            token_program: anchor_spl::token::ID,
+        },
+        ix::FundTokenPair { amount_to_send },
+        vec![],
+    )
+}
+
+/// [nftamm::nftamm::fund_sol_pair]
+pub fn fund_sol_pair(
+    payer: Pubkey,
+    pair_key: Pubkey,
+    pair: &Pair,
+    amount_to_send: u64,
+) -> Instruction {
+    instruction(
+        accounts::FundSolPair {
+            payer,
+            pair_authority: pair.pair_authority,
+            pair: pair_key,
+            sol_vault: pair.quote_token_vault,
+            system_program: system_program::ID,
+        },
+        ix::FundSolPair { amount_to_send },
+        vec![],
+    )
+}
+
+/// [nftamm::nftamm::fund_nft_pair], from the payer's nft ATA
+pub fn fund_nft_pair(
+    payer: Pubkey,
+    pair_key: Pubkey,
+    pair: &Pair,
+    nft_token_mint: Pubkey,
+    proof: Vec<[u8; 32]>,
+) -> Instruction {
+    instruction(
+        accounts::FundNftPair {
+            payer,
+            pair: pair_key,
+            pair_metadata: find_pair_metadata(&pair_key, &nft_token_mint).0,
+            nft_collection_mint: pair.collection_mint,
+            nft_collection_metadata: metadata(&pair.collection_mint),
+            nft_token_mint,
+            nft_token_metadata: metadata(&nft_token_mint),
+            owner_nft_token_account: associated_token_address(&payer, &nft_token_mint),
+            nft_token_vault: find_nft_token_vault(&pair_key, &nft_token_mint).0,
+            program_as_signer: program_as_signer(),
+            system_program: system_program::ID,
+            token_program: anchor_spl::token::ID,
+            rent: sysvar::rent::ID,
+        },
+        ix::FundNftPair { proof },
+        vec![],
+    )
+}
+
+/// [nftamm::nftamm::trade_token_pair], selling the nft in the payer's ATA
+#[allow(clippy::too_many_arguments)]
+pub fn trade_token_pair(
+    payer: Pubkey,
+    pair_key: Pubkey,
+    pair: &Pair,
+    nft_token_mint: Pubkey,
+    min_price: u64,
+    expected_trade_count: Option<u64>,
+    proof: Vec<[u8; 32]>,
+    royalty_accounts: Vec<AccountMeta>,
+) -> Instruction {
+    instruction(
+        accounts::TradeTokenPair {
+            payer,
+            pair_authority: pair.pair_authority,
+            protocol_fee_vault: find_protocol_fee_vault(
+                &pair.pair_authority,
+                &pair.quote_token_mint,
+            )
+            .0,
+            pair: pair_key,
+            pair_metadata: find_pair_metadata(&pair_key, &nft_token_mint).0,
+            nft_collection_mint: pair.collection_mint,
+            nft_collection_metadata: metadata(&pair.collection_mint),
+            nft_token_mint,
+            nft_token_metadata: metadata(&nft_token_mint),
+            nft_token_vault: find_nft_token_vault(&pair_key, &nft_token_mint).0,
+            user_nft_token_account: associated_token_address(&payer, &nft_token_mint),
+            quote_token_mint: pair.quote_token_mint,
+            quote_token_vault: pair.quote_token_vault,
+            user_quote_token_account: associated_token_address(&payer, &pair.quote_token_mint),
+            program_as_signer: program_as_signer(),
+            system_program: system_program::ID,
+            token_program: anchor_spl::token::ID,
+            associated_token_program: anchor_spl::associated_token::ID,
+            rent: sysvar::rent::ID,
+        },
+        ix::TradeTokenPair {
+            min_price,
+            expected_trade_count,
+            proof,
+        },
+        royalty_accounts,
+    )
+}
+
+/// [nftamm::nftamm::trade_nft_pair], paying from the payer's quote token ATA
+#[allow(clippy::too_many_arguments)]
+pub fn trade_nft_pair(
+    payer: Pubkey,
+    pair_key: Pubkey,
+    pair: &Pair,
+    nft_token_mint: Pubkey,
+    pair_metadata_creator: Pubkey,
+    max_price: u64,
+    expected_trade_count: Option<u64>,
+    royalty_accounts: Vec<AccountMeta>,
+) -> Instruction {
+    instruction(
+        accounts::TradeNftPair {
+            payer,
+            pair_authority: pair.pair_authority,
+            protocol_fee_vault: find_protocol_fee_vault(
+                &pair.pair_authority,
+                &pair.quote_token_mint,
+            )
+            .0,
+            pair: pair_key,
+            pair_metadata_creator,
+            pair_metadata: find_pair_metadata(&pair_key, &nft_token_mint).0,
+            nft_collection_mint: pair.collection_mint,
+            nft_collection_metadata: metadata(&pair.collection_mint),
+            nft_token_mint,
+            nft_token_metadata: metadata(&nft_token_mint),
+            nft_token_vault: find_nft_token_vault(&pair_key, &nft_token_mint).0,
+            user_nft_token_account: associated_token_address(&payer, &nft_token_mint),
+            quote_token_mint: pair.quote_token_mint,
+            quote_token_vault: pair.quote_token_vault,
+            user_quote_token_account: associated_token_address(&payer, &pair.quote_token_mint),
+            program_as_signer: program_as_signer(),
+            system_program: system_program::ID,
+            token_program: anchor_spl::token::ID,
+            associated_token_program: anchor_spl::associated_token::ID,
+            rent: sysvar::rent::ID,
+        },
+        ix::TradeNftPair {
+            max_price,
+            expected_trade_count,
+        },
+        royalty_accounts,
+    )
+}
+
+/// [nftamm::nftamm::swap_token_trade_pair], paying from the payer's quote token ATA
+#[allow(clippy::too_many_arguments)]
+pub fn swap_token_trade_pair(
+    payer: Pubkey,
+    pair_key: Pubkey,
+    pair: &Pair,
+    nft_token_mint: Pubkey,
+    pair_metadata_creator: Pubkey,
+    max_price: u64,
+    expected_trade_count: Option<u64>,
+    royalty_accounts: Vec<AccountMeta>,
+) -> Instruction {
+    instruction(
+        accounts::SwapTokenTradePair {
+            payer,
+            pair_authority: pair.pair_authority,
+            protocol_fee_vault: find_protocol_fee_vault(
+                &pair.pair_authority,
+                &pair.quote_token_mint,
+            )
+            .0,
+            pair: pair_key,
+            pair_metadata_creator,
+            pair_metadata: find_pair_metadata(&pair_key, &nft_token_mint).0,
+            nft_collection_mint: pair.collection_mint,
+            nft_collection_metadata: metadata(&pair.collection_mint),
+            nft_token_mint,
+            nft_token_metadata: metadata(&nft_token_mint),
+            nft_token_vault: find_nft_token_vault(&pair_key, &nft_token_mint).0,
+            user_nft_token_account: associated_token_address(&payer, &nft_token_mint),
+            quote_token_mint: pair.quote_token_mint,
+            quote_token_vault: pair.quote_token_vault,
+            quote_fee_vault: pair.fee_vault,
+            user_quote_token_account: associated_token_address(&payer, &pair.quote_token_mint),
+            program_as_signer: program_as_signer(),
+            system_program: system_program::ID,
+            token_program: anchor_spl::token::ID,
+            associated_token_program: anchor_spl::associated_token::ID,
+            rent: sysvar::rent::ID,
+        },
+        ix::SwapTokenTradePair {
+            max_price,
+            expected_trade_count,
+        },
+        royalty_accounts,
+    )
+}
+
+/// [nftamm::nftamm::swap_nft_trade_pair], selling the nft in the payer's ATA
+#[allow(clippy::too_many_arguments)]
+pub fn swap_nft_trade_pair(
+    payer: Pubkey,
+    pair_key: Pubkey,
+    pair: &Pair,
+    nft_token_mint: Pubkey,
+    min_price: u64,
+    expected_trade_count: Option<u64>,
+    proof: Vec<[u8; 32]>,
+    royalty_accounts: Vec<AccountMeta>,
+) -> Instruction {
+    instruction(
+        accounts::SwapNftTradePair {
+            payer,
+            pair_authority: pair.pair_authority,
+            protocol_fee_vault: find_protocol_fee_vault(
+                &pair.pair_authority,
+                &pair.quote_token_mint,
+            )
+            .0,
+            pair: pair_key,
+            pair_metadata: find_pair_metadata(&pair_key, &nft_token_mint).0,
+            nft_collection_mint: pair.collection_mint,
+            nft_collection_metadata: metadata(&pair.collection_mint),
+            nft_token_mint,
+            nft_token_metadata: metadata(&nft_token_mint),
+            nft_token_vault: find_nft_token_vault(&pair_key, &nft_token_mint).0,
+            user_nft_token_account: associated_token_address(&payer, &nft_token_mint),
+            quote_token_mint: pair.quote_token_mint,
+            quote_token_vault: pair.quote_token_vault,
+            quote_fee_vault: pair.fee_vault,
+            user_quote_token_account: associated_token_address(&payer, &pair.quote_token_mint),
+            program_as_signer: program_as_signer(),
+            system_program: system_program::ID,
+            token_program: anchor_spl::token::ID,
+            associated_token_program: anchor_spl::associated_token::ID,
+            rent: sysvar::rent::ID,
+        },
+        ix::SwapNftTradePair {
+            min_price,
+            expected_trade_count,
+            proof,
+        },
+        royalty_accounts,
+    )
+}
+
+/// [nftamm::nftamm::buy_nfts], paying from the payer's quote token ATA into the payer's nft ATAs
+pub fn buy_nfts(
+    payer: Pubkey,
+    pair_key: Pubkey,
+    pair: &Pair,
+    items: &[BuyItem],
+    max_total_input: u64,
+) -> Instruction {
+    let remaining_accounts = items
+        .iter()
+        .flat_map(|item| {
+            let mint = item.nft_token_mint;
+            let mut group = vec![
+                AccountMeta::new_readonly(mint, false),
+                AccountMeta::new_readonly(metadata(&mint), false),
+                AccountMeta::new(find_pair_metadata(&pair_key, &mint).0, false),
+                AccountMeta::new(item.pair_metadata_creator, false),
+                AccountMeta::new(find_nft_token_vault(&pair_key, &mint).0, false),
+                AccountMeta::new(associated_token_address(&payer, &mint), false),
+            ];
+            group.extend(item.royalty_accounts.iter().cloned());
+            group
+        })
+        .collect();
+
+    instruction(
+        accounts::BuyNfts {
+            payer,
+            pair_authority: pair.pair_authority,
+            protocol_fee_vault: find_protocol_fee_vault(
+                &pair.pair_authority,
+                &pair.quote_token_mint,
+            )
+            .0,
+            pair: pair_key,
+            quote_token_mint: pair.quote_token_mint,
+            quote_token_vault: pair.quote_token_vault,
+            quote_fee_vault: pair.fee_vault,
+            user_quote_token_account: associated_token_address(&payer, &pair.quote_token_mint),
+            program_as_signer: program_as_signer(),
+            system_program: system_program::ID,
+            token_program: anchor_spl::token::ID,
+            associated_token_program: anchor_spl::associated_token::ID,
+            rent: sysvar::rent::ID,
+        },
+        ix::BuyNfts { max_total_input },
+        remaining_accounts,
+    )
+}
+
+/// [nftamm::nftamm::sell_nfts], selling the nfts in the payer's ATAs
+pub fn sell_nfts(
+    payer: Pubkey,
+    pair_key: Pubkey,
+    pair: &Pair,
+    items: &[SellItem],
+    min_total_output: u64,
+) -> Instruction {
+    let remaining_accounts = items
+        .iter()
+        .flat_map(|item| {
+            let mint = item.nft_token_mint;
+            let mut group = vec![
+                AccountMeta::new_readonly(mint, false),
+                AccountMeta::new_readonly(metadata(&mint), false),
+                AccountMeta::new(find_pair_metadata(&pair_key, &mint).0, false),
+                AccountMeta::new(find_nft_token_vault(&pair_key, &mint).0, false),
+                AccountMeta::new(associated_token_address(&payer, &mint), false),
+            ];
+            group.extend(item.royalty_accounts.iter().cloned());
+            group
+        })
+        .collect();
+
+    // Proofs can be left out entirely for pairs without an allowlist
+    let proofs = if pair.mint_allowlist_root.is_some() {
+        items.iter().map(|item| item.proof.clone()).collect()
+    } else {
+        vec![]
+    };
+
+    instruction(
+        accounts::SellNfts {
+            payer,
+            pair_authority: pair.pair_authority,
+            protocol_fee_vault: find_protocol_fee_vault(
+                &pair.pair_authority,
+                &pair.quote_token_mint,
+            )
+            .0,
+            pair: pair_key,
+            nft_collection_mint: pair.collection_mint,
+            nft_collection_metadata: metadata(&pair.collection_mint),
+            quote_token_mint: pair.quote_token_mint,
+            quote_token_vault: pair.quote_token_vault,
+            user_quote_token_account: associated_token_address(&payer, &pair.quote_token_mint),
+            program_as_signer: program_as_signer(),
+            system_program: system_program::ID,
+            token_program: anchor_spl::token::ID,
+            associated_token_program: anchor_spl::associated_token::ID,
+            rent: sysvar::rent::ID,
+        },
+        ix::SellNfts {
+            min_total_output,
+            proofs,
+        },
+        remaining_accounts,
+    )
+}
+
+/// [nftamm::nftamm::buy_nft_with_sol], into the payer's nft ATA
+#[allow(clippy::too_many_arguments)]
+pub fn buy_nft_with_sol(
+    payer: Pubkey,
+    pair_key: Pubkey,
+    pair: &Pair,
+    nft_token_mint: Pubkey,
+    pair_metadata_creator: Pubkey,
+    max_price: u64,
+    expected_trade_count: Option<u64>,
+    royalty_accounts: Vec<AccountMeta>,
+) -> Instruction {
+    instruction(
+        accounts::BuyNftWithSol {
+            payer,
+            pair_authority: pair.pair_authority,
+            protocol_sol_fee_vault: find_protocol_sol_fee_vault(&pair.pair_authority).0,
+            pair: pair_key,
+            pair_metadata_creator,
+            pair_metadata: find_pair_metadata(&pair_key, &nft_token_mint).0,
+            nft_collection_mint: pair.collection_mint,
+            nft_collection_metadata: metadata(&pair.collection_mint),
+            nft_token_mint,
+            nft_token_metadata: metadata(&nft_token_mint),
+            nft_token_vault: find_nft_token_vault(&pair_key, &nft_token_mint).0,
+            user_nft_token_account: associated_token_address(&payer, &nft_token_mint),
+            sol_vault: pair.quote_token_vault,
+            sol_fee_vault: pair.fee_vault,
+            program_as_signer: program_as_signer(),
+            system_program: system_program::ID,
+            token_program: anchor_spl::token::ID,
+            associated_token_program: anchor_spl::associated_token::ID,
+            rent: sysvar::rent::ID,
+        },
+        ix::BuyNftWithSol {
+            max_price,
+            expected_trade_count,
+        },
+        royalty_accounts,
+    )
+}
+
+/// [nftamm::nftamm::sell_nft_for_sol], selling the nft in the payer's ATA
+#[allow(clippy::too_many_arguments)]
+pub fn sell_nft_for_sol(
+    payer: Pubkey,
+    pair_key: Pubkey,
+    pair: &Pair,
+    nft_token_mint: Pubkey,
+    min_price: u64,
+    expected_trade_count: Option<u64>,
+    proof: Vec<[u8; 32]>,
+    royalty_accounts: Vec<AccountMeta>,
+) -> Instruction {
+    instruction(
+        accounts::SellNftForSol {
+            payer,
+            pair_authority: pair.pair_authority,
+            protocol_sol_fee_vault: find_protocol_sol_fee_vault(&pair.pair_authority).0,
+            pair: pair_key,
+            pair_metadata: find_pair_metadata(&pair_key, &nft_token_mint).0,
+            nft_collection_mint: pair.collection_mint,
+            nft_collection_metadata: metadata(&pair.collection_mint),
+            nft_token_mint,
+            nft_token_metadata: metadata(&nft_token_mint),
+            nft_token_vault: find_nft_token_vault(&pair_key, &nft_token_mint).0,
+            user_nft_token_account: associated_token_address(&payer, &nft_token_mint),
+            sol_vault: pair.quote_token_vault,
+            program_as_signer: program_as_signer(),
+            system_program: system_program::ID,
+            token_program: anchor_spl::token::ID,
+            rent: sysvar::rent::ID,
+        },
+        ix::SellNftForSol {
+            min_price,
+            expected_trade_count,
+            proof,
+        },
+        royalty_accounts,
+    )
+}
+
+/// [nftamm::nftamm::quote_buy], meant to be simulated for its return data
+pub fn quote_buy(pair_key: Pubkey, pair: &Pair, n: u64, royalty_bps: u16) -> Instruction {
+    instruction(
+        accounts::QuoteBuy {
+            pair_authority: pair.pair_authority,
+            pair: pair_key,
+            quote_token_vault: pair.quote_token_vault,
+        },
+        ix::QuoteBuy { n, royalty_bps },
+        vec![],
+    )
+}
+
+/// [nftamm::nftamm::quote_sell], meant to be simulated for its return data
+pub fn quote_sell(pair_key: Pubkey, pair: &Pair, n: u64, royalty_bps: u16) -> Instruction {
+    instruction(
+        accounts::QuoteSell {
+            pair_authority: pair.pair_authority,
+            pair: pair_key,
+            quote_token_vault: pair.quote_token_vault,
+        },
+        ix::QuoteSell { n, royalty_bps },
+        vec![],
+    )
+}
+
+/// [nftamm::nftamm::change_bonding_curve]
+pub fn change_bonding_curve(
+    payer: Pubkey,
+    pair: Pubkey,
+    new_bonding_curve: u8,
+    new_delta: u64,
+    new_spot_price: u64,
+) -> Instruction {
+    instruction(
+        accounts::ChangeBondingCurve { payer, pair },
+        ix::ChangeBondingCurve {
+            new_bonding_curve,
+            new_delta,
+            new_spot_price,
+        },
+        vec![],
+    )
+}
+
+/// [nftamm::nftamm::change_delta]
+pub fn change_delta(payer: Pubkey, pair: Pubkey, new_delta: u64) -> Instruction {
+    instruction(
+        accounts::ChangeDelta { payer, pair },
+        ix::ChangeDelta { new_delta },
+        vec![],
+    )
+}
+
+/// [nftamm::nftamm::change_fee]
+pub fn change_fee(payer: Pubkey, pair: Pubkey, new_fee: u16) -> Instruction {
+    instruction(
+        accounts::ChangeFee { payer, pair },
+        ix::ChangeFee { new_fee },
+        vec![],
+    )
+}
+
+/// [nftamm::nftamm::change_mint_allowlist_root]
+pub fn change_mint_allowlist_root(
+    payer: Pubkey,
+    pair: Pubkey,
+    new_mint_allowlist_root: Option<[u8; 32]>,
+) -> Instruction {
+    instruction(
+        accounts::ChangeMintAllowlistRoot { payer, pair },
+        ix::ChangeMintAllowlistRoot {
+            new_mint_allowlist_root,
+        },
+        vec![],
+    )
+}
+
+/// [nftamm::nftamm::update_pair_params]
+#[allow(clippy::too_many_arguments)]
+pub fn update_pair_params(
+    payer: Pubkey,
+    pair: Pubkey,
+    new_bonding_curve: Option<u8>,
+    new_delta: Option<u64>,
+    new_fee: Option<u16>,
+    new_spot_price: Option<u64>,
+    new_royalty_policy: Option<u8>,
+    new_royalty_bps: Option<u16>,
+) -> Instruction {
+    instruction(
+        accounts::UpdatePairParams { payer, pair },
+        ix::UpdatePairParams {
+            new_bonding_curve,
+            new_delta,
+            new_fee,
+            new_spot_price,
+            new_royalty_policy,
+            new_royalty_bps,
+        },
+        vec![],
+    )
+}
+
+/// [nftamm::nftamm::change_royalty_policy]
+pub fn change_royalty_policy(
+    payer: Pubkey,
+    pair: Pubkey,
+    new_royalty_policy: u8,
+    new_royalty_bps: u16,
+) -> Instruction {
+    instruction(
+        accounts::ChangeRoyaltyPolicy { payer, pair },
+        ix::ChangeRoyaltyPolicy {
+            new_royalty_policy,
+            new_royalty_bps,
+        },
+        vec![],
+    )
+}
+
+/// [nftamm::nftamm::change_spot_price]
+pub fn change_spot_price(payer: Pubkey, pair: Pubkey, new_spot_price: u64) -> Instruction {
+    instruction(
+        accounts::ChangeSpotPrice { payer, pair },
+        ix::ChangeSpotPrice { new_spot_price },
+        vec![],
+    )
+}
+
+/// [nftamm::nftamm::set_pair_timelock]
+pub fn set_pair_timelock(
+    payer: Pubkey,
+    pair: Pubkey,
+    new_timelock_slots: u64,
+    halt_trades_while_pending: bool,
+) -> Instruction {
+    instruction(
+        accounts::SetPairTimelock { payer, pair },
+        ix::SetPairTimelock {
+            new_timelock_slots,
+            halt_trades_while_pending,
+        },
+        vec![],
+    )
+}
+
+/// [nftamm::nftamm::set_spot_price_clamp]
+pub fn set_spot_price_clamp(payer: Pubkey, pair: Pubkey, clamp_spot_price: bool) -> Instruction {
+    instruction(
+        accounts::SetSpotPriceClamp { payer, pair },
+        ix::SetSpotPriceClamp { clamp_spot_price },
+        vec![],
+    )
+}
+
+/// [nftamm::nftamm::queue_pair_change]
+#[allow(clippy::too_many_arguments)]
+pub fn queue_pair_change(
+    payer: Pubkey,
+    pair: Pubkey,
+    new_bonding_curve: Option<u8>,
+    new_delta: Option<u64>,
+    new_fee: Option<u16>,
+    new_spot_price: Option<u64>,
+    new_royalty_policy: Option<u8>,
+    new_royalty_bps: Option<u16>,
+    new_mint_allowlist_root: Option<Option<[u8; 32]>>,
+    new_timelock_slots: Option<u64>,
+) -> Instruction {
+    instruction(
+        accounts::QueuePairChange { payer, pair },
+        ix::QueuePairChange {
+            new_bonding_curve,
+            new_delta,
+            new_fee,
+            new_spot_price,
+            new_royalty_policy,
+            new_royalty_bps,
+            new_mint_allowlist_root,
+            new_timelock_slots,
+        },
+        vec![],
+    )
+}
+
+/// [nftamm::nftamm::apply_pair_change]
+pub fn apply_pair_change(payer: Pubkey, pair: Pubkey) -> Instruction {
+    instruction(
+        accounts::ApplyPairChange { payer, pair },
+        ix::ApplyPairChange {},
+        vec![],
+    )
+}
+
+/// [nftamm::nftamm::cancel_pair_change]
+pub fn cancel_pair_change(payer: Pubkey, pair: Pubkey) -> Instruction {
+    instruction(
+        accounts::CancelPairChange { payer, pair },
+        ix::CancelPairChange {},
+        vec![],
+    )
+}
+
+/// [nftamm::nftamm::close_pair]
+pub fn close_pair(payer: Pubkey, pair_key: Pubkey, pair: &Pair) -> Instruction {
+    instruction(
+        accounts::ClosePair {
+            payer,
+            pair: pair_key,
+            pair_owner: pair.owner,
+        },
+        ix::ClosePair {},
+        vec![],
+    )
+}
+
+/// [nftamm::nftamm::close_sol_pair]
+pub fn close_sol_pair(payer: Pubkey, pair: Pubkey) -> Instruction {
+    instruction(
+        accounts::CloseSolPair {
+            payer,
+            pair,
+            sol_vault: find_sol_vault(&pair).0,
+            sol_fee_vault: find_sol_fee_vault(&pair).0,
+            system_program: system_program::ID,
+        },
+        ix::CloseSolPair {},
+        vec![],
+    )
+}
+
+/// [nftamm::nftamm::withdraw_nft], into the payer's nft ATA
+pub fn withdraw_nft(
+    payer: Pubkey,
+    pair_key: Pubkey,
+    pair: &Pair,
+    nft_token_mint: Pubkey,
+    pair_metadata_creator: Pubkey,
+) -> Instruction {
+    instruction(
+        accounts::WithdrawNft {
+            payer,
+            pair: pair_key,
+            pair_metadata_creator,
+            pair_metadata: find_pair_metadata(&pair_key, &nft_token_mint).0,
+            nft_collection_mint: pair.collection_mint,
+            nft_collection_metadata: metadata(&pair.collection_mint),
+            nft_token_mint,
+            nft_token_metadata: metadata(&nft_token_mint),
+            owner_nft_token_account: associated_token_address(&payer, &nft_token_mint),
+            nft_token_vault: find_nft_token_vault(&pair_key, &nft_token_mint).0,
+            program_as_signer: program_as_signer(),
+            system_program: system_program::ID,
+            token_program: anchor_spl::token::ID,
+            associated_token_program: anchor_spl::associated_token::ID,
+            rent: sysvar::rent::ID,
+        },
+        ix::WithdrawNft {},
+        vec![],
+    )
+}
+
+/// [nftamm::nftamm::withdraw_quote_token], into the payer's quote token ATA
+pub fn withdraw_quote_token(
+    payer: Pubkey,
+    pair_key: Pubkey,
+    pair: &Pair,
+    amount_to_withdraw: u64,
+) -> Instruction {
+    instruction(
+        accounts::WithdrawQuoteToken {
+            payer,
+            pair_authority: pair.pair_authority,
+            pair: pair_key,
+            quote_token_mint: pair.quote_token_mint,
+            quote_token_vault: pair.quote_token_vault,
+            owner_quote_token_account: associated_token_address(&payer, &pair.quote_token_mint),
+            program_as_signer: program_as_signer(),
+            token_program: anchor_spl::token::ID,
+            system_program: system_program::ID,
+            associated_token_program: anchor_spl::associated_token::ID,
+            rent: sysvar::rent::ID,
+        },
+        ix::WithdrawQuoteToken { amount_to_withdraw },
+        vec![],
+    )
+}
+
+/// [nftamm::nftamm::withdraw_fee], into the pair owner's quote token ATA
+pub fn withdraw_fee(payer: Pubkey, pair_key: Pubkey, pair: &Pair, amount: u64) -> Instruction {
+    instruction(
+        accounts::WithdrawFee {
+            payer,
+            pair: pair_key,
+            pair_owner: pair.owner,
+            pair_owner_quote_token_account: associated_token_address(
+                &pair.owner,
+                &pair.quote_token_mint,
+            ),
+            quote_token_mint: pair.quote_token_mint,
+            quote_fee_vault: pair.fee_vault,
+            program_as_signer: program_as_signer(),
+            system_program: system_program::ID,
+            token_program: anchor_spl::token::ID,
+            associated_token_program: anchor_spl::associated_token::ID,
+            rent: sysvar::rent::ID,
+        },
+        ix::WithdrawFee { amount },
+        vec![],
+    )
+}
+
+/// [nftamm::nftamm::withdraw_sol]
+pub fn withdraw_sol(
+    payer: Pubkey,
+    pair_key: Pubkey,
+    pair: &Pair,
+    amount_to_withdraw: u64,
+) -> Instruction {
+    instruction(
+        accounts::WithdrawSol {
+            payer,
+            pair_authority: pair.pair_authority,
+            pair: pair_key,
+            sol_vault: pair.quote_token_vault,
+            system_program: system_program::ID,
+        },
+        ix::WithdrawSol { amount_to_withdraw },
+        vec![],
+    )
+}
+
+/// [nftamm::nftamm::withdraw_sol_fee]
+pub fn withdraw_sol_fee(payer: Pubkey, pair: Pubkey, amount: u64) -> Instruction {
+    instruction(
+        accounts::WithdrawSolFee {
+            payer,
+            pair,
+            sol_fee_vault: find_sol_fee_vault(&pair).0,
+            system_program: system_program::ID,
+        },
+        ix::WithdrawSolFee { amount },
+        vec![],
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use anchor_lang::AnchorDeserialize;
+
+    fn pair(mint_allowlist_root: Option<[u8; 32]>) -> Pair {
+        let pair_key = Pubkey::new_unique();
+
+        Pair {
+            pair_authority: Pubkey::new_unique(),
+            owner: Pubkey::new_unique(),
+            collection_mint: Pubkey::new_unique(),
+            collection_mode: 0,
+            mint_allowlist_root,
+            quote_token_mint: Pubkey::new_unique(),
+            quote_token_vault: find_quote_token_vault(&pair_key).0,
+            is_native: false,
+            pair_type: 2,
+            bonding_curve: 0,
+            delta: 0,
+            fee: 0,
+            fee_vault: find_quote_fee_vault(&pair_key).0,
+            spot_price: 0,
+            clamp_spot_price: false,
+            royalty_policy: 1,
+            royalty_bps: 0,
+            trade_count: 0,
+            is_active: true,
+            nfts_held: 0,
+            timelock_slots: 0,
+            halt_trades_while_pending: false,
+            pending_change: None,
+        }
+    }
+
+    fn royalty_accounts() -> Vec<AccountMeta> {
+        vec![
+            AccountMeta::new_readonly(Pubkey::new_unique(), false),
+            AccountMeta::new(Pubkey::new_unique(), false),
+        ]
+    }
+
+    // The accounts after the fixed accounts of an instruction built with no items
+    fn remaining(instruction: &Instruction, empty: &Instruction) -> Vec<AccountMeta> {
+        instruction.accounts[empty.accounts.len()..].to_vec()
+    }
+
+    #[test]
+    fn sell_nfts_groups_accounts_in_program_order() {
+        let payer = Pubkey::new_unique();
+        let pair_key = Pubkey::new_unique();
+        let pair = pair(None);
+        let items: Vec<SellItem> = (0..2)
+            .map(|_| SellItem {
+                nft_token_mint: Pubkey::new_unique(),
+                proof: vec![],
+                royalty_accounts: royalty_accounts(),
+            })
+            .collect();
+
+        let instruction = sell_nfts(payer, pair_key, &pair, &items, 0);
+        let empty = sell_nfts(payer, pair_key, &pair, &[], 0);
+
+        // nft_token_mint, nft_token_metadata, pair_metadata (mut), nft_token_vault (mut) and
+        // user_nft_token_account (mut), followed by the nft's creator accounts
+        let expected: Vec<AccountMeta> = items
+            .iter()
+            .flat_map(|item| {
+                let mint = item.nft_token_mint;
+                let mut group = vec![
+                    AccountMeta::new_readonly(mint, false),
+                    AccountMeta::new_readonly(find_metadata(&mint).0, false),
+                    AccountMeta::new(find_pair_metadata(&pair_key, &mint).0, false),
+                    AccountMeta::new(find_nft_token_vault(&pair_key, &mint).0, false),
+                    AccountMeta::new(associated_token_address(&payer, &mint), false),
+                ];
+                group.extend(item.royalty_accounts.iter().cloned());
+                group
+            })
+            .collect();
+
+        assert_eq!(remaining(&instruction, &empty), expected);
+    }
+
+    #[test]
+    fn buy_nfts_groups_accounts_in_program_order() {
+        let payer = Pubkey::new_unique();
+        let pair_key = Pubkey::new_unique();
+        let pair = pair(None);
+        let items: Vec<BuyItem> = (0..2)
+            .map(|_| BuyItem {
+                nft_token_mint: Pubkey::new_unique(),
+                pair_metadata_creator: Pubkey::new_unique(),
+                royalty_accounts: royalty_accounts(),
+            })
+            .collect();
+
+        let instruction = buy_nfts(payer, pair_key, &pair, &items, u64::MAX);
+        let empty = buy_nfts(payer, pair_key, &pair, &[], u64::MAX);
+
+        // nft_token_mint, nft_token_metadata, pair_metadata (mut), pair_metadata_creator (mut),
+        // nft_token_vault (mut) and user_nft_token_account (mut), followed by the nft's creator
+        // accounts
+        let expected: Vec<AccountMeta> = items
+            .iter()
+            .flat_map(|item| {
+                let mint = item.nft_token_mint;
+                let mut group = vec![
+                    AccountMeta::new_readonly(mint, false),
+                    AccountMeta::new_readonly(find_metadata(&mint).0, false),
+                    AccountMeta::new(find_pair_metadata(&pair_key, &mint).0, false),
+                    AccountMeta::new(item.pair_metadata_creator, false),
+                    AccountMeta::new(find_nft_token_vault(&pair_key, &mint).0, false),
+                    AccountMeta::new(associated_token_address(&payer, &mint), false),
+                ];
+                group.extend(item.royalty_accounts.iter().cloned());
+                group
+            })
+            .collect();
+
+        assert_eq!(remaining(&instruction, &empty), expected);
+    }
+
+    #[test]
+    fn sell_nfts_only_sends_proofs_to_allowlisted_pairs() {
+        let payer = Pubkey::new_unique();
+        let pair_key = Pubkey::new_unique();
+        let items = [SellItem {
+            nft_token_mint: Pubkey::new_unique(),
+            proof: vec![[1; 32]],
+            royalty_accounts: vec![],
+        }];
+
+        let proofs = |pair: &Pair| {
+            let instruction = sell_nfts(payer, pair_key, pair, &items, 0);
+            ix::SellNfts::try_from_slice(&instruction.data[8..])
+                .unwrap()
+                .proofs
+        };
+
+        assert!(proofs(&pair(None)).is_empty());
+        assert_eq!(proofs(&pair(Some([2; 32]))), vec![vec![[1; 32]]]);
+    }
+
+    #[test]
+    fn trades_derive_the_program_pdas() {
+        let payer = Pubkey::new_unique();
+        let pair_key = Pubkey::new_unique();
+        let pair = pair(None);
+        let mint = Pubkey::new_unique();
+        let royalty_accounts = royalty_accounts();
+
+        let instruction = trade_token_pair(
+            payer,
+            pair_key,
+            &pair,
+            mint,
+            0,
+            None,
+            vec![],
+            royalty_accounts.clone(),
+        );
+        let keys: Vec<Pubkey> = instruction
+            .accounts
+            .iter()
+            .map(|meta| meta.pubkey)
+            .collect();
+
+        for pda in [
+            find_protocol_fee_vault(&pair.pair_authority, &pair.quote_token_mint).0,
+            find_pair_metadata(&pair_key, &mint).0,
+            find_nft_token_vault(&pair_key, &mint).0,
+            find_program_as_signer().0,
+            find_metadata(&mint).0,
+            find_metadata(&pair.collection_mint).0,
+        ] {
+            assert!(keys.contains(&pda));
+        }
+
+        // Creator accounts come last, where the program reads them from
+        assert_eq!(
+            instruction.accounts[instruction.accounts.len() - royalty_accounts.len()..],
+            royalty_accounts[..]
+        );
+    }
+}
@@ -0,0 +1,14 @@
+//! Off-chain client for the nftamm program.
+//!
+//! Builds [Instruction]s for every nftamm instruction, derives the program's PDAs and decodes its
+//! accounts, so services do not have to assemble transactions by hand from the IDL.
+//!
+//! [Instruction]: anchor_lang::solana_program::instruction::Instruction
+#![warn(missing_docs)]
+
+pub mod instructions;
+pub mod pda;
+pub mod royalty;
+pub mod state;
+
+pub use nftamm::{curve, error, events, quote, ID};
@@ -0,0 +1,166 @@
+//! Program derived addresses used by nftamm, and the Metaplex accounts it reads.
+
+use anchor_lang::prelude::Pubkey;
+
+/// Vault holding the quote tokens of a pair, `[b"quote", pair]`
+pub fn find_quote_token_vault(pair: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[b"quote", pair.as_ref()], &nftamm::ID)
+}
+
+/// Vault holding the pair fees of a trade pair, `[b"quote", b"fee", pair]`
+pub fn find_quote_fee_vault(pair: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[b"quote", b"fee", pair.as_ref()], &nftamm::ID)
+}
+
+/// Lamport vault of a native sol pair, `[b"sol", pair]`
+pub fn find_sol_vault(pair: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[b"sol", pair.as_ref()], &nftamm::ID)
+}
+
+/// Lamport vault holding the pair fees of a native sol pair, `[b"sol", b"fee", pair]`
+pub fn find_sol_fee_vault(pair: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[b"sol", b"fee", pair.as_ref()], &nftamm::ID)
+}
+
+/// Record of an nft held by a pair, `[b"pair_metadata", pair, mint]`
+pub fn find_pair_metadata(pair: &Pubkey, mint: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(
+        &[b"pair_metadata", pair.as_ref(), mint.as_ref()],
+        &nftamm::ID,
+    )
+}
+
+/// Token account holding an nft of a pair, `[b"nft_account", pair, mint]`
+pub fn find_nft_token_vault(pair: &Pubkey, mint: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[b"nft_account", pair.as_ref(), mint.as_ref()], &nftamm::ID)
+}
+
+/// Authority of every token vault, `[b"program", b"signer"]`
+pub fn find_program_as_signer() -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[b"program", b"signer"], &nftamm::ID)
+}
+
+/// Vault escrowing protocol fees in a quote token, `[b"quote", b"protocol_fee", pair_authority, mint]`
+pub fn find_protocol_fee_vault(pair_authority: &Pubkey, quote_token_mint: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(
+        &[
+            b"quote",
+            b"protocol_fee",
+            pair_authority.as_ref(),
+            quote_token_mint.as_ref(),
+        ],
+        &nftamm::ID,
+    )
+}
+
+/// Lamport vault escrowing protocol fees in native sol, `[b"sol", b"protocol_fee", pair_authority]`
+pub fn find_protocol_sol_fee_vault(pair_authority: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(
+        &[b"sol", b"protocol_fee", pair_authority.as_ref()],
+        &nftamm::ID,
+    )
+}
+
+/// Metaplex metadata account of a mint
+pub fn find_metadata(mint: &Pubkey) -> (Pubkey, u8) {
+    mpl_token_metadata::pda::find_metadata_account(mint)
+}
+
+/// Associated token account of `owner` for `mint`
+pub fn associated_token_address(owner: &Pubkey, mint: &Pubkey) -> Pubkey {
+    anchor_spl::associated_token::get_associated_token_address(owner, mint)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Seeds as the program's account constraints spell them
+    fn program_address(seeds: &[&[u8]]) -> (Pubkey, u8) {
+        Pubkey::find_program_address(seeds, &nftamm::ID)
+    }
+
+    #[test]
+    fn pair_vaults_match_program_seeds() {
+        let pair = Pubkey::new_unique();
+
+        assert_eq!(
+            find_quote_token_vault(&pair),
+            program_address(&[b"quote", pair.as_ref()])
+        );
+        assert_eq!(
+            find_quote_fee_vault(&pair),
+            program_address(&[b"quote", "fee".as_bytes(), pair.as_ref()])
+        );
+        assert_eq!(
+            find_sol_vault(&pair),
+            program_address(&[b"sol", pair.as_ref()])
+        );
+        assert_eq!(
+            find_sol_fee_vault(&pair),
+            program_address(&[b"sol", b"fee", pair.as_ref()])
+        );
+    }
+
+    #[test]
+    fn nft_accounts_match_program_seeds() {
+        let pair = Pubkey::new_unique();
+        let mint = Pubkey::new_unique();
+
+        assert_eq!(
+            find_pair_metadata(&pair, &mint),
+            program_address(&[b"pair_metadata", pair.as_ref(), mint.as_ref()])
+        );
+        assert_eq!(
+            find_nft_token_vault(&pair, &mint),
+            program_address(&[b"nft_account", pair.as_ref(), mint.as_ref()])
+        );
+        assert_eq!(
+            find_program_as_signer(),
+            program_address(&[b"program", b"signer"])
+        );
+
+        // The pair metadata and the vault of the same nft never collide
+        assert_ne!(
+            find_pair_metadata(&pair, &mint).0,
+            find_nft_token_vault(&pair, &mint).0
+        );
+    }
+
+    #[test]
+    fn protocol_fee_vaults_match_program_seeds() {
+        let pair_authority = Pubkey::new_unique();
+        let mint = Pubkey::new_unique();
+
+        assert_eq!(
+            find_protocol_fee_vault(&pair_authority, &mint),
+            program_address(&[
+                b"quote",
+                "protocol_fee".as_bytes(),
+                pair_authority.as_ref(),
+                mint.as_ref(),
+            ])
+        );
+        assert_eq!(
+            find_protocol_sol_fee_vault(&pair_authority),
+            program_address(&[b"sol", b"protocol_fee", pair_authority.as_ref()])
+        );
+    }
+
+    #[test]
+    fn metadata_matches_the_derivation_the_program_asserts() {
+        let mint = Pubkey::new_unique();
+
+        assert_eq!(
+            find_metadata(&mint),
+            Pubkey::find_program_address(
+                &[
+                    mpl_token_metadata::state::PREFIX.as_bytes(),
+                    mpl_token_metadata::id().as_ref(),
+                    mint.as_ref(),
+                ],
+                &mpl_token_metadata::id(),
+            )
+        );
+    }
+}
@@ -0,0 +1,40 @@
+//! Creator accounts appended to `remaining_accounts` when a trade owes royalties.
+//!
+//! Trade instructions only read these accounts when the pair's royalty policy charges a royalty
+//! on the traded nft, in which case every creator in the nft's metadata must be passed in order.
+
+use crate::pda::associated_token_address;
+use anchor_lang::{prelude::*, solana_program::instruction::AccountMeta};
+use mpl_token_metadata::state::{Creator, Metadata, TokenMetadataAccount};
+
+/// Creators listed in the data of a Metaplex metadata account, empty if it has none
+pub fn metadata_creators(metadata_data: &[u8]) -> Result<Vec<Creator>> {
+    let metadata: Metadata = Metadata::safe_deserialize(metadata_data)
+        .map_err(|_| anchor_lang::error::ErrorCode::AccountDidNotDeserialize)?;
+
+    Ok(metadata.data.creators.unwrap_or_default())
+}
+
+/// A (creator, creator quote token ATA) pair for every creator, for trades in a quote token
+pub fn creator_accounts(creators: &[Creator], quote_token_mint: &Pubkey) -> Vec<AccountMeta> {
+    creators
+        .iter()
+        .flat_map(|creator| {
+            vec![
+                AccountMeta::new_readonly(creator.address, false),
+                AccountMeta::new(
+                    associated_token_address(&creator.address, quote_token_mint),
+                    false,
+                ),
+            ]
+        })
+        .collect()
+}
+
+/// A creator account for every creator, for trades in native sol
+pub fn creator_accounts_sol(creators: &[Creator]) -> Vec<AccountMeta> {
+    creators
+        .iter()
+        .map(|creator| AccountMeta::new(creator.address, false))
+        .collect()
+}
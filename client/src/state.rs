@@ -0,0 +1,19 @@
+//! Decoding of nftamm accounts from raw account data.
+
+use anchor_lang::{AccountDeserialize, Result};
+pub use nftamm::state::{Pair, PairAuthority, PairMetadata, PendingChange, PendingFeeChange};
+
+/// Decode a [Pair], checking its account discriminator
+pub fn decode_pair(data: &[u8]) -> Result<Pair> {
+    Pair::try_deserialize(&mut &data[..])
+}
+
+/// Decode a [PairMetadata], checking its account discriminator
+pub fn decode_pair_metadata(data: &[u8]) -> Result<PairMetadata> {
+    PairMetadata::try_deserialize(&mut &data[..])
+}
+
+/// Decode a [PairAuthority], checking its account discriminator
+pub fn decode_pair_authority(data: &[u8]) -> Result<PairAuthority> {
+    PairAuthority::try_deserialize(&mut &data[..])
+}
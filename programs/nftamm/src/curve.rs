@@ -0,0 +1,378 @@
+//! Bonding curve math shared by every trade instruction.
+//!
+//! All prices are denominated in the smallest unit of the pair's quote token. Exponential
+//! deltas are expressed in basis points. Every division rounds in the pair's favour: prices
+//! the pair charges round up and prices the pair pays round down.
+
+use crate::error::ProgramError;
+use anchor_lang::prelude::*;
+use std::convert::TryFrom;
+
+/// Denominator for values expressed in basis points
+pub const BASIS_POINTS: u64 = 10000;
+
+/// Rounding direction for fixed-point division
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Rounding {
+    /// Round towards zero
+    Down,
+    /// Round away from zero
+    Up,
+}
+
+/// Compute `value * numerator / denominator` without intermediate overflow
+pub fn mul_div(value: u64, numerator: u64, denominator: u64, rounding: Rounding) -> Result<u64> {
+    if denominator == 0 {
+        return Err(ProgramError::NumericalOverflow.into());
+    }
+
+    let product = (value as u128)
+        .checked_mul(numerator as u128)
+        .ok_or(ProgramError::NumericalOverflow)?;
+
+    let mut quotient = product / denominator as u128;
+
+    if rounding == Rounding::Up && product % denominator as u128 != 0 {
+        quotient = quotient
+            .checked_add(1)
+            .ok_or(ProgramError::NumericalOverflow)?;
+    }
+
+    u64::try_from(quotient).map_err(|_| ProgramError::NumericalOverflow.into())
+}
+
+/// Quote tokens and nfts held by a pair
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct Reserves {
+    /// Spendable balance of the pair's quote vault
+    pub quote: u64,
+    /// Number of nfts held by the pair
+    pub nfts: u64,
+}
+
+impl Reserves {
+    /// Reserves once the pair has sold one nft for `price`
+    pub fn after_buy(&self, price: u64) -> Result<Reserves> {
+        Ok(Reserves {
+            quote: self
+                .quote
+                .checked_add(price)
+                .ok_or(ProgramError::NumericalOverflow)?,
+            nfts: self
+                .nfts
+                .checked_sub(1)
+                .ok_or(ProgramError::InsufficientReserves)?,
+        })
+    }
+
+    /// Reserves once the pair has bought one nft, paying `vault_outflow` out of its quote vault
+    pub fn after_sell(&self, vault_outflow: u64) -> Result<Reserves> {
+        Ok(Reserves {
+            quote: self
+                .quote
+                .checked_sub(vault_outflow)
+                .ok_or(ProgramError::InsufficientReserves)?,
+            nfts: self
+                .nfts
+                .checked_add(1)
+                .ok_or(ProgramError::NumericalOverflow)?,
+        })
+    }
+}
+
+/// Pricing rules of a pair
+///
+/// "Buy" is always from the trader's perspective: the trader takes an NFT out of the pair and
+/// pays quote tokens in. "Sell" is the opposite direction.
+///
+/// A buy is charged the spot price it moves the pair to, and a sell is paid the spot price the
+/// pair is at. Buying an NFT and selling it straight back therefore never pays the trader more
+/// than they paid in.
+pub trait BondingCurve {
+    /// Quote tokens the pair charges for its next NFT, before fees and royalties
+    fn buy_price(&self, spot_price: u64) -> Result<u64> {
+        Ok(spot_price)
+    }
+
+    /// Quote tokens the pair pays for the next NFT it receives, before fees and royalties
+    fn sell_price(&self, spot_price: u64) -> Result<u64> {
+        Ok(spot_price)
+    }
+
+    /// Spot price of the pair after it has sold one NFT
+    fn spot_price_after_buy(&mut self, spot_price: u64) -> Result<u64>;
+
+    /// Spot price of the pair after it has bought one NFT
+    ///
+    /// `vault_outflow` is what left the pair's quote vault for that NFT: the sell price less the
+    /// pair fee, plus the protocol fee. Only the constant product curve reads it.
+    fn spot_price_after_sell(&mut self, spot_price: u64, vault_outflow: u64) -> Result<u64>;
+}
+
+/// Spot price moves by a fixed amount of quote tokens per trade
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Linear {
+    /// Amount added to or subtracted from the spot price per trade
+    pub delta: u64,
+    /// Stop the spot price at zero instead of rejecting a sell that moves it below zero
+    pub clamp_at_zero: bool,
+}
+
+impl BondingCurve for Linear {
+    fn buy_price(&self, spot_price: u64) -> Result<u64> {
+        spot_price
+            .checked_add(self.delta)
+            .ok_or_else(|| ProgramError::DeltaTooLarge.into())
+    }
+
+    fn spot_price_after_buy(&mut self, spot_price: u64) -> Result<u64> {
+        self.buy_price(spot_price)
+    }
+
+    fn spot_price_after_sell(&mut self, spot_price: u64, _vault_outflow: u64) -> Result<u64> {
+        if self.clamp_at_zero {
+            return Ok(spot_price.saturating_sub(self.delta));
+        }
+
+        spot_price
+            .checked_sub(self.delta)
+            .ok_or_else(|| ProgramError::SpotPriceUnderflow.into())
+    }
+}
+
+/// Spot price is multiplied or divided by `1 + delta / BASIS_POINTS` per trade
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Exponential {
+    /// Growth rate per trade in basis points
+    pub delta: u64,
+}
+
+impl Exponential {
+    fn multiplier(&self) -> Result<u64> {
+        BASIS_POINTS
+            .checked_add(self.delta)
+            .ok_or_else(|| ProgramError::DeltaTooLarge.into())
+    }
+}
+
+impl BondingCurve for Exponential {
+    fn buy_price(&self, spot_price: u64) -> Result<u64> {
+        mul_div(spot_price, self.multiplier()?, BASIS_POINTS, Rounding::Up)
+    }
+
+    fn spot_price_after_buy(&mut self, spot_price: u64) -> Result<u64> {
+        self.buy_price(spot_price)
+    }
+
+    fn spot_price_after_sell(&mut self, spot_price: u64, _vault_outflow: u64) -> Result<u64> {
+        mul_div(spot_price, BASIS_POINTS, self.multiplier()?, Rounding::Down)
+    }
+}
+
+/// Spot price never moves
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Fixed;
+
+impl BondingCurve for Fixed {
+    fn spot_price_after_buy(&mut self, spot_price: u64) -> Result<u64> {
+        Ok(spot_price)
+    }
+
+    fn spot_price_after_sell(&mut self, spot_price: u64, _vault_outflow: u64) -> Result<u64> {
+        Ok(spot_price)
+    }
+}
+
+/// Constant product of the pair's quote and nft reserves
+///
+/// Prices ignore the spot price and are derived from the reserves, which are updated as trades
+/// are walked. The spot price reported after each trade is the reserve ratio.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Xyk {
+    /// Reserves before the next trade
+    pub reserves: Reserves,
+}
+
+impl Xyk {
+    fn spot_price(&self) -> Result<u64> {
+        mul_div(self.reserves.quote, 1, self.reserves.nfts, Rounding::Down)
+    }
+}
+
+impl BondingCurve for Xyk {
+    fn buy_price(&self, _spot_price: u64) -> Result<u64> {
+        // The last nft can never be bought out of a constant product pool
+        if self.reserves.nfts < 2 {
+            return Err(ProgramError::InsufficientReserves.into());
+        }
+
+        mul_div(self.reserves.quote, 1, self.reserves.nfts - 1, Rounding::Up)
+    }
+
+    fn sell_price(&self, _spot_price: u64) -> Result<u64> {
+        // An empty pool has no price, the first seller would be paid out the whole vault
+        if self.reserves.nfts == 0 {
+            return Err(ProgramError::InsufficientReserves.into());
+        }
+
+        let nfts = self
+            .reserves
+            .nfts
+            .checked_add(1)
+            .ok_or(ProgramError::NumericalOverflow)?;
+
+        mul_div(self.reserves.quote, 1, nfts, Rounding::Down)
+    }
+
+    fn spot_price_after_buy(&mut self, spot_price: u64) -> Result<u64> {
+        let price = self.buy_price(spot_price)?;
+        self.reserves = self.reserves.after_buy(price)?;
+        self.spot_price()
+    }
+
+    fn spot_price_after_sell(&mut self, _spot_price: u64, vault_outflow: u64) -> Result<u64> {
+        self.reserves = self.reserves.after_sell(vault_outflow)?;
+        self.spot_price()
+    }
+}
+
+/// Build the curve identified by a [crate::state::Pair::bonding_curve] value
+pub fn bonding_curve(
+    bonding_curve: u8,
+    delta: u64,
+    clamp_spot_price: bool,
+    reserves: Reserves,
+) -> Result<Box<dyn BondingCurve>> {
+    match bonding_curve {
+        0 => Ok(Box::new(Linear {
+            delta,
+            clamp_at_zero: clamp_spot_price,
+        })),
+        1 => Ok(Box::new(Exponential { delta })),
+        2 => Ok(Box::new(Fixed)),
+        3 => Ok(Box::new(Xyk { reserves })),
+        _ => Err(ProgramError::InvalidBondingCurve.into()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn linear(delta: u64) -> Linear {
+        Linear {
+            delta,
+            clamp_at_zero: false,
+        }
+    }
+
+    #[test]
+    fn mul_div_rounds_in_requested_direction() {
+        assert_eq!(mul_div(10, 1, 3, Rounding::Down).unwrap(), 3);
+        assert_eq!(mul_div(10, 1, 3, Rounding::Up).unwrap(), 4);
+
+        // Exact quotients are never rounded up
+        assert_eq!(mul_div(9, 1, 3, Rounding::Up).unwrap(), 3);
+    }
+
+    #[test]
+    fn mul_div_does_not_overflow_intermediate_product() {
+        assert_eq!(
+            mul_div(u64::MAX, BASIS_POINTS, BASIS_POINTS, Rounding::Down).unwrap(),
+            u64::MAX
+        );
+        assert!(mul_div(u64::MAX, 2, 1, Rounding::Down).is_err());
+        assert!(mul_div(1, 1, 0, Rounding::Down).is_err());
+    }
+
+    #[test]
+    fn linear_moves_spot_price_by_delta() {
+        let mut curve = linear(10);
+
+        assert_eq!(curve.buy_price(100).unwrap(), 110);
+        assert_eq!(curve.sell_price(100).unwrap(), 100);
+        assert_eq!(curve.spot_price_after_buy(100).unwrap(), 110);
+        assert_eq!(curve.spot_price_after_sell(100, 0).unwrap(), 90);
+    }
+
+    #[test]
+    fn linear_rejects_spot_price_out_of_range() {
+        assert!(linear(10).spot_price_after_sell(5, 0).is_err());
+        assert!(linear(1).spot_price_after_buy(u64::MAX).is_err());
+    }
+
+    #[test]
+    fn exponential_moves_spot_price_by_delta_basis_points() {
+        // 10% per trade
+        let mut curve = Exponential { delta: 1000 };
+
+        assert_eq!(curve.buy_price(1000).unwrap(), 1100);
+        assert_eq!(curve.sell_price(1000).unwrap(), 1000);
+        assert_eq!(curve.spot_price_after_buy(1000).unwrap(), 1100);
+        assert_eq!(curve.spot_price_after_sell(1100, 0).unwrap(), 1000);
+    }
+
+    #[test]
+    fn exponential_rounds_in_pair_favour() {
+        // 1% per trade, on prices that do not divide evenly
+        let mut curve = Exponential { delta: 100 };
+
+        // 101 * 1.01 = 102.01, charged as 103
+        assert_eq!(curve.spot_price_after_buy(101).unwrap(), 103);
+        // 101 / 1.01 = 100, exact
+        assert_eq!(curve.spot_price_after_sell(101, 0).unwrap(), 100);
+        // 102 / 1.01 = 100.99, paid as 100
+        assert_eq!(curve.spot_price_after_sell(102, 0).unwrap(), 100);
+    }
+
+    #[test]
+    fn exponential_rejects_overflowing_delta() {
+        let mut curve = Exponential { delta: u64::MAX };
+
+        assert!(curve.spot_price_after_buy(1).is_err());
+        assert!(curve.spot_price_after_sell(1, 0).is_err());
+    }
+
+    #[test]
+    fn buying_and_selling_back_never_pays_the_trader() {
+        let curves: [Box<dyn BondingCurve>; 4] = [
+            Box::new(linear(100)),
+            Box::new(Exponential { delta: 333 }),
+            Box::new(Fixed),
+            Box::new(Xyk {
+                reserves: Reserves {
+                    quote: 1_000_003,
+                    nfts: 7,
+                },
+            }),
+        ];
+
+        for mut curve in curves {
+            let spot_price = 1000;
+
+            let paid = curve.buy_price(spot_price).unwrap();
+            let spot_price = curve.spot_price_after_buy(spot_price).unwrap();
+            let received = curve.sell_price(spot_price).unwrap();
+
+            assert!(received <= paid, "paid {} and received {}", paid, received);
+        }
+
+        // With a linear delta of 100 the trader pays 1100 and gets the same 1100 back
+        let mut curve = linear(100);
+        assert_eq!(curve.buy_price(1000).unwrap(), 1100);
+        let spot_price = curve.spot_price_after_buy(1000).unwrap();
+        assert_eq!(curve.sell_price(spot_price).unwrap(), 1100);
+    }
+
+    #[test]
+    fn xyk_rejects_sells_into_empty_inventory() {
+        let curve = Xyk {
+            reserves: Reserves {
+                quote: 1_000_000,
+                nfts: 0,
+            },
+        };
+
+        assert!(curve.sell_price(0).is_err());
+    }
+}
@@ -1,5 +1,12 @@
+//! Errors returned by the nftamm program.
+//!
+//! Public so that clients and tests can match a failed transaction's custom error code against
+//! a variant. Each variant is described by its `msg`.
+
 use anchor_lang::prelude::*;
 
+/// Error codes of the nftamm program, numbered from anchor's custom error offset in order
+#[allow(missing_docs)]
 #[error_code]
 pub enum ProgramError {
     #[msg("Metadata account does not exist")]
@@ -62,4 +69,38 @@ pub enum ProgramError {
     NumericalOverflow,
     #[msg("Public keys not equal")]
     PublicKeyMismatch,
+    #[msg("Invalid number of remaining accounts")]
+    InvalidRemainingAccounts,
+    #[msg("Trade price is outside of the slippage bound")]
+    SlippageExceeded,
+    #[msg("Pair trade count does not match the expected trade count")]
+    TradeCountMismatch,
+    #[msg("Invalid royalty policy")]
+    InvalidRoyaltyPolicy,
+    #[msg("Pair does not trade in native sol")]
+    NotNativePair,
+    #[msg("Nft mint is not in the pair's allowlist")]
+    MintNotAllowed,
+    #[msg("Invalid collection mode")]
+    InvalidCollectionMode,
+    #[msg("Nft is not verified by the pair's collection creator")]
+    CreatorNotVerified,
+    #[msg("Parameters are timelocked, queue the change instead")]
+    TimelockActive,
+    #[msg("Timelock has not elapsed yet")]
+    TimelockNotElapsed,
+    #[msg("No parameter change is pending")]
+    NoPendingChange,
+    #[msg("Pair does not trade while a parameter change is pending")]
+    PendingPairChange,
+    #[msg("Pair reserves are too low for this trade")]
+    InsufficientReserves,
+    #[msg("Invalid fee recipient for pair authority")]
+    InvalidFeeRecipient,
+    #[msg("Trade would move the spot price below zero")]
+    SpotPriceUnderflow,
+    #[msg("Delta moves the spot price above the maximum price")]
+    DeltaTooLarge,
+    #[msg("Too many nfts in a single quote")]
+    QuoteTooLarge,
 }
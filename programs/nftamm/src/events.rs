@@ -0,0 +1,246 @@
+//! Events emitted by the nftamm program.
+
+use crate::state::{Pair, PairAuthority, PendingChange, PendingFeeChange};
+use anchor_lang::prelude::*;
+
+/// Royalty paid to one creator of a traded nft
+#[event]
+pub struct RoyaltyPaid {
+    /// Mint of the traded nft
+    pub mint: Pubkey,
+    /// Creator receiving the royalty
+    pub creator: Pubkey,
+    /// Quote tokens paid to the creator
+    pub amount: u64,
+}
+
+/// A pair was created
+#[event]
+pub struct PairCreated {
+    /// Created pair
+    pub pair: Pubkey,
+    /// Owner of the pair
+    pub owner: Pubkey,
+    /// Pair authority charging protocol fees on the pair
+    pub pair_authority: Pubkey,
+    /// Collection mint, or creator address for creator collection modes
+    pub collection_mint: Pubkey,
+    /// Collection mode of the pair
+    pub collection_mode: u8,
+    /// Mint the pair is quoted in, the native mint for native sol pairs
+    pub quote_token_mint: Pubkey,
+    /// Whether the pair trades in native sol
+    pub is_native: bool,
+    /// Type of the pair
+    pub pair_type: u8,
+    /// Bonding curve of the pair
+    pub bonding_curve: u8,
+    /// Initial spot price
+    pub spot_price: u64,
+    /// Bonding curve delta
+    pub delta: u64,
+    /// Pair fee in basis points
+    pub fee: u16,
+}
+
+/// Quote tokens were deposited into a pair
+#[event]
+pub struct PairFunded {
+    /// Funded pair
+    pub pair: Pubkey,
+    /// Quote tokens deposited
+    pub amount: u64,
+    /// Whether the pair is active after the deposit
+    pub is_active: bool,
+}
+
+/// Quote tokens were withdrawn from a pair by its owner
+#[event]
+pub struct QuoteWithdrawn {
+    /// Pair withdrawn from
+    pub pair: Pubkey,
+    /// Quote tokens withdrawn
+    pub amount: u64,
+    /// Whether the pair is active after the withdrawal
+    pub is_active: bool,
+}
+
+/// An nft was deposited into a pair by its owner
+#[event]
+pub struct NftDeposited {
+    /// Pair deposited into
+    pub pair: Pubkey,
+    /// Mint of the deposited nft
+    pub mint: Pubkey,
+    /// Nfts held by the pair after the deposit
+    pub nfts_held: u32,
+}
+
+/// An nft was withdrawn from a pair by its owner
+#[event]
+pub struct NftWithdrawn {
+    /// Pair withdrawn from
+    pub pair: Pubkey,
+    /// Mint of the withdrawn nft
+    pub mint: Pubkey,
+    /// Nfts held by the pair after the withdrawal
+    pub nfts_held: u32,
+}
+
+/// A trader bought an nft from a pair
+#[event]
+pub struct NftBought {
+    /// Pair the nft was bought from
+    pub pair: Pubkey,
+    /// Mint of the bought nft
+    pub mint: Pubkey,
+    /// Trader buying the nft
+    pub buyer: Pubkey,
+    /// Bonding curve price, before fees and royalties
+    pub price: u64,
+    /// Fee paid to the pair authority
+    pub protocol_fee: u64,
+    /// Fee kept by the pair
+    pub pair_fee: u64,
+    /// Royalties paid to the nft creators
+    pub royalty: u64,
+    /// Quote tokens paid by the buyer
+    pub total: u64,
+    /// Spot price of the pair after the trade
+    pub new_spot_price: u64,
+    /// Trade count of the pair after the trade
+    pub trade_count: u64,
+}
+
+/// A trader sold an nft into a pair
+#[event]
+pub struct NftSold {
+    /// Pair the nft was sold into
+    pub pair: Pubkey,
+    /// Mint of the sold nft
+    pub mint: Pubkey,
+    /// Trader selling the nft
+    pub seller: Pubkey,
+    /// Bonding curve price, before fees and royalties
+    pub price: u64,
+    /// Fee paid to the pair authority
+    pub protocol_fee: u64,
+    /// Fee kept by the pair
+    pub pair_fee: u64,
+    /// Royalties paid to the nft creators
+    pub royalty: u64,
+    /// Quote tokens received by the seller
+    pub total: u64,
+    /// Spot price of the pair after the trade
+    pub new_spot_price: u64,
+    /// Trade count of the pair after the trade
+    pub trade_count: u64,
+}
+
+/// Pricing or trading parameters of a pair were changed, with the resulting values
+#[event]
+pub struct ParamsChanged {
+    /// Changed pair
+    pub pair: Pubkey,
+    /// Bonding curve of the pair
+    pub bonding_curve: u8,
+    /// Spot price of the pair
+    pub spot_price: u64,
+    /// Whether linear sells stop the spot price at zero
+    pub clamp_spot_price: bool,
+    /// Bonding curve delta
+    pub delta: u64,
+    /// Pair fee in basis points
+    pub fee: u16,
+    /// Royalty policy of the pair
+    pub royalty_policy: u8,
+    /// Royalty policy basis points
+    pub royalty_bps: u16,
+    /// Merkle root of allowed nft mints
+    pub mint_allowlist_root: Option<[u8; 32]>,
+    /// Timelock on parameter changes in slots
+    pub timelock_slots: u64,
+    /// Whether trades are rejected while a change is pending
+    pub halt_trades_while_pending: bool,
+    /// Queued parameter change
+    pub pending_change: Option<PendingChange>,
+}
+
+impl ParamsChanged {
+    /// Snapshot the parameters of `pair`
+    pub fn new(pair_key: Pubkey, pair: &Pair) -> Self {
+        ParamsChanged {
+            pair: pair_key,
+            bonding_curve: pair.bonding_curve,
+            spot_price: pair.spot_price,
+            clamp_spot_price: pair.clamp_spot_price,
+            delta: pair.delta,
+            fee: pair.fee,
+            royalty_policy: pair.royalty_policy,
+            royalty_bps: pair.royalty_bps,
+            mint_allowlist_root: pair.mint_allowlist_root,
+            timelock_slots: pair.timelock_slots,
+            halt_trades_while_pending: pair.halt_trades_while_pending,
+            pending_change: pair.pending_change,
+        }
+    }
+}
+
+/// Fees were withdrawn from a pair fee vault or a protocol fee vault
+#[event]
+pub struct FeesWithdrawn {
+    /// Vault the fees were withdrawn from
+    pub vault: Pubkey,
+    /// Owner of the account receiving the fees
+    pub recipient: Pubkey,
+    /// Fees withdrawn
+    pub amount: u64,
+}
+
+/// A pair was closed by its owner
+#[event]
+pub struct PairClosed {
+    /// Closed pair
+    pub pair: Pubkey,
+    /// Owner receiving the pair's remaining balances
+    pub owner: Pubkey,
+}
+
+/// A pair authority transfer was started or accepted
+#[event]
+pub struct AuthorityTransferred {
+    /// Pair authority being transferred
+    pub pair_authority: Pubkey,
+    /// Current authority after the instruction
+    pub current_authority: Pubkey,
+    /// Pending authority after the instruction, the default key once accepted
+    pub pending_authority: Pubkey,
+}
+
+/// Protocol fee settings of a pair authority were set
+#[event]
+pub struct ProtocolFeesChanged {
+    /// Changed pair authority
+    pub pair_authority: Pubkey,
+    /// Recipient of protocol fees
+    pub fee_recipient: Pubkey,
+    /// Protocol fee tiers in basis points, indexed by pair type
+    pub fees: [u16; 3],
+    /// Timelock on protocol fee changes in slots
+    pub timelock_slots: u64,
+    /// Queued protocol fee change
+    pub pending_fee_change: Option<PendingFeeChange>,
+}
+
+impl ProtocolFeesChanged {
+    /// Snapshot the protocol fee settings of `pair_authority`
+    pub fn new(pair_authority_key: Pubkey, pair_authority: &PairAuthority) -> Self {
+        ProtocolFeesChanged {
+            pair_authority: pair_authority_key,
+            fee_recipient: pair_authority.fee_recipient,
+            fees: pair_authority.fees,
+            timelock_slots: pair_authority.timelock_slots,
+            pending_fee_change: pair_authority.pending_fee_change,
+        }
+    }
+}
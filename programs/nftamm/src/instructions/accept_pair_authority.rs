@@ -1,4 +1,4 @@
-use crate::{error::ProgramError, state::*};
+use crate::{error::ProgramError, events::AuthorityTransferred, state::*};
 use anchor_lang::prelude::*;
 
 #[derive(Accounts)]
@@ -18,5 +18,11 @@ pub fn handler(ctx: Context<AcceptPairAuthority>) -> Result<()> {
     pair_authority.current_authority = ctx.accounts.payer.key();
     pair_authority.pending_authority = Pubkey::default();
 
+    emit!(AuthorityTransferred {
+        pair_authority: pair_authority.key(),
+        current_authority: pair_authority.current_authority,
+        pending_authority: pair_authority.pending_authority,
+    });
+
     Ok(())
 }
@@ -0,0 +1,54 @@
+use crate::{error::ProgramError, events::ParamsChanged, state::Pair, utils::*};
+use anchor_lang::prelude::*;
+
+#[derive(Accounts)]
+pub struct ApplyPairChange<'info> {
+    #[account(
+        constraint = payer.key() == pair.owner @ ProgramError::InvalidOwner,
+    )]
+    pub payer: Signer<'info>,
+
+    #[account(mut)]
+    pub pair: Account<'info, Pair>,
+}
+
+pub fn handler(ctx: Context<ApplyPairChange>) -> Result<()> {
+    let pair = &mut ctx.accounts.pair;
+
+    let pending_change = pair.pending_change.ok_or(ProgramError::NoPendingChange)?;
+
+    if Clock::get()?.slot < pending_change.apply_after_slot {
+        return Err(ProgramError::TimelockNotElapsed.into());
+    }
+
+    // Parameters the change leaves alone may have changed since it was queued
+    let bonding_curve = pending_change.bonding_curve.unwrap_or(pair.bonding_curve);
+    let delta = pending_change.delta.unwrap_or(pair.delta);
+    let fee = pending_change.fee.unwrap_or(pair.fee);
+    let royalty_policy = pending_change.royalty_policy.unwrap_or(pair.royalty_policy);
+    let royalty_bps = pending_change.royalty_bps.unwrap_or(pair.royalty_bps);
+
+    validate_pair_params(pair.pair_type, bonding_curve, delta, fee)?;
+    validate_royalty_policy(royalty_policy, royalty_bps)?;
+
+    pair.bonding_curve = bonding_curve;
+    pair.delta = delta;
+    pair.fee = fee;
+    pair.spot_price = pending_change.spot_price.unwrap_or(pair.spot_price);
+    pair.royalty_policy = royalty_policy;
+    pair.royalty_bps = royalty_bps;
+
+    if let Some(mint_allowlist_root) = pending_change.mint_allowlist_root {
+        pair.mint_allowlist_root = mint_allowlist_root;
+    }
+
+    if let Some(timelock_slots) = pending_change.timelock_slots {
+        pair.timelock_slots = timelock_slots;
+    }
+
+    pair.pending_change = None;
+
+    emit!(ParamsChanged::new(pair.key(), pair));
+
+    Ok(())
+}
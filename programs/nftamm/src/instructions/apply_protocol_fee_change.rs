@@ -0,0 +1,46 @@
+use crate::{error::ProgramError, events::ProtocolFeesChanged, state::*};
+use anchor_lang::prelude::*;
+
+#[derive(Accounts)]
+pub struct ApplyProtocolFeeChange<'info> {
+    #[account(
+        constraint = payer.key() == pair_authority.current_authority @ ProgramError::InvalidCurrentAuthority,
+    )]
+    pub payer: Signer<'info>,
+
+    #[account(mut)]
+    pub pair_authority: Account<'info, PairAuthority>,
+}
+
+pub fn handler(ctx: Context<ApplyProtocolFeeChange>) -> Result<()> {
+    let pair_authority = &mut ctx.accounts.pair_authority;
+
+    let pending_fee_change = pair_authority
+        .pending_fee_change
+        .ok_or(ProgramError::NoPendingChange)?;
+
+    if Clock::get()?.slot < pending_fee_change.apply_after_slot {
+        return Err(ProgramError::TimelockNotElapsed.into());
+    }
+
+    if let Some(fees) = pending_fee_change.fees {
+        pair_authority.fees = fees;
+    }
+
+    if let Some(fee_recipient) = pending_fee_change.fee_recipient {
+        pair_authority.fee_recipient = fee_recipient;
+    }
+
+    if let Some(timelock_slots) = pending_fee_change.timelock_slots {
+        pair_authority.timelock_slots = timelock_slots;
+    }
+
+    pair_authority.pending_fee_change = None;
+
+    emit!(ProtocolFeesChanged::new(
+        pair_authority.key(),
+        pair_authority
+    ));
+
+    Ok(())
+}
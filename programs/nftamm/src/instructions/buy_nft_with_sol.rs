@@ -0,0 +1,266 @@
+use crate::{error::ProgramError, events::NftBought, quote::quote_buy, state::*, utils::*};
+use anchor_lang::prelude::*;
+use anchor_spl::{
+    associated_token::AssociatedToken,
+    token::{transfer, Mint, Token, TokenAccount, Transfer},
+};
+
+// Sol in -> NFT out (native sol NFT or Trade pair)
+//
+// remaining_accounts holds, when royalties are owed on the NFT, one creator account (mut) for
+// every creator in the NFT's metadata.
+
+#[derive(Accounts)]
+pub struct BuyNftWithSol<'info> {
+    #[account(mut)]
+    pub payer: Signer<'info>,
+
+    #[account(constraint = pair_authority.key() == pair.pair_authority @ ProgramError::InvalidPairAuthority)]
+    pub pair_authority: Account<'info, PairAuthority>,
+
+    /// CHECK: system account PDA holding the protocol's fees in lamports
+    #[account(mut, seeds = [b"sol", b"protocol_fee", pair_authority.key().as_ref()], bump)]
+    pub protocol_sol_fee_vault: UncheckedAccount<'info>,
+
+    #[account(
+        mut,
+        constraint = pair.is_native @ ProgramError::NotNativePair,
+        constraint = pair.pair_type == 1 || pair.pair_type == 2 @ ProgramError::InvalidPairType,
+    )]
+    pub pair: Account<'info, Pair>,
+
+    /// CHECK: only used as close target for pair_metadata
+    #[account(
+        mut,
+        constraint = pair_metadata_creator.key() == pair_metadata.creator @ ProgramError::InvalidCreator,
+    )]
+    pub pair_metadata_creator: UncheckedAccount<'info>,
+
+    #[account(
+        mut,
+        close = pair_metadata_creator,
+        seeds = [b"pair_metadata", pair.key().as_ref(), nft_token_mint.key().as_ref()],
+        bump
+    )]
+    pub pair_metadata: Account<'info, PairMetadata>,
+
+    /// CHECK: validated in access control logic
+    #[account(constraint = nft_collection_mint.key() == pair.collection_mint @ ProgramError::InvalidCollectionMint)]
+    pub nft_collection_mint: UncheckedAccount<'info>,
+
+    /// CHECK: validated in access control logic
+    pub nft_collection_metadata: UncheckedAccount<'info>,
+
+    pub nft_token_mint: Box<Account<'info, Mint>>,
+
+    /// CHECK: validated in access control logic
+    pub nft_token_metadata: UncheckedAccount<'info>,
+
+    #[account(
+        mut,
+        seeds = [b"nft_account", pair.key().as_ref(), nft_token_mint.key().as_ref()],
+        bump,
+        constraint = nft_token_vault.amount == 1 @ ProgramError::InsufficientBalance,
+        constraint = nft_token_vault.owner == program_as_signer.key() @ ProgramError::InvalidOwner,
+    )]
+    pub nft_token_vault: Box<Account<'info, TokenAccount>>,
+
+    #[account(
+        init_if_needed,
+        payer = payer,
+        associated_token::mint = nft_token_mint,
+        associated_token::authority = payer,
+    )]
+    pub user_nft_token_account: Box<Account<'info, TokenAccount>>,
+
+    /// CHECK: system account PDA holding the pair's lamports
+    #[account(
+        mut,
+        seeds = [b"sol", pair.key().as_ref()],
+        bump,
+        constraint = sol_vault.key() == pair.quote_token_vault @ ProgramError::InvalidQuoteTokenVault,
+    )]
+    pub sol_vault: UncheckedAccount<'info>,
+
+    /// CHECK: system account PDA holding the pair's fees in lamports
+    #[account(
+        mut,
+        seeds = [b"sol", b"fee", pair.key().as_ref()],
+        bump,
+        constraint = sol_fee_vault.key() == pair.fee_vault @ ProgramError::InvalidFeeVault,
+    )]
+    pub sol_fee_vault: UncheckedAccount<'info>,
+
+    /// CHECK: PDA used as token account authority only
+    #[account(seeds = [b"program", b"signer"], bump)]
+    pub program_as_signer: UncheckedAccount<'info>,
+
+    pub system_program: Program<'info, System>,
+    pub token_program: Program<'info, Token>,
+    pub associated_token_program: Program<'info, AssociatedToken>,
+    pub rent: Sysvar<'info, Rent>,
+}
+
+impl<'info> BuyNftWithSol<'info> {
+    fn accounts(ctx: &Context<BuyNftWithSol>, expected_trade_count: Option<u64>) -> Result<()> {
+        let pair = ctx.accounts.pair.clone();
+
+        if !pair.is_active {
+            return Err(ProgramError::PairNotActive.into());
+        }
+
+        assert_no_pending_change(&pair)?;
+
+        if let Some(expected_trade_count) = expected_trade_count {
+            if pair.trade_count != expected_trade_count {
+                return Err(ProgramError::TradeCountMismatch.into());
+            }
+        }
+
+        validate_nft(
+            pair.collection_mode,
+            *ctx.accounts.nft_token_mint.clone(),
+            ctx.accounts.nft_token_metadata.clone(),
+            ctx.accounts.nft_collection_mint.clone(),
+            ctx.accounts.nft_collection_metadata.clone(),
+        )?;
+
+        let royalty_bps = royalty_basis_points(&ctx.accounts.nft_token_metadata.to_account_info())?;
+        let reserves = pair_reserves(&pair, &ctx.accounts.sol_vault.to_account_info())?;
+        let quote = quote_buy(
+            &pair,
+            &ctx.accounts.pair_authority,
+            reserves,
+            royalty_bps,
+            1,
+        )?;
+
+        // The user pays the price, fees and royalties
+        if ctx.accounts.payer.lamports() < quote.total {
+            return Err(ProgramError::InsufficientBalance.into());
+        }
+
+        Ok(())
+    }
+}
+
+/// Handler to buy an nft from a native sol pair, paying the pair, the protocol and the
+/// creators in lamports
+#[access_control(BuyNftWithSol::accounts(&ctx, expected_trade_count))]
+pub fn handler<'info>(
+    ctx: Context<'_, '_, '_, 'info, BuyNftWithSol<'info>>,
+    max_price: u64,
+    expected_trade_count: Option<u64>,
+) -> Result<()> {
+    let program_as_signer_bump = *ctx.bumps.get("program_as_signer").unwrap();
+
+    let royalty_bps = royalty_basis_points(&ctx.accounts.nft_token_metadata.to_account_info())?;
+    let reserves = pair_reserves(
+        &ctx.accounts.pair,
+        &ctx.accounts.sol_vault.to_account_info(),
+    )?;
+    let quote = quote_buy(
+        &ctx.accounts.pair,
+        &ctx.accounts.pair_authority,
+        reserves,
+        royalty_bps,
+        1,
+    )?;
+
+    // Effective price paid by the user, including fees
+    if quote.total > max_price {
+        return Err(ProgramError::SlippageExceeded.into());
+    }
+
+    let payer = ctx.accounts.payer.to_account_info();
+    let system_program = ctx.accounts.system_program.to_account_info();
+
+    transfer_sol(
+        &payer,
+        &ctx.accounts.protocol_sol_fee_vault.to_account_info(),
+        &system_program,
+        None,
+        quote.protocol_fee,
+    )?;
+
+    transfer_sol(
+        &payer,
+        &ctx.accounts.sol_vault.to_account_info(),
+        &system_program,
+        None,
+        quote.price,
+    )?;
+
+    transfer_sol(
+        &payer,
+        &ctx.accounts.sol_fee_vault.to_account_info(),
+        &system_program,
+        None,
+        quote.pair_fee,
+    )?;
+
+    let transfer_nft_accounts = Transfer {
+        from: ctx.accounts.nft_token_vault.to_account_info(),
+        to: ctx.accounts.user_nft_token_account.to_account_info(),
+        authority: ctx.accounts.program_as_signer.to_account_info(),
+    };
+
+    let seeds = &[
+        "program".as_bytes(),
+        "signer".as_bytes(),
+        &[program_as_signer_bump],
+    ];
+
+    let signer = &[&seeds[..]];
+
+    let transfer_nft_ctx = CpiContext::new_with_signer(
+        ctx.accounts.token_program.to_account_info(),
+        transfer_nft_accounts,
+        signer,
+    );
+
+    transfer(transfer_nft_ctx, 1)?;
+
+    if quote.royalty > 0 {
+        honor_royalties_sol(
+            &mut ctx.remaining_accounts.iter(),
+            &ctx.accounts.nft_token_metadata.to_account_info(),
+            quote.royalty,
+            &payer,
+            None,
+            &system_program,
+        )?;
+    }
+
+    let pair = &mut ctx.accounts.pair;
+
+    pair.spot_price = quote.new_spot_price;
+
+    pair.nfts_held = pair
+        .nfts_held
+        .checked_sub(1)
+        .ok_or(ProgramError::NumericalOverflow)?;
+    pair.trade_count = pair
+        .trade_count
+        .checked_add(1)
+        .ok_or(ProgramError::NumericalOverflow)?;
+
+    if pair.pair_type == 1 && pair.nfts_held == 0 {
+        pair.is_active = false;
+    }
+
+    emit!(NftBought {
+        pair: ctx.accounts.pair.key(),
+        mint: ctx.accounts.nft_token_mint.key(),
+        buyer: ctx.accounts.payer.key(),
+        price: quote.price,
+        protocol_fee: quote.protocol_fee,
+        pair_fee: quote.pair_fee,
+        royalty: quote.royalty,
+        total: quote.total,
+        new_spot_price: ctx.accounts.pair.spot_price,
+        trade_count: ctx.accounts.pair.trade_count,
+    });
+
+    Ok(())
+}
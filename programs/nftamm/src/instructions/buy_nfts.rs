@@ -0,0 +1,340 @@
+use crate::{
+    error::ProgramError,
+    events::NftBought,
+    quote::{quote_buy, Quote},
+    state::*,
+    utils::*,
+};
+use anchor_lang::{prelude::*, AccountsClose};
+use anchor_spl::{
+    associated_token::{create, get_associated_token_address, AssociatedToken, Create},
+    token::{transfer, Mint, Token, TokenAccount, Transfer},
+};
+
+// Token in -> many NFTs out (NFT or Trade pair)
+//
+// remaining_accounts holds one group of accounts per NFT bought, in order:
+//   0. nft_token_mint
+//   1. nft_token_metadata
+//   2. pair_metadata (mut)
+//   3. pair_metadata_creator (mut)
+//   4. nft_token_vault (mut)
+//   5. user_nft_token_account (mut), created as the payer's ATA if empty
+// followed, when royalties are owed on the NFT, by a (creator, creator quote token ATA (mut))
+// pair for every creator in the NFT's metadata.
+
+#[derive(Accounts)]
+pub struct BuyNfts<'info> {
+    #[account(mut)]
+    pub payer: Signer<'info>,
+
+    #[account(constraint = pair_authority.key() == pair.pair_authority @ ProgramError::InvalidPairAuthority)]
+    pub pair_authority: Account<'info, PairAuthority>,
+
+    #[account(
+        mut,
+        seeds = [b"quote", "protocol_fee".as_bytes(), pair_authority.key().as_ref(), quote_token_mint.key().as_ref()],
+        bump,
+    )]
+    pub protocol_fee_vault: Box<Account<'info, TokenAccount>>,
+
+    #[account(
+        mut,
+        constraint = pair.pair_type == 1 || pair.pair_type == 2 @ ProgramError::InvalidPairType,
+    )]
+    pub pair: Account<'info, Pair>,
+
+    #[account(constraint = quote_token_mint.key() == pair.quote_token_mint @ ProgramError::InvalidQuoteTokenMint)]
+    pub quote_token_mint: Box<Account<'info, Mint>>,
+
+    #[account(
+        mut,
+        seeds = [b"quote", pair.key().as_ref()],
+        bump,
+        constraint = quote_token_vault.key() == pair.quote_token_vault @ ProgramError::InvalidQuoteTokenVault,
+        constraint = quote_token_vault.mint == quote_token_mint.key() @ ProgramError::InvalidQuoteTokenMint,
+    )]
+    pub quote_token_vault: Box<Account<'info, TokenAccount>>,
+
+    #[account(
+        mut,
+        seeds = [b"quote", "fee".as_bytes(), pair.key().as_ref()],
+        bump,
+        constraint = quote_fee_vault.key() == pair.fee_vault @ ProgramError::InvalidFeeVault,
+        constraint = quote_fee_vault.mint == quote_token_mint.key() @ ProgramError::InvalidQuoteTokenMint,
+    )]
+    pub quote_fee_vault: Box<Account<'info, TokenAccount>>,
+
+    #[account(
+        mut,
+        constraint = user_quote_token_account.mint == quote_token_mint.key() @ ProgramError::InvalidQuoteTokenMint,
+        constraint = user_quote_token_account.owner == payer.key() @ ProgramError::InvalidOwner,
+    )]
+    pub user_quote_token_account: Box<Account<'info, TokenAccount>>,
+
+    /// CHECK: PDA used as token account authority only
+    #[account(seeds = [b"program", b"signer"], bump)]
+    pub program_as_signer: UncheckedAccount<'info>,
+
+    pub system_program: Program<'info, System>,
+    pub token_program: Program<'info, Token>,
+    pub associated_token_program: Program<'info, AssociatedToken>,
+    pub rent: Sysvar<'info, Rent>,
+}
+
+impl<'info> BuyNfts<'info> {
+    fn accounts(ctx: &Context<BuyNfts>) -> Result<()> {
+        if ctx.remaining_accounts.is_empty() {
+            return Err(ProgramError::InvalidRemainingAccounts.into());
+        }
+
+        if !ctx.accounts.pair.is_active {
+            return Err(ProgramError::PairNotActive.into());
+        }
+
+        assert_no_pending_change(&ctx.accounts.pair)?;
+
+        Ok(())
+    }
+}
+
+/// Handler to buy every nft passed through remaining_accounts from a pair, walking the bonding
+/// curve once per nft.
+#[access_control(BuyNfts::accounts(&ctx))]
+pub fn handler<'info>(
+    ctx: Context<'_, '_, '_, 'info, BuyNfts<'info>>,
+    max_total_input: u64,
+) -> Result<()> {
+    let program_as_signer_bump = *ctx.bumps.get("program_as_signer").unwrap();
+    let remaining_accounts = &mut ctx.remaining_accounts.iter();
+
+    let seeds = &[
+        "program".as_bytes(),
+        "signer".as_bytes(),
+        &[program_as_signer_bump],
+    ];
+
+    let signer = &[&seeds[..]];
+
+    let pair_key = ctx.accounts.pair.key();
+    let mut total = Quote {
+        new_spot_price: ctx.accounts.pair.spot_price,
+        ..Quote::default()
+    };
+    let mut nfts_bought: u32 = 0;
+    let mut reserves = pair_reserves(
+        &ctx.accounts.pair,
+        &ctx.accounts.quote_token_vault.to_account_info(),
+    )?;
+
+    while !remaining_accounts.as_slice().is_empty() {
+        let nft_token_mint_info = next_account_info(remaining_accounts)?;
+        let nft_token_metadata_info = next_account_info(remaining_accounts)?;
+        let pair_metadata_info = next_account_info(remaining_accounts)?;
+        let pair_metadata_creator_info = next_account_info(remaining_accounts)?;
+        let nft_token_vault_info = next_account_info(remaining_accounts)?;
+        let user_nft_token_account_info = next_account_info(remaining_accounts)?;
+
+        let pair_metadata: Account<PairMetadata> = Account::try_from(pair_metadata_info)?;
+
+        assert_derivation(
+            ctx.program_id,
+            pair_metadata_info,
+            &[
+                b"pair_metadata",
+                pair_key.as_ref(),
+                nft_token_mint_info.key.as_ref(),
+            ],
+        )?;
+        assert_keys_equal(pair_metadata.pair, pair_key)?;
+        assert_keys_equal(pair_metadata.token_mint, *nft_token_mint_info.key)?;
+
+        if pair_metadata.creator != *pair_metadata_creator_info.key {
+            return Err(ProgramError::InvalidCreator.into());
+        }
+
+        assert_metadata_valid(
+            &UncheckedAccount::try_from(nft_token_metadata_info.clone()),
+            nft_token_mint_info.key,
+        )?;
+
+        assert_derivation(
+            ctx.program_id,
+            nft_token_vault_info,
+            &[
+                b"nft_account",
+                pair_key.as_ref(),
+                nft_token_mint_info.key.as_ref(),
+            ],
+        )?;
+
+        let nft_token_vault: Account<TokenAccount> = Account::try_from(nft_token_vault_info)?;
+
+        if nft_token_vault.key() != pair_metadata.token_account {
+            return Err(ProgramError::InvalidNftTokenVault.into());
+        }
+
+        if nft_token_vault.owner != ctx.accounts.program_as_signer.key() {
+            return Err(ProgramError::InvalidOwner.into());
+        }
+
+        if nft_token_vault.amount != 1 {
+            return Err(ProgramError::InsufficientBalance.into());
+        }
+
+        assert_keys_equal(
+            get_associated_token_address(&ctx.accounts.payer.key(), nft_token_mint_info.key),
+            *user_nft_token_account_info.key,
+        )?;
+
+        if user_nft_token_account_info.data_is_empty() {
+            let create_ata_accounts = Create {
+                payer: ctx.accounts.payer.to_account_info(),
+                associated_token: user_nft_token_account_info.clone(),
+                authority: ctx.accounts.payer.to_account_info(),
+                mint: nft_token_mint_info.clone(),
+                system_program: ctx.accounts.system_program.to_account_info(),
+                token_program: ctx.accounts.token_program.to_account_info(),
+                rent: ctx.accounts.rent.to_account_info(),
+            };
+
+            let create_ctx = CpiContext::new(
+                ctx.accounts.associated_token_program.to_account_info(),
+                create_ata_accounts,
+            );
+
+            create(create_ctx)?;
+        }
+
+        let transfer_nft_accounts = Transfer {
+            from: nft_token_vault_info.clone(),
+            to: user_nft_token_account_info.clone(),
+            authority: ctx.accounts.program_as_signer.to_account_info(),
+        };
+
+        let transfer_nft_ctx = CpiContext::new_with_signer(
+            ctx.accounts.token_program.to_account_info(),
+            transfer_nft_accounts,
+            signer,
+        );
+
+        transfer(transfer_nft_ctx, 1)?;
+
+        pair_metadata.close(pair_metadata_creator_info.clone())?;
+
+        // Walk the curve one nft at a time
+        let royalty_bps = royalty_basis_points(nft_token_metadata_info)?;
+        let pair = &mut ctx.accounts.pair;
+        let quote = quote_buy(pair, &ctx.accounts.pair_authority, reserves, royalty_bps, 1)?;
+        reserves = reserves.after_buy(quote.price)?;
+        total.merge(&quote)?;
+        pair.spot_price = quote.new_spot_price;
+
+        if quote.royalty > 0 {
+            honor_royalties(
+                false,
+                None,
+                remaining_accounts,
+                nft_token_metadata_info,
+                quote.royalty,
+                &ctx.accounts.associated_token_program.to_account_info(),
+                &ctx.accounts.payer.to_account_info(),
+                &ctx.accounts.user_quote_token_account.to_account_info(),
+                &ctx.accounts.payer.to_account_info(),
+                &ctx.accounts.quote_token_mint.to_account_info(),
+                &ctx.accounts.system_program.to_account_info(),
+                &ctx.accounts.token_program.to_account_info(),
+                &ctx.accounts.rent.to_account_info(),
+            )?;
+        }
+
+        nfts_bought = nfts_bought
+            .checked_add(1)
+            .ok_or(ProgramError::NumericalOverflow)?;
+
+        // trade_count is only written once all nfts have been traded
+        let pair = &ctx.accounts.pair;
+        emit!(NftBought {
+            pair: pair_key,
+            mint: nft_token_mint_info.key(),
+            buyer: ctx.accounts.payer.key(),
+            price: quote.price,
+            protocol_fee: quote.protocol_fee,
+            pair_fee: quote.pair_fee,
+            royalty: quote.royalty,
+            total: quote.total,
+            new_spot_price: pair.spot_price,
+            trade_count: pair
+                .trade_count
+                .checked_add(nfts_bought as u64)
+                .ok_or(ProgramError::NumericalOverflow)?,
+        });
+    }
+
+    if total.total > max_total_input {
+        return Err(ProgramError::SlippageExceeded.into());
+    }
+
+    // user_quote_token_account still holds the balance from before royalties were paid
+    if ctx.accounts.user_quote_token_account.amount < total.total {
+        return Err(ProgramError::InsufficientBalance.into());
+    }
+
+    let transfer_pair_authority_fee_accounts = Transfer {
+        from: ctx.accounts.user_quote_token_account.to_account_info(),
+        to: ctx.accounts.protocol_fee_vault.to_account_info(),
+        authority: ctx.accounts.payer.to_account_info(),
+    };
+
+    let transfer_pair_authority_fee_ctx = CpiContext::new(
+        ctx.accounts.token_program.to_account_info(),
+        transfer_pair_authority_fee_accounts,
+    );
+
+    transfer(transfer_pair_authority_fee_ctx, total.protocol_fee)?;
+
+    let transfer_quote_accounts = Transfer {
+        from: ctx.accounts.user_quote_token_account.to_account_info(),
+        to: ctx.accounts.quote_token_vault.to_account_info(),
+        authority: ctx.accounts.payer.to_account_info(),
+    };
+
+    let transfer_quote_ctx = CpiContext::new(
+        ctx.accounts.token_program.to_account_info(),
+        transfer_quote_accounts,
+    );
+
+    transfer(transfer_quote_ctx, total.price)?;
+
+    if total.pair_fee > 0 {
+        let transfer_fee_accounts = Transfer {
+            from: ctx.accounts.user_quote_token_account.to_account_info(),
+            to: ctx.accounts.quote_fee_vault.to_account_info(),
+            authority: ctx.accounts.payer.to_account_info(),
+        };
+
+        let transfer_fee_ctx = CpiContext::new(
+            ctx.accounts.token_program.to_account_info(),
+            transfer_fee_accounts,
+        );
+
+        transfer(transfer_fee_ctx, total.pair_fee)?;
+    }
+
+    let pair = &mut ctx.accounts.pair;
+
+    pair.nfts_held = pair
+        .nfts_held
+        .checked_sub(nfts_bought)
+        .ok_or(ProgramError::NumericalOverflow)?;
+    pair.trade_count = pair
+        .trade_count
+        .checked_add(nfts_bought as u64)
+        .ok_or(ProgramError::NumericalOverflow)?;
+
+    if pair.pair_type == 1 && pair.nfts_held == 0 {
+        pair.is_active = false;
+    }
+
+    Ok(())
+}
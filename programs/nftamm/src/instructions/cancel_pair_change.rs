@@ -0,0 +1,27 @@
+use crate::{error::ProgramError, events::ParamsChanged, state::Pair};
+use anchor_lang::prelude::*;
+
+#[derive(Accounts)]
+pub struct CancelPairChange<'info> {
+    #[account(
+        constraint = payer.key() == pair.owner @ ProgramError::InvalidOwner,
+    )]
+    pub payer: Signer<'info>,
+
+    #[account(mut)]
+    pub pair: Account<'info, Pair>,
+}
+
+pub fn handler(ctx: Context<CancelPairChange>) -> Result<()> {
+    let pair = &mut ctx.accounts.pair;
+
+    if pair.pending_change.is_none() {
+        return Err(ProgramError::NoPendingChange.into());
+    }
+
+    pair.pending_change = None;
+
+    emit!(ParamsChanged::new(pair.key(), pair));
+
+    Ok(())
+}
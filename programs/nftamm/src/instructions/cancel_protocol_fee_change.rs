@@ -0,0 +1,30 @@
+use crate::{error::ProgramError, events::ProtocolFeesChanged, state::*};
+use anchor_lang::prelude::*;
+
+#[derive(Accounts)]
+pub struct CancelProtocolFeeChange<'info> {
+    #[account(
+        constraint = payer.key() == pair_authority.current_authority @ ProgramError::InvalidCurrentAuthority,
+    )]
+    pub payer: Signer<'info>,
+
+    #[account(mut)]
+    pub pair_authority: Account<'info, PairAuthority>,
+}
+
+pub fn handler(ctx: Context<CancelProtocolFeeChange>) -> Result<()> {
+    let pair_authority = &mut ctx.accounts.pair_authority;
+
+    if pair_authority.pending_fee_change.is_none() {
+        return Err(ProgramError::NoPendingChange.into());
+    }
+
+    pair_authority.pending_fee_change = None;
+
+    emit!(ProtocolFeesChanged::new(
+        pair_authority.key(),
+        pair_authority
+    ));
+
+    Ok(())
+}
@@ -0,0 +1,38 @@
+use crate::{error::ProgramError, events::ParamsChanged, state::Pair, utils::validate_pair_params};
+use anchor_lang::prelude::*;
+
+#[derive(Accounts)]
+pub struct ChangeBondingCurve<'info> {
+    #[account(
+        constraint = payer.key() == pair.owner @ ProgramError::InvalidOwner,
+    )]
+    pub payer: Signer<'info>,
+
+    #[account(mut)]
+    pub pair: Account<'info, Pair>,
+}
+
+/// Handler to move a live pair to another bonding curve. delta and spot_price are set in the
+/// same instruction since their meaning depends on the curve.
+pub fn handler(
+    ctx: Context<ChangeBondingCurve>,
+    new_bonding_curve: u8,
+    new_delta: u64,
+    new_spot_price: u64,
+) -> Result<()> {
+    let pair = &mut ctx.accounts.pair;
+
+    if pair.timelock_slots > 0 {
+        return Err(ProgramError::TimelockActive.into());
+    }
+
+    validate_pair_params(pair.pair_type, new_bonding_curve, new_delta, pair.fee)?;
+
+    pair.bonding_curve = new_bonding_curve;
+    pair.delta = new_delta;
+    pair.spot_price = new_spot_price;
+
+    emit!(ParamsChanged::new(pair.key(), pair));
+
+    Ok(())
+}
@@ -1,4 +1,4 @@
-use crate::{error::ProgramError, state::Pair};
+use crate::{error::ProgramError, events::ParamsChanged, state::Pair, utils::validate_pair_params};
 use anchor_lang::prelude::*;
 
 #[derive(Accounts)]
@@ -15,13 +15,15 @@ pub struct ChangeDelta<'info> {
 pub fn handler(ctx: Context<ChangeDelta>, new_delta: u64) -> Result<()> {
     let pair = &mut ctx.accounts.pair;
 
-    if pair.bonding_curve == 1 {
-        if new_delta > 10000 {
-            return Err(ProgramError::InvalidDelta.into());
-        }
+    if pair.timelock_slots > 0 {
+        return Err(ProgramError::TimelockActive.into());
     }
 
+    validate_pair_params(pair.pair_type, pair.bonding_curve, new_delta, pair.fee)?;
+
     pair.delta = new_delta;
 
+    emit!(ParamsChanged::new(pair.key(), pair));
+
     Ok(())
 }
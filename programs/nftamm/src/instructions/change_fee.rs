@@ -1,4 +1,4 @@
-use crate::{error::ProgramError, state::Pair};
+use crate::{error::ProgramError, events::ParamsChanged, state::Pair, utils::validate_pair_params};
 use anchor_lang::prelude::*;
 
 #[derive(Accounts)]
@@ -15,11 +15,15 @@ pub struct ChangeFee<'info> {
 pub fn handler(ctx: Context<ChangeFee>, new_fee: u16) -> Result<()> {
     let pair = &mut ctx.accounts.pair;
 
-    if new_fee > 10000 {
-        return Err(ProgramError::InvalidDelta.into());
+    if pair.timelock_slots > 0 {
+        return Err(ProgramError::TimelockActive.into());
     }
 
+    validate_pair_params(pair.pair_type, pair.bonding_curve, pair.delta, new_fee)?;
+
     pair.fee = new_fee;
 
+    emit!(ParamsChanged::new(pair.key(), pair));
+
     Ok(())
 }
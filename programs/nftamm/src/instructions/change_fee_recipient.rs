@@ -0,0 +1,35 @@
+use crate::{error::ProgramError, events::ProtocolFeesChanged, state::*};
+use anchor_lang::prelude::*;
+
+#[derive(Accounts)]
+pub struct ChangeFeeRecipient<'info> {
+    #[account(
+        constraint = payer.key() == pair_authority.current_authority @ ProgramError::InvalidCurrentAuthority,
+    )]
+    pub payer: Signer<'info>,
+
+    #[account(mut)]
+    pub pair_authority: Account<'info, PairAuthority>,
+
+    /// CHECK: used as field for pair_authority
+    pub new_fee_recipient: UncheckedAccount<'info>,
+}
+
+/// Handler to change the recipient of protocol fees. The change applies instantly, so a
+/// timelocked pair authority has to queue it with queue_protocol_fee_change instead.
+pub fn handler(ctx: Context<ChangeFeeRecipient>) -> Result<()> {
+    let pair_authority = &mut ctx.accounts.pair_authority;
+
+    if pair_authority.timelock_slots > 0 {
+        return Err(ProgramError::TimelockActive.into());
+    }
+
+    pair_authority.fee_recipient = ctx.accounts.new_fee_recipient.key();
+
+    emit!(ProtocolFeesChanged::new(
+        pair_authority.key(),
+        pair_authority
+    ));
+
+    Ok(())
+}
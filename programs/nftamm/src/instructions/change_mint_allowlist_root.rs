@@ -0,0 +1,30 @@
+use crate::{error::ProgramError, events::ParamsChanged, state::Pair};
+use anchor_lang::prelude::*;
+
+#[derive(Accounts)]
+pub struct ChangeMintAllowlistRoot<'info> {
+    #[account(
+        constraint = payer.key() == pair.owner @ ProgramError::InvalidOwner,
+    )]
+    pub payer: Signer<'info>,
+
+    #[account(mut)]
+    pub pair: Account<'info, Pair>,
+}
+
+pub fn handler(
+    ctx: Context<ChangeMintAllowlistRoot>,
+    new_mint_allowlist_root: Option<[u8; 32]>,
+) -> Result<()> {
+    let pair = &mut ctx.accounts.pair;
+
+    if pair.timelock_slots > 0 {
+        return Err(ProgramError::TimelockActive.into());
+    }
+
+    pair.mint_allowlist_root = new_mint_allowlist_root;
+
+    emit!(ParamsChanged::new(pair.key(), pair));
+
+    Ok(())
+}
@@ -0,0 +1,41 @@
+use crate::{
+    error::ProgramError, events::ProtocolFeesChanged, state::*, utils::validate_protocol_fees,
+};
+use anchor_lang::prelude::*;
+
+#[derive(Accounts)]
+pub struct ChangeProtocolFee<'info> {
+    #[account(
+        constraint = payer.key() == pair_authority.current_authority @ ProgramError::InvalidCurrentAuthority,
+    )]
+    pub payer: Signer<'info>,
+
+    #[account(mut)]
+    pub pair_authority: Account<'info, PairAuthority>,
+}
+
+/// Handler to change the protocol fee tier of a pair type. The change applies instantly to every
+/// pair of that type, so a timelocked pair authority has to queue it with
+/// queue_protocol_fee_change instead.
+pub fn handler(ctx: Context<ChangeProtocolFee>, pair_type: u8, new_fee: u16) -> Result<()> {
+    validate_protocol_fees(&[new_fee])?;
+
+    let pair_authority = &mut ctx.accounts.pair_authority;
+
+    if pair_authority.timelock_slots > 0 {
+        return Err(ProgramError::TimelockActive.into());
+    }
+
+    let fee = pair_authority
+        .fees
+        .get_mut(pair_type as usize)
+        .ok_or(ProgramError::InvalidPairType)?;
+    *fee = new_fee;
+
+    emit!(ProtocolFeesChanged::new(
+        pair_authority.key(),
+        pair_authority
+    ));
+
+    Ok(())
+}
@@ -0,0 +1,36 @@
+use crate::{
+    error::ProgramError, events::ParamsChanged, state::Pair, utils::validate_royalty_policy,
+};
+use anchor_lang::prelude::*;
+
+#[derive(Accounts)]
+pub struct ChangeRoyaltyPolicy<'info> {
+    #[account(
+        constraint = payer.key() == pair.owner @ ProgramError::InvalidOwner,
+    )]
+    pub payer: Signer<'info>,
+
+    #[account(mut)]
+    pub pair: Account<'info, Pair>,
+}
+
+pub fn handler(
+    ctx: Context<ChangeRoyaltyPolicy>,
+    new_royalty_policy: u8,
+    new_royalty_bps: u16,
+) -> Result<()> {
+    let pair = &mut ctx.accounts.pair;
+
+    if pair.timelock_slots > 0 {
+        return Err(ProgramError::TimelockActive.into());
+    }
+
+    validate_royalty_policy(new_royalty_policy, new_royalty_bps)?;
+
+    pair.royalty_policy = new_royalty_policy;
+    pair.royalty_bps = new_royalty_bps;
+
+    emit!(ParamsChanged::new(pair.key(), pair));
+
+    Ok(())
+}
@@ -1,4 +1,4 @@
-use crate::{error::ProgramError, state::Pair};
+use crate::{error::ProgramError, events::ParamsChanged, state::Pair};
 use anchor_lang::prelude::*;
 
 #[derive(Accounts)]
@@ -15,7 +15,13 @@ pub struct ChangeSpotPrice<'info> {
 pub fn handler(ctx: Context<ChangeSpotPrice>, new_spot_price: u64) -> Result<()> {
     let pair = &mut ctx.accounts.pair;
 
+    if pair.timelock_slots > 0 {
+        return Err(ProgramError::TimelockActive.into());
+    }
+
     pair.spot_price = new_spot_price;
 
+    emit!(ParamsChanged::new(pair.key(), pair));
+
     Ok(())
 }
@@ -1,4 +1,5 @@
 use crate::error::ProgramError;
+use crate::events::PairClosed;
 use crate::state::Pair;
 use anchor_lang::prelude::*;
 
@@ -34,5 +35,10 @@ impl<'info> ClosePair<'info> {
 
 #[access_control(ClosePair::accounts(&ctx))]
 pub fn handler(ctx: Context<ClosePair>) -> Result<()> {
+    emit!(PairClosed {
+        pair: ctx.accounts.pair.key(),
+        owner: ctx.accounts.pair_owner.key(),
+    });
+
     Ok(())
 }
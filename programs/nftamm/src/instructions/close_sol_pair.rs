@@ -0,0 +1,87 @@
+use crate::{error::ProgramError, events::PairClosed, state::Pair, utils::*};
+use anchor_lang::prelude::*;
+
+#[derive(Accounts)]
+pub struct CloseSolPair<'info> {
+    #[account(
+        mut,
+        constraint = payer.key() == pair.owner @ ProgramError::InvalidOwner,
+    )]
+    pub payer: Signer<'info>,
+
+    #[account(
+        mut,
+        close = payer,
+        constraint = pair.is_native @ ProgramError::NotNativePair,
+    )]
+    pub pair: Account<'info, Pair>,
+
+    /// CHECK: system account PDA holding the pair's lamports
+    #[account(
+        mut,
+        seeds = [b"sol", pair.key().as_ref()],
+        bump,
+        constraint = sol_vault.key() == pair.quote_token_vault @ ProgramError::InvalidQuoteTokenVault,
+    )]
+    pub sol_vault: UncheckedAccount<'info>,
+
+    /// CHECK: system account PDA holding the pair's fees in lamports
+    #[account(
+        mut,
+        seeds = [b"sol", b"fee", pair.key().as_ref()],
+        bump,
+        constraint = sol_fee_vault.key() == pair.fee_vault @ ProgramError::InvalidFeeVault,
+    )]
+    pub sol_fee_vault: UncheckedAccount<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+impl<'info> CloseSolPair<'info> {
+    fn accounts(ctx: &Context<CloseSolPair>) -> Result<()> {
+        if ctx.accounts.pair.nfts_held > 0 {
+            return Err(ProgramError::StillHoldsNfts.into());
+        }
+
+        Ok(())
+    }
+}
+
+/// Handler to close a native sol pair, returning every lamport of both vaults to the owner
+#[access_control(CloseSolPair::accounts(&ctx))]
+pub fn handler(ctx: Context<CloseSolPair>) -> Result<()> {
+    let sol_vault_bump = *ctx.bumps.get("sol_vault").unwrap();
+    let sol_fee_vault_bump = *ctx.bumps.get("sol_fee_vault").unwrap();
+    let pair_key = ctx.accounts.pair.key();
+
+    let sol_vault_seeds = &[b"sol".as_ref(), pair_key.as_ref(), &[sol_vault_bump]];
+    let sol_fee_vault_seeds = &[
+        b"sol".as_ref(),
+        b"fee".as_ref(),
+        pair_key.as_ref(),
+        &[sol_fee_vault_bump],
+    ];
+
+    transfer_sol(
+        &ctx.accounts.sol_vault.to_account_info(),
+        &ctx.accounts.payer.to_account_info(),
+        &ctx.accounts.system_program.to_account_info(),
+        Some(&[&sol_vault_seeds[..]]),
+        ctx.accounts.sol_vault.lamports(),
+    )?;
+
+    transfer_sol(
+        &ctx.accounts.sol_fee_vault.to_account_info(),
+        &ctx.accounts.payer.to_account_info(),
+        &ctx.accounts.system_program.to_account_info(),
+        Some(&[&sol_fee_vault_seeds[..]]),
+        ctx.accounts.sol_fee_vault.lamports(),
+    )?;
+
+    emit!(PairClosed {
+        pair: pair_key,
+        owner: ctx.accounts.payer.key(),
+    });
+
+    Ok(())
+}
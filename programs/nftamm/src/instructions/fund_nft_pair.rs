@@ -1,5 +1,6 @@
 use crate::{
     error::ProgramError,
+    events::NftDeposited,
     state::{Pair, PairMetadata},
     utils::*,
 };
@@ -23,8 +24,9 @@ pub struct FundNftPair<'info> {
     )]
     pub pair_metadata: Account<'info, PairMetadata>,
 
+    /// CHECK: validated in access control logic
     #[account(constraint = nft_collection_mint.key() == pair.collection_mint @ ProgramError::InvalidCollectionMint)]
-    pub nft_collection_mint: Box<Account<'info, Mint>>,
+    pub nft_collection_mint: UncheckedAccount<'info>,
 
     /// CHECK: validated in access control logic
     pub nft_collection_metadata: UncheckedAccount<'info>,
@@ -41,8 +43,10 @@ pub struct FundNftPair<'info> {
     pub owner_nft_token_account: Account<'info, TokenAccount>,
 
     #[account(
-        init,
+        init_if_needed,
         payer = payer,
+        seeds = [b"nft_account", pair.key().as_ref(), nft_token_mint.key().as_ref()],
+        bump,
         token::mint = nft_token_mint,
         token::authority = program_as_signer,
     )]
@@ -58,7 +62,7 @@ pub struct FundNftPair<'info> {
 }
 
 impl<'info> FundNftPair<'info> {
-    fn accounts(ctx: &Context<FundNftPair>) -> Result<()> {
+    fn accounts(ctx: &Context<FundNftPair>, proof: &[[u8; 32]]) -> Result<()> {
         let nft_token_mint = ctx.accounts.nft_token_mint.clone();
         let nft_token_metadata = ctx.accounts.nft_token_metadata.clone();
 
@@ -66,23 +70,31 @@ impl<'info> FundNftPair<'info> {
         let collection_metadata = ctx.accounts.nft_collection_metadata.clone();
 
         validate_nft(
+            ctx.accounts.pair.collection_mode,
             *nft_token_mint,
             nft_token_metadata,
-            *collection_mint,
+            collection_mint,
             collection_metadata,
         )?;
 
+        // Pairs with an allowlist only take in the mints it contains
+        verify_mint_allowlist(
+            &ctx.accounts.pair,
+            &ctx.accounts.nft_token_mint.key(),
+            proof,
+        )?;
+
         Ok(())
     }
 }
 
-#[access_control(FundNftPair::accounts(&ctx))]
-pub fn handler(ctx: Context<FundNftPair>) -> Result<()> {
+#[access_control(FundNftPair::accounts(&ctx, &proof))]
+pub fn handler(ctx: Context<FundNftPair>, proof: Vec<[u8; 32]>) -> Result<()> {
     let pair = &mut ctx.accounts.pair;
     let pair_metadata = &mut ctx.accounts.pair_metadata;
 
     // This can be called on nft pairs or trade pairs
-    if pair.pair_type != 1 || pair.pair_type != 2 {
+    if pair.pair_type != 1 && pair.pair_type != 2 {
         return Err(ProgramError::InvalidPairType.into());
     }
 
@@ -103,8 +115,10 @@ pub fn handler(ctx: Context<FundNftPair>) -> Result<()> {
         pair.is_active = true;
     }
 
-    let current_held = pair.nfts_held;
-    pair.nfts_held = current_held.checked_add(1).unwrap();
+    pair.nfts_held = pair
+        .nfts_held
+        .checked_add(1)
+        .ok_or(ProgramError::NumericalOverflow)?;
 
     pair_metadata.pair = pair.key();
     pair_metadata.token_mint = ctx.accounts.nft_token_mint.key();
@@ -112,5 +126,11 @@ pub fn handler(ctx: Context<FundNftPair>) -> Result<()> {
     pair_metadata.token_account = ctx.accounts.nft_token_vault.key();
     pair_metadata.creator = ctx.accounts.payer.key();
 
+    emit!(NftDeposited {
+        pair: pair.key(),
+        mint: ctx.accounts.nft_token_mint.key(),
+        nfts_held: pair.nfts_held,
+    });
+
     Ok(())
 }
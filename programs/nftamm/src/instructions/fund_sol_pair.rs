@@ -0,0 +1,59 @@
+use crate::{error::ProgramError, events::PairFunded, state::*, utils::*};
+use anchor_lang::prelude::*;
+
+#[derive(Accounts)]
+pub struct FundSolPair<'info> {
+    #[account(mut, constraint = payer.key() == pair.owner @ ProgramError::InvalidOwner)]
+    pub payer: Signer<'info>,
+
+    #[account(
+        constraint = pair_authority.key() == pair.pair_authority @ ProgramError::InvalidPairAuthority,
+    )]
+    pub pair_authority: Account<'info, PairAuthority>,
+
+    #[account(
+        mut,
+        constraint = pair.is_native @ ProgramError::NotNativePair,
+        constraint = pair.pair_type == 0 || pair.pair_type == 2 @ ProgramError::InvalidPairType,
+    )]
+    pub pair: Account<'info, Pair>,
+
+    /// CHECK: system account PDA holding the pair's lamports
+    #[account(
+        mut,
+        seeds = [b"sol", pair.key().as_ref()],
+        bump,
+        constraint = sol_vault.key() == pair.quote_token_vault @ ProgramError::InvalidQuoteTokenVault,
+    )]
+    pub sol_vault: UncheckedAccount<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+/// Handler to deposit lamports into a native sol token or trade pair
+pub fn handler(ctx: Context<FundSolPair>, amount_to_send: u64) -> Result<()> {
+    transfer_sol(
+        &ctx.accounts.payer.to_account_info(),
+        &ctx.accounts.sol_vault.to_account_info(),
+        &ctx.accounts.system_program.to_account_info(),
+        None,
+        amount_to_send,
+    )?;
+
+    let pair = &mut ctx.accounts.pair;
+
+    if pair.pair_type == 2 {
+        pair.is_active = true;
+    } else {
+        // A token pair is active as long as it can buy at least one nft
+        pair.is_active = sol_vault_balance(&ctx.accounts.sol_vault)? >= next_nft_cost(pair);
+    }
+
+    emit!(PairFunded {
+        pair: pair.key(),
+        amount: amount_to_send,
+        is_active: pair.is_active,
+    });
+
+    Ok(())
+}
@@ -1,4 +1,4 @@
-use crate::{error::ProgramError, state::*};
+use crate::{error::ProgramError, events::PairFunded, state::*, utils::next_nft_cost};
 use anchor_lang::prelude::*;
 use anchor_spl::token::{transfer, Mint, Token, TokenAccount, Transfer};
 
@@ -44,21 +44,9 @@ pub struct FundTokenPair<'info> {
 
 pub fn handler(ctx: Context<FundTokenPair>, amount_to_send: u64) -> Result<()> {
     let pair = &mut ctx.accounts.pair;
-    let pair_authority = &mut ctx.accounts.pair_authority;
-
-    let pair_authority_fees = pair_authority.fees;
-
-    let pair_auth_fee_applied = pair
-        .spot_price
-        .checked_mul(pair_authority_fees as u64)
-        .unwrap()
-        .checked_div(10000)
-        .unwrap();
-
-    let current_spot_price = pair.spot_price;
 
     // This can be called on token pairs or trade pairs
-    if pair.pair_type != 0 || pair.pair_type != 2 {
+    if pair.pair_type != 0 && pair.pair_type != 2 {
         return Err(ProgramError::InvalidPairType.into());
     }
 
@@ -75,15 +63,20 @@ pub fn handler(ctx: Context<FundTokenPair>, amount_to_send: u64) -> Result<()> {
 
     transfer(transfer_ctx, amount_to_send)?;
 
-    if pair.pair_type == 0 {
-        if ctx.accounts.quote_token_vault.amount
-            < current_spot_price
-                .checked_add(pair_auth_fee_applied)
-                .unwrap()
-        {
-            pair.is_active = false;
-        }
+    ctx.accounts.quote_token_vault.reload()?;
+
+    if pair.pair_type == 2 {
+        pair.is_active = true;
+    } else {
+        // A token pair is active as long as it can buy at least one nft
+        pair.is_active = ctx.accounts.quote_token_vault.amount >= next_nft_cost(pair);
     }
 
+    emit!(PairFunded {
+        pair: pair.key(),
+        amount: amount_to_send,
+        is_active: pair.is_active,
+    });
+
     Ok(())
 }
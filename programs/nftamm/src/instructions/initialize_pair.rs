@@ -1,7 +1,6 @@
-use crate::{error::ProgramError, state::PairAuthority};
+use crate::{error::ProgramError, events::PairCreated, state::PairAuthority};
 use anchor_lang::prelude::*;
 use anchor_spl::token::{Mint, Token, TokenAccount};
-use mpl_token_metadata::state::{Metadata, TokenMetadataAccount};
 
 use crate::{state::Pair, utils::*};
 
@@ -20,7 +19,7 @@ pub struct InitializePair<'info> {
     pub pair_authority: Account<'info, PairAuthority>,
 
     /// CHECK: validated in access control logic
-    pub nft_collection_mint: Box<Account<'info, Mint>>,
+    pub nft_collection_mint: UncheckedAccount<'info>,
     /// CHECK: validated in access control logic
     pub nft_collection_metadata: UncheckedAccount<'info>,
 
@@ -46,6 +45,18 @@ pub struct InitializePair<'info> {
     )]
     pub fee_vault: Account<'info, TokenAccount>,
 
+    // Trades credit the protocol fee vault of their quote token, so the first pair quoted in a
+    // token creates it
+    #[account(
+        init_if_needed,
+        payer = payer,
+        token::mint = quote_token_mint,
+        token::authority = program_as_signer,
+        seeds = [b"quote", "protocol_fee".as_bytes(), pair_authority.key().as_ref(), quote_token_mint.key().as_ref()],
+        bump
+    )]
+    pub protocol_fee_vault: Account<'info, TokenAccount>,
+
     pub system_program: Program<'info, System>,
     pub token_program: Program<'info, Token>,
     pub rent: Sysvar<'info, Rent>,
@@ -56,44 +67,33 @@ pub struct InitializePair<'info> {
 }
 
 impl<'info> InitializePair<'info> {
-    fn accounts(ctx: &Context<InitializePair>, pair_type: u8, bonding_curve: u8) -> Result<()> {
-        // Validate that collection metadata exists
-        let collection_mint = ctx.accounts.nft_collection_mint.clone();
-        let collection_metadata = ctx.accounts.nft_collection_metadata.clone();
-
-        assert_metadata_valid(&collection_metadata, &collection_mint.key())?;
-
-        // Check to see that Collection and CollectionDetails fields on metadata account are valid
-        let metadata: Metadata =
-            Metadata::from_account_info(&ctx.accounts.nft_collection_metadata.to_account_info())?;
-
-        let collection = metadata.collection;
-        let collection_details = metadata.collection_details;
-
-        // metadata.collection must be None
-        if collection.is_some() {
-            return Err(ProgramError::InvalidCollection.into());
+    fn accounts(
+        ctx: &Context<InitializePair>,
+        pair_type: u8,
+        bonding_curve: u8,
+        delta: u64,
+        fee: u16,
+        collection_mode: u8,
+    ) -> Result<()> {
+        // Validate that collection metadata exists and is a sized collection parent. Creator
+        // collection modes identify the collection by the nft_collection_mint address alone
+        match collection_mode {
+            0 => validate_collection(
+                &ctx.accounts.nft_collection_mint,
+                &ctx.accounts.nft_collection_metadata,
+            )?,
+            1 | 2 => {}
+            _ => return Err(ProgramError::InvalidCollectionMode.into()),
         }
 
-        // metadata.collection_details must be Some(V1 {...})
-        if collection_details.is_none() {
-            return Err(ProgramError::InvalidCollectionDetails.into());
-        }
-
-        // Validate pair type and bonding curve
-        if pair_type > 2 {
-            return Err(ProgramError::InvalidPairType.into());
-        }
-
-        if bonding_curve > 1 {
-            return Err(ProgramError::InvalidBondingCurve.into());
-        }
+        validate_pair_params(pair_type, bonding_curve, delta, fee)?;
 
         Ok(())
     }
 }
 
-#[access_control(InitializePair::accounts(&ctx, pair_type, bonding_curve))]
+#[allow(clippy::too_many_arguments)]
+#[access_control(InitializePair::accounts(&ctx, pair_type, bonding_curve, delta, fee, collection_mode))]
 pub fn handler(
     ctx: Context<InitializePair>,
     pair_type: u8,
@@ -101,43 +101,53 @@ pub fn handler(
     delta: u64,
     fee: u16,
     spot_price: u64,
-    honor_royalties: bool,
+    royalty_policy: u8,
+    royalty_bps: u16,
+    mint_allowlist_root: Option<[u8; 32]>,
+    collection_mode: u8,
 ) -> Result<()> {
-    if fee > 10000 {
-        return Err(ProgramError::InvalidFee.into());
-    }
-
-    // If bonding curve is exponential, then enforce delta to be represented in basis points
-    if bonding_curve == 1 {
-        if delta > 10000 {
-            return Err(ProgramError::InvalidDelta.into());
-        }
-    }
+    validate_royalty_policy(royalty_policy, royalty_bps)?;
 
     let pair = &mut ctx.accounts.pair;
 
-    // If the pair is not of type 2, then make sure fees is set to 0
-    if pair.pair_type != 2 {
-        if fee != 0 {
-            return Err(ProgramError::InvalidFee.into());
-        }
-    }
-
     pair.pair_authority = ctx.accounts.pair_authority.key();
     pair.owner = ctx.accounts.payer.key();
     pair.collection_mint = ctx.accounts.nft_collection_mint.key();
+    pair.collection_mode = collection_mode;
+    pair.mint_allowlist_root = mint_allowlist_root;
     pair.quote_token_mint = ctx.accounts.quote_token_mint.key();
     pair.quote_token_vault = ctx.accounts.quote_token_vault.key();
+    pair.is_native = false;
     pair.pair_type = pair_type;
     pair.bonding_curve = bonding_curve;
     pair.delta = delta;
     pair.fee = fee;
     pair.fee_vault = ctx.accounts.fee_vault.key();
     pair.spot_price = spot_price;
-    pair.honor_royalties = honor_royalties;
+    pair.clamp_spot_price = false;
+    pair.royalty_policy = royalty_policy;
+    pair.royalty_bps = royalty_bps;
     pair.trade_count = 0;
     pair.is_active = false;
     pair.nfts_held = 0;
+    pair.timelock_slots = 0;
+    pair.halt_trades_while_pending = false;
+    pair.pending_change = None;
+
+    emit!(PairCreated {
+        pair: pair.key(),
+        owner: pair.owner,
+        pair_authority: pair.pair_authority,
+        collection_mint: pair.collection_mint,
+        collection_mode: pair.collection_mode,
+        quote_token_mint: pair.quote_token_mint,
+        is_native: pair.is_native,
+        pair_type: pair.pair_type,
+        bonding_curve: pair.bonding_curve,
+        spot_price: pair.spot_price,
+        delta: pair.delta,
+        fee: pair.fee,
+    });
 
     Ok(())
 }
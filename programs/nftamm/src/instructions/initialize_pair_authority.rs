@@ -1,4 +1,4 @@
-use crate::{error::ProgramError, state::PairAuthority};
+use crate::{events::ProtocolFeesChanged, state::PairAuthority, utils::validate_protocol_fees};
 use anchor_lang::prelude::*;
 
 #[derive(Accounts)]
@@ -13,21 +13,30 @@ pub struct InitializePairAuthority<'info> {
     )]
     pub pair_authority: Account<'info, PairAuthority>,
 
+    /// CHECK: used as field for pair_authority
+    pub fee_recipient: UncheckedAccount<'info>,
+
     pub system_program: Program<'info, System>,
 }
 
-/// Handler to initialize a pair authority.
-pub fn handler(ctx: Context<InitializePairAuthority>, fees: u16) -> Result<()> {
-    // Enforce basis points convention for fees
-    if fees > 10000 {
-        return Err(ProgramError::InvalidFees.into());
-    }
+/// Handler to initialize a pair authority. `fees` are the protocol fee tiers in basis points,
+/// indexed by pair type.
+pub fn handler(ctx: Context<InitializePairAuthority>, fees: [u16; 3]) -> Result<()> {
+    validate_protocol_fees(&fees)?;
 
     let pair_authority = &mut ctx.accounts.pair_authority;
 
     pair_authority.current_authority = ctx.accounts.payer.key();
     pair_authority.pending_authority = Pubkey::default();
+    pair_authority.fee_recipient = ctx.accounts.fee_recipient.key();
     pair_authority.fees = fees;
+    pair_authority.timelock_slots = 0;
+    pair_authority.pending_fee_change = None;
+
+    emit!(ProtocolFeesChanged::new(
+        pair_authority.key(),
+        pair_authority
+    ));
 
     Ok(())
 }
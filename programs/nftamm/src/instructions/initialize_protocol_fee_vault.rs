@@ -0,0 +1,40 @@
+use crate::{error::ProgramError, state::PairAuthority};
+use anchor_lang::prelude::*;
+use anchor_spl::token::{Mint, Token, TokenAccount};
+
+#[derive(Accounts)]
+pub struct InitializeProtocolFeeVault<'info> {
+    #[account(
+        mut,
+        constraint = payer.key() == pair_authority.current_authority @ ProgramError::InvalidCurrentAuthority,
+    )]
+    pub payer: Signer<'info>,
+
+    pub pair_authority: Account<'info, PairAuthority>,
+
+    pub quote_token_mint: Box<Account<'info, Mint>>,
+
+    #[account(
+        init,
+        payer = payer,
+        token::mint = quote_token_mint,
+        token::authority = program_as_signer,
+        seeds = [b"quote", "protocol_fee".as_bytes(), pair_authority.key().as_ref(), quote_token_mint.key().as_ref()],
+        bump
+    )]
+    pub protocol_fee_vault: Account<'info, TokenAccount>,
+
+    /// CHECK: PDA used as token account authority only
+    #[account(seeds = [b"program", b"signer"], bump)]
+    pub program_as_signer: UncheckedAccount<'info>,
+
+    pub system_program: Program<'info, System>,
+    pub token_program: Program<'info, Token>,
+    pub rent: Sysvar<'info, Rent>,
+}
+
+/// Handler to create the vault escrowing protocol fees paid in a quote token. Trades in pairs
+/// quoted in that token credit this vault.
+pub fn handler(_ctx: Context<InitializeProtocolFeeVault>) -> Result<()> {
+    Ok(())
+}
@@ -0,0 +1,34 @@
+use crate::{error::ProgramError, state::PairAuthority, utils::transfer_sol};
+use anchor_lang::prelude::*;
+
+#[derive(Accounts)]
+pub struct InitializeProtocolSolFeeVault<'info> {
+    #[account(
+        mut,
+        constraint = payer.key() == pair_authority.current_authority @ ProgramError::InvalidCurrentAuthority,
+    )]
+    pub payer: Signer<'info>,
+
+    pub pair_authority: Account<'info, PairAuthority>,
+
+    /// CHECK: system account PDA holding the protocol's fees in lamports
+    #[account(mut, seeds = [b"sol", b"protocol_fee", pair_authority.key().as_ref()], bump)]
+    pub protocol_sol_fee_vault: UncheckedAccount<'info>,
+
+    pub system_program: Program<'info, System>,
+    pub rent: Sysvar<'info, Rent>,
+}
+
+/// Handler to fund the rent reserve of the vault escrowing protocol fees paid in native sol
+pub fn handler(ctx: Context<InitializeProtocolSolFeeVault>) -> Result<()> {
+    let reserve = ctx.accounts.rent.minimum_balance(0);
+    let vault = &ctx.accounts.protocol_sol_fee_vault;
+
+    transfer_sol(
+        &ctx.accounts.payer.to_account_info(),
+        &vault.to_account_info(),
+        &ctx.accounts.system_program.to_account_info(),
+        None,
+        reserve.saturating_sub(vault.lamports()),
+    )
+}
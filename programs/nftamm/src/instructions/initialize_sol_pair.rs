@@ -0,0 +1,148 @@
+use crate::{
+    error::ProgramError,
+    events::PairCreated,
+    state::{Pair, PairAuthority},
+    utils::*,
+};
+use anchor_lang::prelude::*;
+use anchor_spl::token::spl_token::native_mint;
+
+#[derive(Accounts)]
+pub struct InitializeSolPair<'info> {
+    #[account(mut)]
+    pub payer: Signer<'info>,
+
+    #[account(
+        init,
+        payer = payer,
+        space = 8 + std::mem::size_of::<Pair>()
+    )]
+    pub pair: Account<'info, Pair>,
+
+    pub pair_authority: Account<'info, PairAuthority>,
+
+    /// CHECK: validated in access control logic
+    pub nft_collection_mint: UncheckedAccount<'info>,
+    /// CHECK: validated in access control logic
+    pub nft_collection_metadata: UncheckedAccount<'info>,
+
+    /// CHECK: system account PDA holding the pair's lamports
+    #[account(mut, seeds = [b"sol", pair.key().as_ref()], bump)]
+    pub sol_vault: UncheckedAccount<'info>,
+
+    /// CHECK: system account PDA holding the pair's fees in lamports
+    #[account(mut, seeds = [b"sol", b"fee", pair.key().as_ref()], bump)]
+    pub sol_fee_vault: UncheckedAccount<'info>,
+
+    /// CHECK: system account PDA holding the protocol's fees in lamports
+    #[account(mut, seeds = [b"sol", b"protocol_fee", pair_authority.key().as_ref()], bump)]
+    pub protocol_sol_fee_vault: UncheckedAccount<'info>,
+
+    pub system_program: Program<'info, System>,
+    pub rent: Sysvar<'info, Rent>,
+}
+
+impl<'info> InitializeSolPair<'info> {
+    fn accounts(
+        ctx: &Context<InitializeSolPair>,
+        pair_type: u8,
+        bonding_curve: u8,
+        delta: u64,
+        fee: u16,
+        collection_mode: u8,
+    ) -> Result<()> {
+        // Validate that collection metadata exists and is a sized collection parent. Creator
+        // collection modes identify the collection by the nft_collection_mint address alone
+        match collection_mode {
+            0 => validate_collection(
+                &ctx.accounts.nft_collection_mint,
+                &ctx.accounts.nft_collection_metadata,
+            )?,
+            1 | 2 => {}
+            _ => return Err(ProgramError::InvalidCollectionMode.into()),
+        }
+
+        validate_pair_params(pair_type, bonding_curve, delta, fee)?;
+
+        Ok(())
+    }
+}
+
+/// Handler to initialize a pair whose quote vaults hold native sol. Both vaults, and the protocol
+/// fee vault trades credit, are funded with their rent exempt reserve by the payer so that they
+/// can receive any amount of lamports.
+#[allow(clippy::too_many_arguments)]
+#[access_control(InitializeSolPair::accounts(&ctx, pair_type, bonding_curve, delta, fee, collection_mode))]
+pub fn handler(
+    ctx: Context<InitializeSolPair>,
+    pair_type: u8,
+    bonding_curve: u8,
+    delta: u64,
+    fee: u16,
+    spot_price: u64,
+    royalty_policy: u8,
+    royalty_bps: u16,
+    mint_allowlist_root: Option<[u8; 32]>,
+    collection_mode: u8,
+) -> Result<()> {
+    validate_royalty_policy(royalty_policy, royalty_bps)?;
+
+    let reserve = ctx.accounts.rent.minimum_balance(0);
+
+    for vault in [
+        &ctx.accounts.sol_vault,
+        &ctx.accounts.sol_fee_vault,
+        &ctx.accounts.protocol_sol_fee_vault,
+    ] {
+        transfer_sol(
+            &ctx.accounts.payer.to_account_info(),
+            &vault.to_account_info(),
+            &ctx.accounts.system_program.to_account_info(),
+            None,
+            reserve.saturating_sub(vault.lamports()),
+        )?;
+    }
+
+    let pair = &mut ctx.accounts.pair;
+
+    pair.pair_authority = ctx.accounts.pair_authority.key();
+    pair.owner = ctx.accounts.payer.key();
+    pair.collection_mint = ctx.accounts.nft_collection_mint.key();
+    pair.collection_mode = collection_mode;
+    pair.mint_allowlist_root = mint_allowlist_root;
+    pair.quote_token_mint = native_mint::ID;
+    pair.quote_token_vault = ctx.accounts.sol_vault.key();
+    pair.is_native = true;
+    pair.pair_type = pair_type;
+    pair.bonding_curve = bonding_curve;
+    pair.delta = delta;
+    pair.fee = fee;
+    pair.fee_vault = ctx.accounts.sol_fee_vault.key();
+    pair.spot_price = spot_price;
+    pair.clamp_spot_price = false;
+    pair.royalty_policy = royalty_policy;
+    pair.royalty_bps = royalty_bps;
+    pair.trade_count = 0;
+    pair.is_active = false;
+    pair.nfts_held = 0;
+    pair.timelock_slots = 0;
+    pair.halt_trades_while_pending = false;
+    pair.pending_change = None;
+
+    emit!(PairCreated {
+        pair: pair.key(),
+        owner: pair.owner,
+        pair_authority: pair.pair_authority,
+        collection_mint: pair.collection_mint,
+        collection_mode: pair.collection_mode,
+        quote_token_mint: pair.quote_token_mint,
+        is_native: pair.is_native,
+        pair_type: pair.pair_type,
+        bonding_curve: pair.bonding_curve,
+        spot_price: pair.spot_price,
+        delta: pair.delta,
+        fee: pair.fee,
+    });
+
+    Ok(())
+}
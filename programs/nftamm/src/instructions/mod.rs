@@ -1,37 +1,97 @@
 #![allow(missing_docs)]
 
 pub mod accept_pair_authority;
+pub mod apply_pair_change;
+pub mod apply_protocol_fee_change;
+pub mod buy_nft_with_sol;
+pub mod buy_nfts;
+pub mod cancel_pair_change;
+pub mod cancel_protocol_fee_change;
+pub mod change_bonding_curve;
 pub mod change_delta;
 pub mod change_fee;
+pub mod change_fee_recipient;
+pub mod change_mint_allowlist_root;
+pub mod change_protocol_fee;
+pub mod change_royalty_policy;
 pub mod change_spot_price;
 pub mod close_pair;
+pub mod close_sol_pair;
 pub mod fund_nft_pair;
+pub mod fund_sol_pair;
 pub mod fund_token_pair;
 pub mod initialize_pair;
 pub mod initialize_pair_authority;
+pub mod initialize_protocol_fee_vault;
+pub mod initialize_protocol_sol_fee_vault;
+pub mod initialize_sol_pair;
+pub mod queue_pair_change;
+pub mod queue_protocol_fee_change;
+pub mod quote_buy;
+pub mod quote_sell;
+pub mod sell_nft_for_sol;
+pub mod sell_nfts;
+pub mod set_pair_timelock;
+pub mod set_protocol_fee_timelock;
+pub mod set_spot_price_clamp;
 pub mod swap_nft_trade_pair;
 pub mod swap_token_trade_pair;
 pub mod trade_nft_pair;
 pub mod trade_token_pair;
 pub mod transfer_pair_authority;
+pub mod update_pair_params;
 pub mod withdraw_fee;
 pub mod withdraw_nft;
+pub mod withdraw_protocol_fees;
+pub mod withdraw_protocol_sol_fees;
 pub mod withdraw_quote_token;
+pub mod withdraw_sol;
+pub mod withdraw_sol_fee;
 
 pub use accept_pair_authority::*;
+pub use apply_pair_change::*;
+pub use apply_protocol_fee_change::*;
+pub use buy_nft_with_sol::*;
+pub use buy_nfts::*;
+pub use cancel_pair_change::*;
+pub use cancel_protocol_fee_change::*;
+pub use change_bonding_curve::*;
 pub use change_delta::*;
 pub use change_fee::*;
+pub use change_fee_recipient::*;
+pub use change_mint_allowlist_root::*;
+pub use change_protocol_fee::*;
+pub use change_royalty_policy::*;
 pub use change_spot_price::*;
 pub use close_pair::*;
+pub use close_sol_pair::*;
 pub use fund_nft_pair::*;
+pub use fund_sol_pair::*;
 pub use fund_token_pair::*;
 pub use initialize_pair::*;
 pub use initialize_pair_authority::*;
+pub use initialize_protocol_fee_vault::*;
+pub use initialize_protocol_sol_fee_vault::*;
+pub use initialize_sol_pair::*;
+pub use queue_pair_change::*;
+pub use queue_protocol_fee_change::*;
+pub use quote_buy::*;
+pub use quote_sell::*;
+pub use sell_nft_for_sol::*;
+pub use sell_nfts::*;
+pub use set_pair_timelock::*;
+pub use set_protocol_fee_timelock::*;
+pub use set_spot_price_clamp::*;
 pub use swap_nft_trade_pair::*;
 pub use swap_token_trade_pair::*;
 pub use trade_nft_pair::*;
 pub use trade_token_pair::*;
 pub use transfer_pair_authority::*;
+pub use update_pair_params::*;
 pub use withdraw_fee::*;
 pub use withdraw_nft::*;
+pub use withdraw_protocol_fees::*;
+pub use withdraw_protocol_sol_fees::*;
 pub use withdraw_quote_token::*;
+pub use withdraw_sol::*;
+pub use withdraw_sol_fee::*;
@@ -0,0 +1,67 @@
+use crate::{
+    error::ProgramError,
+    events::ParamsChanged,
+    state::{Pair, PendingChange},
+    utils::{validate_pair_params, validate_royalty_policy},
+};
+use anchor_lang::prelude::*;
+
+#[derive(Accounts)]
+pub struct QueuePairChange<'info> {
+    #[account(
+        constraint = payer.key() == pair.owner @ ProgramError::InvalidOwner,
+    )]
+    pub payer: Signer<'info>,
+
+    #[account(mut)]
+    pub pair: Account<'info, Pair>,
+}
+
+/// Handler to queue a parameter change on a pair, replacing any change already pending. The
+/// change can be applied once the pair's timelock has elapsed.
+#[allow(clippy::too_many_arguments)]
+pub fn handler(
+    ctx: Context<QueuePairChange>,
+    new_bonding_curve: Option<u8>,
+    new_delta: Option<u64>,
+    new_fee: Option<u16>,
+    new_spot_price: Option<u64>,
+    new_royalty_policy: Option<u8>,
+    new_royalty_bps: Option<u16>,
+    new_mint_allowlist_root: Option<Option<[u8; 32]>>,
+    new_timelock_slots: Option<u64>,
+) -> Result<()> {
+    let pair = &mut ctx.accounts.pair;
+
+    validate_pair_params(
+        pair.pair_type,
+        new_bonding_curve.unwrap_or(pair.bonding_curve),
+        new_delta.unwrap_or(pair.delta),
+        new_fee.unwrap_or(pair.fee),
+    )?;
+    validate_royalty_policy(
+        new_royalty_policy.unwrap_or(pair.royalty_policy),
+        new_royalty_bps.unwrap_or(pair.royalty_bps),
+    )?;
+
+    let apply_after_slot = Clock::get()?
+        .slot
+        .checked_add(pair.timelock_slots)
+        .ok_or(ProgramError::NumericalOverflow)?;
+
+    pair.pending_change = Some(PendingChange {
+        bonding_curve: new_bonding_curve,
+        delta: new_delta,
+        fee: new_fee,
+        spot_price: new_spot_price,
+        royalty_policy: new_royalty_policy,
+        royalty_bps: new_royalty_bps,
+        mint_allowlist_root: new_mint_allowlist_root,
+        timelock_slots: new_timelock_slots,
+        apply_after_slot,
+    });
+
+    emit!(ParamsChanged::new(pair.key(), pair));
+
+    Ok(())
+}
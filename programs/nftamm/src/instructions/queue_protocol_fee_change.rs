@@ -0,0 +1,49 @@
+use crate::{
+    error::ProgramError, events::ProtocolFeesChanged, state::*, utils::validate_protocol_fees,
+};
+use anchor_lang::prelude::*;
+
+#[derive(Accounts)]
+pub struct QueueProtocolFeeChange<'info> {
+    #[account(
+        constraint = payer.key() == pair_authority.current_authority @ ProgramError::InvalidCurrentAuthority,
+    )]
+    pub payer: Signer<'info>,
+
+    #[account(mut)]
+    pub pair_authority: Account<'info, PairAuthority>,
+}
+
+/// Handler to queue a protocol fee change, replacing any change already pending. The change can
+/// be applied once the pair authority's timelock has elapsed.
+pub fn handler(
+    ctx: Context<QueueProtocolFeeChange>,
+    new_fees: Option<[u16; 3]>,
+    new_fee_recipient: Option<Pubkey>,
+    new_timelock_slots: Option<u64>,
+) -> Result<()> {
+    if let Some(fees) = new_fees {
+        validate_protocol_fees(&fees)?;
+    }
+
+    let pair_authority = &mut ctx.accounts.pair_authority;
+
+    let apply_after_slot = Clock::get()?
+        .slot
+        .checked_add(pair_authority.timelock_slots)
+        .ok_or(ProgramError::NumericalOverflow)?;
+
+    pair_authority.pending_fee_change = Some(PendingFeeChange {
+        fees: new_fees,
+        fee_recipient: new_fee_recipient,
+        timelock_slots: new_timelock_slots,
+        apply_after_slot,
+    });
+
+    emit!(ProtocolFeesChanged::new(
+        pair_authority.key(),
+        pair_authority
+    ));
+
+    Ok(())
+}
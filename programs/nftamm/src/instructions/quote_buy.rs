@@ -0,0 +1,33 @@
+use crate::{error::ProgramError, quote::quote_buy, state::*, utils::pair_reserves};
+use anchor_lang::{prelude::*, solana_program::program::set_return_data};
+
+#[derive(Accounts)]
+pub struct QuoteBuy<'info> {
+    #[account(constraint = pair_authority.key() == pair.pair_authority @ ProgramError::InvalidPairAuthority)]
+    pub pair_authority: Account<'info, PairAuthority>,
+
+    pub pair: Account<'info, Pair>,
+
+    /// CHECK: read as the pair's quote reserve, a token account or a sol vault for native pairs
+    #[account(constraint = quote_token_vault.key() == pair.quote_token_vault @ ProgramError::InvalidQuoteTokenVault)]
+    pub quote_token_vault: UncheckedAccount<'info>,
+}
+
+/// Handler to quote buying n nfts from a pair. The [crate::quote::Quote] is set as return data.
+pub fn handler(ctx: Context<QuoteBuy>, n: u64, royalty_bps: u16) -> Result<()> {
+    let reserves = pair_reserves(
+        &ctx.accounts.pair,
+        &ctx.accounts.quote_token_vault.to_account_info(),
+    )?;
+    let quote = quote_buy(
+        &ctx.accounts.pair,
+        &ctx.accounts.pair_authority,
+        reserves,
+        royalty_bps,
+        n,
+    )?;
+
+    set_return_data(&quote.try_to_vec()?);
+
+    Ok(())
+}
@@ -0,0 +1,262 @@
+use crate::{error::ProgramError, events::NftSold, quote::quote_sell, state::*, utils::*};
+use anchor_lang::prelude::*;
+use anchor_spl::token::{transfer, Mint, Token, TokenAccount, Transfer};
+
+// NFT in -> Sol out (native sol Token or Trade pair)
+//
+// remaining_accounts holds, when royalties are owed on the NFT, one creator account (mut) for
+// every creator in the NFT's metadata.
+
+#[derive(Accounts)]
+pub struct SellNftForSol<'info> {
+    #[account(mut)]
+    pub payer: Signer<'info>,
+
+    #[account(constraint = pair_authority.key() == pair.pair_authority @ ProgramError::InvalidPairAuthority)]
+    pub pair_authority: Account<'info, PairAuthority>,
+
+    /// CHECK: system account PDA holding the protocol's fees in lamports
+    #[account(mut, seeds = [b"sol", b"protocol_fee", pair_authority.key().as_ref()], bump)]
+    pub protocol_sol_fee_vault: UncheckedAccount<'info>,
+
+    #[account(
+        mut,
+        constraint = pair.is_native @ ProgramError::NotNativePair,
+        constraint = pair.pair_type == 0 || pair.pair_type == 2 @ ProgramError::InvalidPairType,
+    )]
+    pub pair: Account<'info, Pair>,
+
+    #[account(
+        init,
+        payer = payer,
+        space = 8 + std::mem::size_of::<PairMetadata>(),
+        seeds = [b"pair_metadata", pair.key().as_ref(), nft_token_mint.key().as_ref()],
+        bump
+    )]
+    pub pair_metadata: Account<'info, PairMetadata>,
+
+    /// CHECK: validated in access control logic
+    #[account(constraint = nft_collection_mint.key() == pair.collection_mint @ ProgramError::InvalidCollectionMint)]
+    pub nft_collection_mint: UncheckedAccount<'info>,
+
+    /// CHECK: validated in access control logic
+    pub nft_collection_metadata: UncheckedAccount<'info>,
+
+    pub nft_token_mint: Box<Account<'info, Mint>>,
+
+    /// CHECK: validated in access control logic
+    pub nft_token_metadata: UncheckedAccount<'info>,
+
+    #[account(
+        init_if_needed,
+        payer = payer,
+        seeds = [b"nft_account", pair.key().as_ref(), nft_token_mint.key().as_ref()],
+        bump,
+        token::mint = nft_token_mint,
+        token::authority = program_as_signer,
+    )]
+    pub nft_token_vault: Box<Account<'info, TokenAccount>>,
+
+    #[account(
+        mut,
+        constraint = user_nft_token_account.owner == payer.key() @ ProgramError::InvalidOwner,
+        constraint = user_nft_token_account.mint == nft_token_mint.key() @ ProgramError::InvalidMint,
+        constraint = user_nft_token_account.amount == 1 @ ProgramError::InsufficientBalance,
+    )]
+    pub user_nft_token_account: Box<Account<'info, TokenAccount>>,
+
+    /// CHECK: system account PDA holding the pair's lamports
+    #[account(
+        mut,
+        seeds = [b"sol", pair.key().as_ref()],
+        bump,
+        constraint = sol_vault.key() == pair.quote_token_vault @ ProgramError::InvalidQuoteTokenVault,
+        // sol_vault balance is enforced in SellNftForSol::accounts
+    )]
+    pub sol_vault: UncheckedAccount<'info>,
+
+    /// CHECK: PDA used as token account authority only
+    #[account(seeds = [b"program", b"signer"], bump)]
+    pub program_as_signer: UncheckedAccount<'info>,
+
+    pub system_program: Program<'info, System>,
+    pub token_program: Program<'info, Token>,
+    pub rent: Sysvar<'info, Rent>,
+}
+
+impl<'info> SellNftForSol<'info> {
+    fn accounts(
+        ctx: &Context<SellNftForSol>,
+        expected_trade_count: Option<u64>,
+        proof: &[[u8; 32]],
+    ) -> Result<()> {
+        let pair = ctx.accounts.pair.clone();
+
+        if !pair.is_active {
+            return Err(ProgramError::PairNotActive.into());
+        }
+
+        assert_no_pending_change(&pair)?;
+
+        if let Some(expected_trade_count) = expected_trade_count {
+            if pair.trade_count != expected_trade_count {
+                return Err(ProgramError::TradeCountMismatch.into());
+            }
+        }
+
+        validate_nft(
+            pair.collection_mode,
+            *ctx.accounts.nft_token_mint.clone(),
+            ctx.accounts.nft_token_metadata.clone(),
+            ctx.accounts.nft_collection_mint.clone(),
+            ctx.accounts.nft_collection_metadata.clone(),
+        )?;
+
+        // Pairs with an allowlist only take in the mints it contains
+        verify_mint_allowlist(
+            &ctx.accounts.pair,
+            &ctx.accounts.nft_token_mint.key(),
+            proof,
+        )?;
+
+        let royalty_bps = royalty_basis_points(&ctx.accounts.nft_token_metadata.to_account_info())?;
+        let reserves = pair_reserves(&pair, &ctx.accounts.sol_vault.to_account_info())?;
+        let quote = quote_sell(
+            &pair,
+            &ctx.accounts.pair_authority,
+            reserves,
+            royalty_bps,
+            1,
+        )?;
+
+        // The vault pays the user, the protocol fee and royalties
+        let vault_outflow = quote.sell_vault_outflow()?;
+
+        if sol_vault_balance(&ctx.accounts.sol_vault)? < vault_outflow {
+            return Err(ProgramError::InsufficientBalance.into());
+        }
+
+        Ok(())
+    }
+}
+
+/// Handler to sell an nft into a native sol pair, with the pair paying the user, the protocol
+/// and the creators in lamports
+#[access_control(SellNftForSol::accounts(&ctx, expected_trade_count, &proof))]
+pub fn handler<'info>(
+    ctx: Context<'_, '_, '_, 'info, SellNftForSol<'info>>,
+    min_price: u64,
+    expected_trade_count: Option<u64>,
+    proof: Vec<[u8; 32]>,
+) -> Result<()> {
+    let sol_vault_bump = *ctx.bumps.get("sol_vault").unwrap();
+    let pair_key = ctx.accounts.pair.key();
+
+    let royalty_bps = royalty_basis_points(&ctx.accounts.nft_token_metadata.to_account_info())?;
+    let reserves = pair_reserves(
+        &ctx.accounts.pair,
+        &ctx.accounts.sol_vault.to_account_info(),
+    )?;
+    let quote = quote_sell(
+        &ctx.accounts.pair,
+        &ctx.accounts.pair_authority,
+        reserves,
+        royalty_bps,
+        1,
+    )?;
+
+    // Effective price received by the user, net of fees
+    if quote.total < min_price {
+        return Err(ProgramError::SlippageExceeded.into());
+    }
+
+    // Transfer NFT from user to pair vault
+    let transfer_nft_accounts = Transfer {
+        from: ctx.accounts.user_nft_token_account.to_account_info(),
+        to: ctx.accounts.nft_token_vault.to_account_info(),
+        authority: ctx.accounts.payer.to_account_info(),
+    };
+
+    let transfer_nft_ctx = CpiContext::new(
+        ctx.accounts.token_program.to_account_info(),
+        transfer_nft_accounts,
+    );
+
+    transfer(transfer_nft_ctx, 1)?;
+
+    let seeds = &[b"sol".as_ref(), pair_key.as_ref(), &[sol_vault_bump]];
+    let signer = &[&seeds[..]];
+
+    let sol_vault = ctx.accounts.sol_vault.to_account_info();
+    let system_program = ctx.accounts.system_program.to_account_info();
+
+    transfer_sol(
+        &sol_vault,
+        &ctx.accounts.payer.to_account_info(),
+        &system_program,
+        Some(signer),
+        quote.total,
+    )?;
+
+    transfer_sol(
+        &sol_vault,
+        &ctx.accounts.protocol_sol_fee_vault.to_account_info(),
+        &system_program,
+        Some(signer),
+        quote.protocol_fee,
+    )?;
+
+    if quote.royalty > 0 {
+        honor_royalties_sol(
+            &mut ctx.remaining_accounts.iter(),
+            &ctx.accounts.nft_token_metadata.to_account_info(),
+            quote.royalty,
+            &sol_vault,
+            Some(signer),
+            &system_program,
+        )?;
+    }
+
+    let pair = &mut ctx.accounts.pair;
+
+    pair.spot_price = quote.new_spot_price;
+
+    pair.nfts_held = pair
+        .nfts_held
+        .checked_add(1)
+        .ok_or(ProgramError::NumericalOverflow)?;
+    pair.trade_count = pair
+        .trade_count
+        .checked_add(1)
+        .ok_or(ProgramError::NumericalOverflow)?;
+
+    // If token pair can no longer buy nfts, deactivate it
+    if pair.pair_type == 0 {
+        if sol_vault_balance(&sol_vault)? < next_nft_cost(pair) {
+            pair.is_active = false;
+        }
+    }
+
+    let pair_metadata = &mut ctx.accounts.pair_metadata;
+
+    pair_metadata.pair = pair_key;
+    pair_metadata.token_mint = ctx.accounts.nft_token_mint.key();
+    pair_metadata.collection_mint = ctx.accounts.nft_collection_mint.key();
+    pair_metadata.token_account = ctx.accounts.nft_token_vault.key();
+    pair_metadata.creator = ctx.accounts.payer.key();
+
+    emit!(NftSold {
+        pair: ctx.accounts.pair.key(),
+        mint: ctx.accounts.nft_token_mint.key(),
+        seller: ctx.accounts.payer.key(),
+        price: quote.price,
+        protocol_fee: quote.protocol_fee,
+        pair_fee: quote.pair_fee,
+        royalty: quote.royalty,
+        total: quote.total,
+        new_spot_price: ctx.accounts.pair.spot_price,
+        trade_count: ctx.accounts.pair.trade_count,
+    });
+
+    Ok(())
+}
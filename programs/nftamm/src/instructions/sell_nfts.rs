@@ -0,0 +1,421 @@
+use crate::{
+    error::ProgramError,
+    events::NftSold,
+    quote::{quote_sell, Quote},
+    state::*,
+    utils::*,
+};
+use anchor_lang::prelude::*;
+use anchor_spl::{
+    associated_token::AssociatedToken,
+    token::{initialize_account, transfer, InitializeAccount, Mint, Token, TokenAccount, Transfer},
+};
+
+// Many NFTs in -> Token out (Token or Trade pair)
+//
+// remaining_accounts holds one group of accounts per NFT sold, in order:
+//   0. nft_token_mint
+//   1. nft_token_metadata
+//   2. pair_metadata (mut), uninitialized PDA
+//   3. nft_token_vault (mut), PDA of [b"nft_account", pair, nft_token_mint], created if empty
+//   4. user_nft_token_account (mut)
+// followed, when royalties are owed on the NFT, by a (creator, creator quote token ATA (mut))
+// pair for every creator in the NFT's metadata.
+//
+// proofs holds the mint allowlist proof of each NFT in the same order, and may be empty when the
+// pair has no allowlist.
+
+#[derive(Accounts)]
+pub struct SellNfts<'info> {
+    #[account(mut)]
+    pub payer: Signer<'info>,
+
+    #[account(constraint = pair_authority.key() == pair.pair_authority @ ProgramError::InvalidPairAuthority)]
+    pub pair_authority: Account<'info, PairAuthority>,
+
+    #[account(
+        mut,
+        seeds = [b"quote", "protocol_fee".as_bytes(), pair_authority.key().as_ref(), quote_token_mint.key().as_ref()],
+        bump,
+    )]
+    pub protocol_fee_vault: Box<Account<'info, TokenAccount>>,
+
+    #[account(
+        mut,
+        constraint = pair.pair_type == 0 || pair.pair_type == 2 @ ProgramError::InvalidPairType,
+    )]
+    pub pair: Account<'info, Pair>,
+
+    /// CHECK: validated in access control logic
+    #[account(constraint = nft_collection_mint.key() == pair.collection_mint @ ProgramError::InvalidMint)]
+    pub nft_collection_mint: UncheckedAccount<'info>,
+
+    /// CHECK: validated in access control logic
+    pub nft_collection_metadata: UncheckedAccount<'info>,
+
+    #[account(constraint = quote_token_mint.key() == pair.quote_token_mint @ ProgramError::InvalidQuoteTokenMint)]
+    pub quote_token_mint: Box<Account<'info, Mint>>,
+
+    #[account(
+        mut,
+        seeds = [b"quote", pair.key().as_ref()],
+        bump,
+        constraint = quote_token_vault.key() == pair.quote_token_vault @ ProgramError::InvalidQuoteTokenVault,
+        constraint = quote_token_vault.mint == quote_token_mint.key() @ ProgramError::InvalidQuoteTokenMint,
+        constraint = quote_token_vault.owner == program_as_signer.key() @ ProgramError::InvalidOwner,
+    )]
+    pub quote_token_vault: Box<Account<'info, TokenAccount>>,
+
+    #[account(
+        init_if_needed,
+        payer = payer,
+        associated_token::mint = quote_token_mint,
+        associated_token::authority = payer,
+    )]
+    pub user_quote_token_account: Box<Account<'info, TokenAccount>>,
+
+    /// CHECK: PDA used as token account authority only
+    #[account(seeds = [b"program", b"signer"], bump)]
+    pub program_as_signer: UncheckedAccount<'info>,
+
+    pub system_program: Program<'info, System>,
+    pub token_program: Program<'info, Token>,
+    pub associated_token_program: Program<'info, AssociatedToken>,
+    pub rent: Sysvar<'info, Rent>,
+}
+
+impl<'info> SellNfts<'info> {
+    fn accounts(ctx: &Context<SellNfts>) -> Result<()> {
+        if ctx.remaining_accounts.is_empty() {
+            return Err(ProgramError::InvalidRemainingAccounts.into());
+        }
+
+        if !ctx.accounts.pair.is_active {
+            return Err(ProgramError::PairNotActive.into());
+        }
+
+        assert_no_pending_change(&ctx.accounts.pair)?;
+
+        Ok(())
+    }
+}
+
+/// Handler to sell every nft passed through remaining_accounts into a pair, walking the bonding
+/// curve once per nft.
+#[access_control(SellNfts::accounts(&ctx))]
+pub fn handler<'info>(
+    ctx: Context<'_, '_, '_, 'info, SellNfts<'info>>,
+    min_total_output: u64,
+    proofs: Vec<Vec<[u8; 32]>>,
+) -> Result<()> {
+    let program_as_signer_bump = *ctx.bumps.get("program_as_signer").unwrap();
+    let remaining_accounts = &mut ctx.remaining_accounts.iter();
+
+    let pair_key = ctx.accounts.pair.key();
+    let mut total = Quote {
+        new_spot_price: ctx.accounts.pair.spot_price,
+        ..Quote::default()
+    };
+    let mut nfts_sold: u32 = 0;
+    let mut reserves = pair_reserves(
+        &ctx.accounts.pair,
+        &ctx.accounts.quote_token_vault.to_account_info(),
+    )?;
+
+    while !remaining_accounts.as_slice().is_empty() {
+        let nft_token_mint_info = next_account_info(remaining_accounts)?;
+        let nft_token_metadata_info = next_account_info(remaining_accounts)?;
+        let pair_metadata_info = next_account_info(remaining_accounts)?;
+        let nft_token_vault_info = next_account_info(remaining_accounts)?;
+        let user_nft_token_account_info = next_account_info(remaining_accounts)?;
+
+        let nft_token_mint: Account<Mint> = Account::try_from(nft_token_mint_info)?;
+
+        validate_nft(
+            ctx.accounts.pair.collection_mode,
+            nft_token_mint,
+            UncheckedAccount::try_from(nft_token_metadata_info.clone()),
+            ctx.accounts.nft_collection_mint.clone(),
+            ctx.accounts.nft_collection_metadata.clone(),
+        )?;
+
+        // Pairs with an allowlist only take in the mints it contains
+        let proof = proofs
+            .get(nfts_sold as usize)
+            .map(Vec::as_slice)
+            .unwrap_or_default();
+        verify_mint_allowlist(&ctx.accounts.pair, nft_token_mint_info.key, proof)?;
+
+        let user_nft_token_account: Account<TokenAccount> =
+            Account::try_from(user_nft_token_account_info)?;
+
+        if user_nft_token_account.owner != ctx.accounts.payer.key() {
+            return Err(ProgramError::InvalidOwner.into());
+        }
+
+        if user_nft_token_account.mint != *nft_token_mint_info.key {
+            return Err(ProgramError::InvalidMint.into());
+        }
+
+        if user_nft_token_account.amount != 1 {
+            return Err(ProgramError::InsufficientBalance.into());
+        }
+
+        if nft_token_vault_info.data_is_empty() {
+            create_nft_vault(&ctx, nft_token_vault_info, nft_token_mint_info)?;
+        } else {
+            assert_derivation(
+                ctx.program_id,
+                nft_token_vault_info,
+                &[
+                    b"nft_account",
+                    pair_key.as_ref(),
+                    nft_token_mint_info.key.as_ref(),
+                ],
+            )?;
+
+            let nft_token_vault: Account<TokenAccount> = Account::try_from(nft_token_vault_info)?;
+
+            if nft_token_vault.mint != *nft_token_mint_info.key {
+                return Err(ProgramError::InvalidMint.into());
+            }
+
+            if nft_token_vault.owner != ctx.accounts.program_as_signer.key() {
+                return Err(ProgramError::InvalidOwner.into());
+            }
+        }
+
+        let transfer_nft_accounts = Transfer {
+            from: user_nft_token_account_info.clone(),
+            to: nft_token_vault_info.clone(),
+            authority: ctx.accounts.payer.to_account_info(),
+        };
+
+        let transfer_nft_ctx = CpiContext::new(
+            ctx.accounts.token_program.to_account_info(),
+            transfer_nft_accounts,
+        );
+
+        transfer(transfer_nft_ctx, 1)?;
+
+        let pair_metadata = PairMetadata {
+            pair: pair_key,
+            token_mint: *nft_token_mint_info.key,
+            collection_mint: ctx.accounts.nft_collection_mint.key(),
+            token_account: *nft_token_vault_info.key,
+            creator: ctx.accounts.payer.key(),
+        };
+
+        create_pair_metadata(&ctx, pair_metadata_info, &pair_metadata)?;
+
+        // Walk the curve one nft at a time
+        let royalty_bps = royalty_basis_points(nft_token_metadata_info)?;
+        let pair = &mut ctx.accounts.pair;
+        let quote = quote_sell(pair, &ctx.accounts.pair_authority, reserves, royalty_bps, 1)?;
+        reserves = reserves.after_sell(quote.sell_vault_outflow()?)?;
+        total.merge(&quote)?;
+        pair.spot_price = quote.new_spot_price;
+
+        if quote.royalty > 0 {
+            honor_royalties(
+                true,
+                Some(program_as_signer_bump),
+                remaining_accounts,
+                nft_token_metadata_info,
+                quote.royalty,
+                &ctx.accounts.associated_token_program.to_account_info(),
+                &ctx.accounts.payer.to_account_info(),
+                &ctx.accounts.quote_token_vault.to_account_info(),
+                &ctx.accounts.program_as_signer.to_account_info(),
+                &ctx.accounts.quote_token_mint.to_account_info(),
+                &ctx.accounts.system_program.to_account_info(),
+                &ctx.accounts.token_program.to_account_info(),
+                &ctx.accounts.rent.to_account_info(),
+            )?;
+        }
+
+        nfts_sold = nfts_sold
+            .checked_add(1)
+            .ok_or(ProgramError::NumericalOverflow)?;
+
+        // trade_count is only written once all nfts have been traded
+        let pair = &ctx.accounts.pair;
+        emit!(NftSold {
+            pair: pair_key,
+            mint: nft_token_mint_info.key(),
+            seller: ctx.accounts.payer.key(),
+            price: quote.price,
+            protocol_fee: quote.protocol_fee,
+            pair_fee: quote.pair_fee,
+            royalty: quote.royalty,
+            total: quote.total,
+            new_spot_price: pair.spot_price,
+            trade_count: pair
+                .trade_count
+                .checked_add(nfts_sold as u64)
+                .ok_or(ProgramError::NumericalOverflow)?,
+        });
+    }
+
+    if total.total < min_total_output {
+        return Err(ProgramError::SlippageExceeded.into());
+    }
+
+    let vault_outflow = total.sell_vault_outflow()?;
+
+    // quote_token_vault still holds the balance from before royalties were paid
+    if ctx.accounts.quote_token_vault.amount < vault_outflow {
+        return Err(ProgramError::InsufficientBalance.into());
+    }
+
+    let seeds = &[
+        "program".as_bytes(),
+        "signer".as_bytes(),
+        &[program_as_signer_bump],
+    ];
+
+    let signer = &[&seeds[..]];
+
+    let transfer_quote_accounts = Transfer {
+        from: ctx.accounts.quote_token_vault.to_account_info(),
+        to: ctx.accounts.user_quote_token_account.to_account_info(),
+        authority: ctx.accounts.program_as_signer.to_account_info(),
+    };
+
+    let transfer_quote_ctx = CpiContext::new_with_signer(
+        ctx.accounts.token_program.to_account_info(),
+        transfer_quote_accounts,
+        signer,
+    );
+
+    transfer(transfer_quote_ctx, total.total)?;
+
+    let transfer_pair_authority_fee_accounts = Transfer {
+        from: ctx.accounts.quote_token_vault.to_account_info(),
+        to: ctx.accounts.protocol_fee_vault.to_account_info(),
+        authority: ctx.accounts.program_as_signer.to_account_info(),
+    };
+
+    let transfer_pair_authority_fee_ctx = CpiContext::new_with_signer(
+        ctx.accounts.token_program.to_account_info(),
+        transfer_pair_authority_fee_accounts,
+        signer,
+    );
+
+    transfer(transfer_pair_authority_fee_ctx, total.protocol_fee)?;
+
+    ctx.accounts.quote_token_vault.reload()?;
+
+    let pair = &mut ctx.accounts.pair;
+
+    pair.nfts_held = pair
+        .nfts_held
+        .checked_add(nfts_sold)
+        .ok_or(ProgramError::NumericalOverflow)?;
+    pair.trade_count = pair
+        .trade_count
+        .checked_add(nfts_sold as u64)
+        .ok_or(ProgramError::NumericalOverflow)?;
+
+    // If token pair can no longer buy nfts, deactivate it
+    if pair.pair_type == 0 {
+        if ctx.accounts.quote_token_vault.amount < next_nft_cost(pair) {
+            pair.is_active = false;
+        }
+    }
+
+    Ok(())
+}
+
+// Create and initialize the PDA token account that holds a sold nft
+fn create_nft_vault<'info>(
+    ctx: &Context<'_, '_, '_, 'info, SellNfts<'info>>,
+    nft_token_vault_info: &AccountInfo<'info>,
+    nft_token_mint_info: &AccountInfo<'info>,
+) -> Result<()> {
+    let pair_key = ctx.accounts.pair.key();
+
+    let bump = assert_derivation(
+        ctx.program_id,
+        nft_token_vault_info,
+        &[
+            b"nft_account",
+            pair_key.as_ref(),
+            nft_token_mint_info.key.as_ref(),
+        ],
+    )?;
+
+    let seeds = &[
+        "nft_account".as_bytes(),
+        pair_key.as_ref(),
+        nft_token_mint_info.key.as_ref(),
+        &[bump],
+    ];
+
+    let signer = &[&seeds[..]];
+
+    create_pda_account(
+        &ctx.accounts.payer.to_account_info(),
+        nft_token_vault_info,
+        &ctx.accounts.system_program.to_account_info(),
+        signer,
+        TokenAccount::LEN,
+        &ctx.accounts.token_program.key(),
+    )?;
+
+    let initialize_accounts = InitializeAccount {
+        account: nft_token_vault_info.clone(),
+        mint: nft_token_mint_info.clone(),
+        authority: ctx.accounts.program_as_signer.to_account_info(),
+        rent: ctx.accounts.rent.to_account_info(),
+    };
+
+    let initialize_ctx = CpiContext::new(
+        ctx.accounts.token_program.to_account_info(),
+        initialize_accounts,
+    );
+
+    initialize_account(initialize_ctx)
+}
+
+// Create the pair_metadata PDA for a sold nft and write its data
+fn create_pair_metadata<'info>(
+    ctx: &Context<'_, '_, '_, 'info, SellNfts<'info>>,
+    pair_metadata_info: &AccountInfo<'info>,
+    pair_metadata: &PairMetadata,
+) -> Result<()> {
+    let bump = assert_derivation(
+        ctx.program_id,
+        pair_metadata_info,
+        &[
+            b"pair_metadata",
+            pair_metadata.pair.as_ref(),
+            pair_metadata.token_mint.as_ref(),
+        ],
+    )?;
+
+    let seeds = &[
+        "pair_metadata".as_bytes(),
+        pair_metadata.pair.as_ref(),
+        pair_metadata.token_mint.as_ref(),
+        &[bump],
+    ];
+
+    let signer = &[&seeds[..]];
+
+    let space = 8 + std::mem::size_of::<PairMetadata>();
+
+    create_pda_account(
+        &ctx.accounts.payer.to_account_info(),
+        pair_metadata_info,
+        &ctx.accounts.system_program.to_account_info(),
+        signer,
+        space,
+        ctx.program_id,
+    )?;
+
+    let mut data = pair_metadata_info.try_borrow_mut_data()?;
+    pair_metadata.try_serialize(&mut &mut data[..])?;
+
+    Ok(())
+}
@@ -0,0 +1,34 @@
+use crate::{error::ProgramError, events::ParamsChanged, state::Pair};
+use anchor_lang::prelude::*;
+
+#[derive(Accounts)]
+pub struct SetPairTimelock<'info> {
+    #[account(
+        constraint = payer.key() == pair.owner @ ProgramError::InvalidOwner,
+    )]
+    pub payer: Signer<'info>,
+
+    #[account(mut)]
+    pub pair: Account<'info, Pair>,
+}
+
+/// Handler to raise the timelock of a pair. Lowering it has to go through queue_pair_change so
+/// that takers get the current timelock's notice.
+pub fn handler(
+    ctx: Context<SetPairTimelock>,
+    new_timelock_slots: u64,
+    halt_trades_while_pending: bool,
+) -> Result<()> {
+    let pair = &mut ctx.accounts.pair;
+
+    if new_timelock_slots < pair.timelock_slots {
+        return Err(ProgramError::TimelockActive.into());
+    }
+
+    pair.timelock_slots = new_timelock_slots;
+    pair.halt_trades_while_pending = halt_trades_while_pending;
+
+    emit!(ParamsChanged::new(pair.key(), pair));
+
+    Ok(())
+}
@@ -0,0 +1,32 @@
+use crate::{error::ProgramError, events::ProtocolFeesChanged, state::*};
+use anchor_lang::prelude::*;
+
+#[derive(Accounts)]
+pub struct SetProtocolFeeTimelock<'info> {
+    #[account(
+        constraint = payer.key() == pair_authority.current_authority @ ProgramError::InvalidCurrentAuthority,
+    )]
+    pub payer: Signer<'info>,
+
+    #[account(mut)]
+    pub pair_authority: Account<'info, PairAuthority>,
+}
+
+/// Handler to raise the timelock on protocol fee changes. Lowering it has to go through
+/// queue_protocol_fee_change so that pair owners get the current timelock's notice.
+pub fn handler(ctx: Context<SetProtocolFeeTimelock>, new_timelock_slots: u64) -> Result<()> {
+    let pair_authority = &mut ctx.accounts.pair_authority;
+
+    if new_timelock_slots < pair_authority.timelock_slots {
+        return Err(ProgramError::TimelockActive.into());
+    }
+
+    pair_authority.timelock_slots = new_timelock_slots;
+
+    emit!(ProtocolFeesChanged::new(
+        pair_authority.key(),
+        pair_authority
+    ));
+
+    Ok(())
+}
@@ -0,0 +1,26 @@
+use crate::{error::ProgramError, events::ParamsChanged, state::Pair};
+use anchor_lang::prelude::*;
+
+#[derive(Accounts)]
+pub struct SetSpotPriceClamp<'info> {
+    #[account(
+        constraint = payer.key() == pair.owner @ ProgramError::InvalidOwner,
+    )]
+    pub payer: Signer<'info>,
+
+    #[account(mut)]
+    pub pair: Account<'info, Pair>,
+}
+
+/// Handler to choose whether linear sells that would move the spot price below zero are
+/// rejected or stop the spot price at zero. Either way no trade executes at a different price,
+/// so the pair's timelock does not apply.
+pub fn handler(ctx: Context<SetSpotPriceClamp>, clamp_spot_price: bool) -> Result<()> {
+    let pair = &mut ctx.accounts.pair;
+
+    pair.clamp_spot_price = clamp_spot_price;
+
+    emit!(ParamsChanged::new(pair.key(), pair));
+
+    Ok(())
+}
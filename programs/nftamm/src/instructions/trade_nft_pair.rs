@@ -1,4 +1,4 @@
-use crate::{error::ProgramError, state::*, utils::*};
+use crate::{error::ProgramError, events::NftBought, quote::quote_buy, state::*, utils::*};
 use anchor_lang::prelude::*;
 use anchor_spl::{
     associated_token::AssociatedToken,
@@ -13,19 +13,12 @@ pub struct TradeNftPair<'info> {
     #[account(constraint = pair_authority.key() == pair.pair_authority @ ProgramError::InvalidPairAuthority)]
     pub pair_authority: Account<'info, PairAuthority>,
 
-    /// CHECK: only used as authority target for pair_authority_quote_token_account
     #[account(
-        constraint = current_authority.key() == pair_authority.current_authority @ ProgramError::InvalidCurrentAuthority,
-    )]
-    pub current_authority: UncheckedAccount<'info>,
-
-    #[account(
-        init_if_needed,
-        payer = payer,
-        associated_token::mint = quote_token_mint,
-        associated_token::authority = current_authority
+        mut,
+        seeds = [b"quote", "protocol_fee".as_bytes(), pair_authority.key().as_ref(), quote_token_mint.key().as_ref()],
+        bump,
     )]
-    pub pair_authority_quote_token_account: Box<Account<'info, TokenAccount>>,
+    pub protocol_fee_vault: Box<Account<'info, TokenAccount>>,
 
     #[account(mut, constraint = pair.pair_type == 1)]
     pub pair: Account<'info, Pair>,
@@ -45,8 +38,9 @@ pub struct TradeNftPair<'info> {
     )]
     pub pair_metadata: Account<'info, PairMetadata>,
 
+    /// CHECK: validated in access control logic
     #[account(constraint = nft_collection_mint.key() == pair.collection_mint)]
-    pub nft_collection_mint: Box<Account<'info, Mint>>,
+    pub nft_collection_mint: UncheckedAccount<'info>,
 
     /// CHECK: validated in access control logic
     pub nft_collection_metadata: UncheckedAccount<'info>,
@@ -103,34 +97,22 @@ pub struct TradeNftPair<'info> {
 }
 
 impl<'info> TradeNftPair<'info> {
-    fn accounts(ctx: &Context<TradeNftPair>) -> Result<()> {
+    fn accounts(ctx: &Context<TradeNftPair>, expected_trade_count: Option<u64>) -> Result<()> {
         let pair = ctx.accounts.pair.clone();
         let pair_authority = ctx.accounts.pair_authority.clone();
-        let token_metadata = ctx.accounts.nft_token_metadata.clone();
-
-        let pair_authority_fees = pair_authority.fees;
-
-        let pair_auth_fee_applied = pair
-            .spot_price
-            .checked_mul(pair_authority_fees as u64)
-            .unwrap()
-            .checked_div(10000)
-            .unwrap();
-
-        if pair.honor_royalties {
-            // calc spotprice + pairauthfee + royaltyhonors
-        } else {
-            if ctx.accounts.user_quote_token_account.amount
-                < pair.spot_price.checked_add(pair_auth_fee_applied).unwrap()
-            {
-                return Err(ProgramError::InsufficientBalance.into());
-            }
-        }
 
         if !pair.is_active {
             return Err(ProgramError::PairNotActive.into());
         }
 
+        assert_no_pending_change(&pair)?;
+
+        if let Some(expected_trade_count) = expected_trade_count {
+            if pair.trade_count != expected_trade_count {
+                return Err(ProgramError::TradeCountMismatch.into());
+            }
+        }
+
         let nft_token_mint = ctx.accounts.nft_token_mint.clone();
         let nft_token_metadata = ctx.accounts.nft_token_metadata.clone();
 
@@ -138,37 +120,48 @@ impl<'info> TradeNftPair<'info> {
         let collection_metadata = ctx.accounts.nft_collection_metadata.clone();
 
         validate_nft(
+            pair.collection_mode,
             *nft_token_mint,
             nft_token_metadata,
-            *collection_mint,
+            collection_mint,
             collection_metadata,
         )?;
 
+        let royalty_bps = royalty_basis_points(&ctx.accounts.nft_token_metadata.to_account_info())?;
+        let reserves = pair_reserves(&pair, &ctx.accounts.quote_token_vault.to_account_info())?;
+        let quote = quote_buy(&pair, &pair_authority, reserves, royalty_bps, 1)?;
+
+        // The user pays the price, fees and royalties
+        if ctx.accounts.user_quote_token_account.amount < quote.total {
+            return Err(ProgramError::InsufficientBalance.into());
+        }
+
         Ok(())
     }
 }
 
-#[access_control(TradeNftPair::accounts(&ctx))]
-pub fn handler(ctx: Context<TradeNftPair>) -> Result<()> {
+#[access_control(TradeNftPair::accounts(&ctx, expected_trade_count))]
+pub fn handler<'info>(
+    ctx: Context<'_, '_, '_, 'info, TradeNftPair<'info>>,
+    max_price: u64,
+    expected_trade_count: Option<u64>,
+) -> Result<()> {
     let pair = &mut ctx.accounts.pair;
     let pair_authority = &ctx.accounts.pair_authority;
     let program_as_signer_bump = *ctx.bumps.get("program_as_signer").unwrap();
 
-    let pair_authority_fees = pair_authority.fees;
+    let royalty_bps = royalty_basis_points(&ctx.accounts.nft_token_metadata.to_account_info())?;
+    let reserves = pair_reserves(pair, &ctx.accounts.quote_token_vault.to_account_info())?;
+    let quote = quote_buy(pair, pair_authority, reserves, royalty_bps, 1)?;
 
-    let pair_auth_fee_applied = pair
-        .spot_price
-        .checked_mul(pair_authority_fees as u64)
-        .unwrap()
-        .checked_div(10000)
-        .unwrap();
+    // Effective price paid by the user, including fees
+    if quote.total > max_price {
+        return Err(ProgramError::SlippageExceeded.into());
+    }
 
     let transfer_pair_authority_fee_accounts = Transfer {
         from: ctx.accounts.user_quote_token_account.to_account_info(),
-        to: ctx
-            .accounts
-            .pair_authority_quote_token_account
-            .to_account_info(),
+        to: ctx.accounts.protocol_fee_vault.to_account_info(),
         authority: ctx.accounts.payer.to_account_info(),
     };
 
@@ -177,7 +170,7 @@ pub fn handler(ctx: Context<TradeNftPair>) -> Result<()> {
         transfer_pair_authority_fee_accounts,
     );
 
-    transfer(transfer_pair_authority_fees_ctx, pair_auth_fee_applied)?;
+    transfer(transfer_pair_authority_fees_ctx, quote.protocol_fee)?;
 
     let transfer_quote_accounts = Transfer {
         from: ctx.accounts.user_quote_token_account.to_account_info(),
@@ -190,7 +183,7 @@ pub fn handler(ctx: Context<TradeNftPair>) -> Result<()> {
         transfer_quote_accounts,
     );
 
-    transfer(transfer_quote_ctx, pair.spot_price)?;
+    transfer(transfer_quote_ctx, quote.price)?;
 
     let transfer_nft_accounts = Transfer {
         from: ctx.accounts.nft_token_vault.to_account_info(),
@@ -214,32 +207,51 @@ pub fn handler(ctx: Context<TradeNftPair>) -> Result<()> {
 
     transfer(transfer_nft_ctx, 1)?;
 
-    let bonding_curve = pair.bonding_curve;
-    let current_spot_price = pair.spot_price;
-
-    if bonding_curve == 0 {
-        let delta = pair.delta;
-
-        let new_spot_price = current_spot_price.checked_add(delta).unwrap();
-        pair.spot_price = new_spot_price;
-    } else {
-        let delta = pair.delta;
-
-        let add = current_spot_price
-            .checked_mul(delta as u64)
-            .unwrap()
-            .checked_div(10000)
-            .unwrap();
-
-        pair.spot_price = current_spot_price.checked_add(add).unwrap();
+    if quote.royalty > 0 {
+        honor_royalties(
+            false,
+            None,
+            &mut ctx.remaining_accounts.iter(),
+            &ctx.accounts.nft_token_metadata.to_account_info(),
+            quote.royalty,
+            &ctx.accounts.associated_token_program.to_account_info(),
+            &ctx.accounts.payer.to_account_info(),
+            &ctx.accounts.user_quote_token_account.to_account_info(),
+            &ctx.accounts.payer.to_account_info(),
+            &ctx.accounts.quote_token_mint.to_account_info(),
+            &ctx.accounts.system_program.to_account_info(),
+            &ctx.accounts.token_program.to_account_info(),
+            &ctx.accounts.rent.to_account_info(),
+        )?;
     }
 
-    pair.nfts_held = pair.nfts_held.checked_sub(1).unwrap();
-    pair.trade_count = pair.trade_count.checked_add(1).unwrap();
+    pair.spot_price = quote.new_spot_price;
+
+    pair.nfts_held = pair
+        .nfts_held
+        .checked_sub(1)
+        .ok_or(ProgramError::NumericalOverflow)?;
+    pair.trade_count = pair
+        .trade_count
+        .checked_add(1)
+        .ok_or(ProgramError::NumericalOverflow)?;
 
     if pair.nfts_held == 0 {
         pair.is_active = false;
     }
 
+    emit!(NftBought {
+        pair: ctx.accounts.pair.key(),
+        mint: ctx.accounts.nft_token_mint.key(),
+        buyer: ctx.accounts.payer.key(),
+        price: quote.price,
+        protocol_fee: quote.protocol_fee,
+        pair_fee: quote.pair_fee,
+        royalty: quote.royalty,
+        total: quote.total,
+        new_spot_price: ctx.accounts.pair.spot_price,
+        trade_count: ctx.accounts.pair.trade_count,
+    });
+
     Ok(())
 }
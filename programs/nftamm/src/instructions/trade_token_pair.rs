@@ -1,4 +1,4 @@
-use crate::{error::ProgramError, state::*, utils::*};
+use crate::{error::ProgramError, events::NftSold, quote::quote_sell, state::*, utils::*};
 use anchor_lang::prelude::*;
 use anchor_spl::{
     associated_token::AssociatedToken,
@@ -13,19 +13,12 @@ pub struct TradeTokenPair<'info> {
     #[account(constraint = pair_authority.key() == pair.pair_authority @ ProgramError::InvalidPairAuthority)]
     pub pair_authority: Account<'info, PairAuthority>,
 
-    /// CHECK: only used as authority target for pair_authority_quote_token_account
     #[account(
-        constraint = current_authority.key() == pair_authority.current_authority @ ProgramError::InvalidCurrentAuthority,
-    )]
-    pub current_authority: UncheckedAccount<'info>,
-
-    #[account(
-        init_if_needed,
-        payer = payer,
-        associated_token::mint = quote_token_mint,
-        associated_token::authority = current_authority
+        mut,
+        seeds = [b"quote", "protocol_fee".as_bytes(), pair_authority.key().as_ref(), quote_token_mint.key().as_ref()],
+        bump,
     )]
-    pub pair_authority_quote_token_account: Box<Account<'info, TokenAccount>>,
+    pub protocol_fee_vault: Box<Account<'info, TokenAccount>>,
 
     #[account(
         mut,
@@ -42,8 +35,9 @@ pub struct TradeTokenPair<'info> {
     )]
     pub pair_metadata: Account<'info, PairMetadata>,
 
+    /// CHECK: validated in access control logic
     #[account(constraint = nft_collection_mint.key() == pair.collection_mint @ ProgramError::InvalidMint)]
-    pub nft_collection_mint: Box<Account<'info, Mint>>,
+    pub nft_collection_mint: UncheckedAccount<'info>,
 
     /// CHECK: validated in access control logic
     pub nft_collection_metadata: UncheckedAccount<'info>,
@@ -54,8 +48,10 @@ pub struct TradeTokenPair<'info> {
     pub nft_token_metadata: UncheckedAccount<'info>,
 
     #[account(
-        init,
+        init_if_needed,
         payer = payer,
+        seeds = [b"nft_account", pair.key().as_ref(), nft_token_mint.key().as_ref()],
+        bump,
         token::mint = nft_token_mint,
         token::authority = program_as_signer,
     )]
@@ -99,29 +95,26 @@ pub struct TradeTokenPair<'info> {
 }
 
 impl<'info> TradeTokenPair<'info> {
-    fn accounts(ctx: &Context<TradeTokenPair>) -> Result<()> {
+    fn accounts(
+        ctx: &Context<TradeTokenPair>,
+        expected_trade_count: Option<u64>,
+        proof: &[[u8; 32]],
+    ) -> Result<()> {
         let pair = ctx.accounts.pair.clone();
         let pair_authority = ctx.accounts.pair_authority.clone();
 
-        let pair_authority_fees = pair_authority.fees;
-
-        let pair_auth_fee_applied = pair
-            .spot_price
-            .checked_mul(pair_authority_fees as u64)
-            .unwrap()
-            .checked_div(10000)
-            .unwrap();
-
-        if ctx.accounts.quote_token_vault.amount
-            < pair.spot_price.checked_add(pair_auth_fee_applied).unwrap()
-        {
-            return Err(ProgramError::InsufficientBalance.into());
-        }
-
         if !pair.is_active {
             return Err(ProgramError::PairNotActive.into());
         }
 
+        assert_no_pending_change(&pair)?;
+
+        if let Some(expected_trade_count) = expected_trade_count {
+            if pair.trade_count != expected_trade_count {
+                return Err(ProgramError::TradeCountMismatch.into());
+            }
+        }
+
         let nft_token_mint = ctx.accounts.nft_token_mint.clone();
         let nft_token_metadata = ctx.accounts.nft_token_metadata.clone();
 
@@ -129,33 +122,55 @@ impl<'info> TradeTokenPair<'info> {
         let collection_metadata = ctx.accounts.nft_collection_metadata.clone();
 
         validate_nft(
+            pair.collection_mode,
             *nft_token_mint,
             nft_token_metadata,
-            *collection_mint,
+            collection_mint,
             collection_metadata,
         )?;
 
+        // Pairs with an allowlist only take in the mints it contains
+        verify_mint_allowlist(
+            &ctx.accounts.pair,
+            &ctx.accounts.nft_token_mint.key(),
+            proof,
+        )?;
+
+        let royalty_bps = royalty_basis_points(&ctx.accounts.nft_token_metadata.to_account_info())?;
+        let reserves = pair_reserves(&pair, &ctx.accounts.quote_token_vault.to_account_info())?;
+        let quote = quote_sell(&pair, &pair_authority, reserves, royalty_bps, 1)?;
+
+        // The vault pays the user, the protocol fee and royalties
+        let vault_outflow = quote.sell_vault_outflow()?;
+
+        if ctx.accounts.quote_token_vault.amount < vault_outflow {
+            return Err(ProgramError::InsufficientBalance.into());
+        }
+
         Ok(())
     }
 }
 
-#[access_control(TradeTokenPair::accounts(&ctx))]
-pub fn handler(ctx: Context<TradeTokenPair>) -> Result<()> {
+#[access_control(TradeTokenPair::accounts(&ctx, expected_trade_count, &proof))]
+pub fn handler<'info>(
+    ctx: Context<'_, '_, '_, 'info, TradeTokenPair<'info>>,
+    min_price: u64,
+    expected_trade_count: Option<u64>,
+    proof: Vec<[u8; 32]>,
+) -> Result<()> {
     let pair = &mut ctx.accounts.pair;
     let pair_metadata = &mut ctx.accounts.pair_metadata;
     let pair_authority = &mut ctx.accounts.pair_authority;
     let program_as_signer_bump = *ctx.bumps.get("program_as_signer").unwrap();
 
-    let pair_authority_fees = pair_authority.fees;
+    let royalty_bps = royalty_basis_points(&ctx.accounts.nft_token_metadata.to_account_info())?;
+    let reserves = pair_reserves(pair, &ctx.accounts.quote_token_vault.to_account_info())?;
+    let quote = quote_sell(pair, pair_authority, reserves, royalty_bps, 1)?;
 
-    let pair_auth_fee_applied = pair
-        .spot_price
-        .checked_mul(pair_authority_fees as u64)
-        .unwrap()
-        .checked_div(10000)
-        .unwrap();
-
-    let current_spot_price = pair.spot_price;
+    // Effective price received by the user, net of fees
+    if quote.total < min_price {
+        return Err(ProgramError::SlippageExceeded.into());
+    }
 
     // Transfer NFT from user to pair vault
     let transfer_nft_accounts = Transfer {
@@ -192,14 +207,11 @@ pub fn handler(ctx: Context<TradeTokenPair>) -> Result<()> {
         signer,
     );
 
-    transfer(transfer_quote_ctx, current_spot_price)?;
+    transfer(transfer_quote_ctx, quote.total)?;
 
     let transfer_pair_authority_fee_accounts = Transfer {
         from: ctx.accounts.quote_token_vault.to_account_info(),
-        to: ctx
-            .accounts
-            .pair_authority_quote_token_account
-            .to_account_info(),
+        to: ctx.accounts.protocol_fee_vault.to_account_info(),
         authority: ctx.accounts.program_as_signer.to_account_info(),
     };
 
@@ -209,32 +221,42 @@ pub fn handler(ctx: Context<TradeTokenPair>) -> Result<()> {
         signer,
     );
 
-    transfer(transfer_pair_authority_fee_ctx, pair_auth_fee_applied)?;
-
-    let bonding_curve = pair.bonding_curve;
-    let latest_spot_price: u64;
-
-    if bonding_curve == 0 {
-        let delta = pair.delta;
-
-        let new_spot_price = current_spot_price.checked_sub(delta).unwrap();
-        pair.spot_price = new_spot_price;
-        latest_spot_price = new_spot_price;
-    } else {
-        todo!()
+    transfer(transfer_pair_authority_fee_ctx, quote.protocol_fee)?;
+
+    if quote.royalty > 0 {
+        honor_royalties(
+            true,
+            Some(program_as_signer_bump),
+            &mut ctx.remaining_accounts.iter(),
+            &ctx.accounts.nft_token_metadata.to_account_info(),
+            quote.royalty,
+            &ctx.accounts.associated_token_program.to_account_info(),
+            &ctx.accounts.payer.to_account_info(),
+            &ctx.accounts.quote_token_vault.to_account_info(),
+            &ctx.accounts.program_as_signer.to_account_info(),
+            &ctx.accounts.quote_token_mint.to_account_info(),
+            &ctx.accounts.system_program.to_account_info(),
+            &ctx.accounts.token_program.to_account_info(),
+            &ctx.accounts.rent.to_account_info(),
+        )?;
     }
 
-    pair.nfts_held = pair.nfts_held.checked_add(1).unwrap();
-    pair.trade_count = pair.trade_count.checked_add(1).unwrap();
+    pair.spot_price = quote.new_spot_price;
+
+    pair.nfts_held = pair
+        .nfts_held
+        .checked_add(1)
+        .ok_or(ProgramError::NumericalOverflow)?;
+    pair.trade_count = pair
+        .trade_count
+        .checked_add(1)
+        .ok_or(ProgramError::NumericalOverflow)?;
 
     let quote_token_vault = &mut ctx.accounts.quote_token_vault;
+    quote_token_vault.reload()?;
 
     // If token pair can no longer sell quote tokens, deactivate it
-    if quote_token_vault.amount
-        < latest_spot_price
-            .checked_add(pair_auth_fee_applied)
-            .unwrap()
-    {
+    if quote_token_vault.amount < next_nft_cost(pair) {
         pair.is_active = false;
     }
 
@@ -244,5 +266,18 @@ pub fn handler(ctx: Context<TradeTokenPair>) -> Result<()> {
     pair_metadata.token_account = ctx.accounts.nft_token_vault.key();
     pair_metadata.creator = ctx.accounts.payer.key();
 
+    emit!(NftSold {
+        pair: ctx.accounts.pair.key(),
+        mint: ctx.accounts.nft_token_mint.key(),
+        seller: ctx.accounts.payer.key(),
+        price: quote.price,
+        protocol_fee: quote.protocol_fee,
+        pair_fee: quote.pair_fee,
+        royalty: quote.royalty,
+        total: quote.total,
+        new_spot_price: ctx.accounts.pair.spot_price,
+        trade_count: ctx.accounts.pair.trade_count,
+    });
+
     Ok(())
 }
@@ -1,4 +1,4 @@
-use crate::{error::ProgramError, state::*};
+use crate::{error::ProgramError, events::AuthorityTransferred, state::*};
 use anchor_lang::prelude::*;
 
 #[derive(Accounts)]
@@ -17,7 +17,13 @@ pub struct TransferPairAuthority<'info> {
 
 pub fn handler(ctx: Context<TransferPairAuthority>) -> Result<()> {
     let pair_authority = &mut ctx.accounts.pair_authority;
-    pair_authority.pending_authority = ctx.accounts.payer.key();
+    pair_authority.pending_authority = ctx.accounts.pending_authority.key();
+
+    emit!(AuthorityTransferred {
+        pair_authority: pair_authority.key(),
+        current_authority: pair_authority.current_authority,
+        pending_authority: pair_authority.pending_authority,
+    });
 
     Ok(())
 }
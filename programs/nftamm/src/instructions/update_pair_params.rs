@@ -0,0 +1,51 @@
+use crate::{error::ProgramError, events::ParamsChanged, state::Pair, utils::*};
+use anchor_lang::prelude::*;
+
+#[derive(Accounts)]
+pub struct UpdatePairParams<'info> {
+    #[account(
+        constraint = payer.key() == pair.owner @ ProgramError::InvalidOwner,
+    )]
+    pub payer: Signer<'info>,
+
+    #[account(mut)]
+    pub pair: Account<'info, Pair>,
+}
+
+/// Handler to update any subset of a pair's pricing parameters at once. The resulting parameters
+/// go through the same checks as pair creation.
+pub fn handler(
+    ctx: Context<UpdatePairParams>,
+    new_bonding_curve: Option<u8>,
+    new_delta: Option<u64>,
+    new_fee: Option<u16>,
+    new_spot_price: Option<u64>,
+    new_royalty_policy: Option<u8>,
+    new_royalty_bps: Option<u16>,
+) -> Result<()> {
+    let pair = &mut ctx.accounts.pair;
+
+    if pair.timelock_slots > 0 {
+        return Err(ProgramError::TimelockActive.into());
+    }
+
+    let bonding_curve = new_bonding_curve.unwrap_or(pair.bonding_curve);
+    let delta = new_delta.unwrap_or(pair.delta);
+    let fee = new_fee.unwrap_or(pair.fee);
+    let royalty_policy = new_royalty_policy.unwrap_or(pair.royalty_policy);
+    let royalty_bps = new_royalty_bps.unwrap_or(pair.royalty_bps);
+
+    validate_pair_params(pair.pair_type, bonding_curve, delta, fee)?;
+    validate_royalty_policy(royalty_policy, royalty_bps)?;
+
+    pair.bonding_curve = bonding_curve;
+    pair.delta = delta;
+    pair.fee = fee;
+    pair.spot_price = new_spot_price.unwrap_or(pair.spot_price);
+    pair.royalty_policy = royalty_policy;
+    pair.royalty_bps = royalty_bps;
+
+    emit!(ParamsChanged::new(pair.key(), pair));
+
+    Ok(())
+}
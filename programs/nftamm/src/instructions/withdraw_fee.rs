@@ -1,4 +1,4 @@
-use crate::{error::ProgramError, state::Pair};
+use crate::{error::ProgramError, events::FeesWithdrawn, state::Pair};
 use anchor_lang::prelude::*;
 use anchor_spl::{
     associated_token::AssociatedToken,
@@ -94,5 +94,11 @@ pub fn handler(ctx: Context<WithdrawFee>, amount: u64) -> Result<()> {
 
     transfer(transfer_fee_vault_ctx, amount)?;
 
+    emit!(FeesWithdrawn {
+        vault: ctx.accounts.quote_fee_vault.key(),
+        recipient: ctx.accounts.pair_owner.key(),
+        amount,
+    });
+
     Ok(())
 }
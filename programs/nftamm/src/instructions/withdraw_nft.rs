@@ -1,5 +1,6 @@
 use crate::{
     error::ProgramError,
+    events::NftWithdrawn,
     state::{Pair, PairMetadata},
     utils::*,
 };
@@ -35,8 +36,9 @@ pub struct WithdrawNft<'info> {
     )]
     pub pair_metadata: Account<'info, PairMetadata>,
 
+    /// CHECK: validated in access control logic
     #[account(constraint = nft_collection_mint.key() == pair.collection_mint @ ProgramError::InvalidCollectionMint)]
-    pub nft_collection_mint: Box<Account<'info, Mint>>,
+    pub nft_collection_mint: UncheckedAccount<'info>,
 
     /// CHECK: validated in access control logic
     pub nft_collection_metadata: UncheckedAccount<'info>,
@@ -56,6 +58,8 @@ pub struct WithdrawNft<'info> {
 
     #[account(
         mut,
+        seeds = [b"nft_account", pair.key().as_ref(), nft_token_mint.key().as_ref()],
+        bump,
         constraint = nft_token_vault.key() == pair_metadata.token_account @ ProgramError::InvalidNftTokenVault,
         constraint = nft_token_vault.mint == nft_token_mint.key() @ ProgramError::InvalidMint,
         constraint = nft_token_vault.owner == program_as_signer.key() @ ProgramError::InvalidMint,
@@ -81,9 +85,10 @@ impl<'info> WithdrawNft<'info> {
         let collection_metadata = ctx.accounts.nft_collection_metadata.clone();
 
         validate_nft(
+            ctx.accounts.pair.collection_mode,
             *nft_token_mint,
             nft_token_metadata,
-            *collection_mint,
+            collection_mint,
             collection_metadata,
         )?;
 
@@ -118,7 +123,10 @@ pub fn handler(ctx: Context<WithdrawNft>) -> Result<()> {
 
     transfer(transfer_nft_ctx, 1)?;
 
-    pair.nfts_held = pair.nfts_held.checked_sub(1).unwrap();
+    pair.nfts_held = pair
+        .nfts_held
+        .checked_sub(1)
+        .ok_or(ProgramError::NumericalOverflow)?;
 
     if pair.pair_type == 1 {
         if pair.nfts_held == 0 {
@@ -126,5 +134,11 @@ pub fn handler(ctx: Context<WithdrawNft>) -> Result<()> {
         }
     }
 
+    emit!(NftWithdrawn {
+        pair: pair.key(),
+        mint: ctx.accounts.nft_token_mint.key(),
+        nfts_held: pair.nfts_held,
+    });
+
     Ok(())
 }
@@ -0,0 +1,98 @@
+use crate::{error::ProgramError, events::FeesWithdrawn, state::PairAuthority};
+use anchor_lang::prelude::*;
+use anchor_spl::{
+    associated_token::AssociatedToken,
+    token::{transfer, Mint, Token, TokenAccount, Transfer},
+};
+
+#[derive(Accounts)]
+pub struct WithdrawProtocolFees<'info> {
+    #[account(
+        mut,
+        constraint = payer.key() == pair_authority.current_authority @ ProgramError::InvalidCurrentAuthority,
+    )]
+    pub payer: Signer<'info>,
+
+    pub pair_authority: Account<'info, PairAuthority>,
+
+    /// CHECK: only used as authority target for fee_recipient_quote_token_account
+    #[account(
+        constraint = fee_recipient.key() == pair_authority.fee_recipient @ ProgramError::InvalidFeeRecipient,
+    )]
+    pub fee_recipient: UncheckedAccount<'info>,
+
+    #[account(
+        init_if_needed,
+        payer = payer,
+        associated_token::mint = quote_token_mint,
+        associated_token::authority = fee_recipient
+    )]
+    pub fee_recipient_quote_token_account: Box<Account<'info, TokenAccount>>,
+
+    pub quote_token_mint: Box<Account<'info, Mint>>,
+
+    #[account(
+        mut,
+        seeds = [b"quote", "protocol_fee".as_bytes(), pair_authority.key().as_ref(), quote_token_mint.key().as_ref()],
+        bump,
+    )]
+    pub protocol_fee_vault: Box<Account<'info, TokenAccount>>,
+
+    /// CHECK: PDA used as token account authority only
+    #[account(seeds = [b"program", b"signer"], bump)]
+    pub program_as_signer: UncheckedAccount<'info>,
+
+    pub system_program: Program<'info, System>,
+    pub token_program: Program<'info, Token>,
+    pub associated_token_program: Program<'info, AssociatedToken>,
+    pub rent: Sysvar<'info, Rent>,
+}
+
+impl<'info> WithdrawProtocolFees<'info> {
+    fn accounts(ctx: &Context<WithdrawProtocolFees>, amount: u64) -> Result<()> {
+        if ctx.accounts.protocol_fee_vault.amount < amount {
+            return Err(ProgramError::InsufficientBalance.into());
+        }
+
+        Ok(())
+    }
+}
+
+/// Handler to withdraw escrowed protocol fees to the fee recipient
+#[access_control(WithdrawProtocolFees::accounts(&ctx, amount))]
+pub fn handler(ctx: Context<WithdrawProtocolFees>, amount: u64) -> Result<()> {
+    let program_as_signer_bump = *ctx.bumps.get("program_as_signer").unwrap();
+
+    let transfer_fee_vault_accounts = Transfer {
+        from: ctx.accounts.protocol_fee_vault.to_account_info(),
+        to: ctx
+            .accounts
+            .fee_recipient_quote_token_account
+            .to_account_info(),
+        authority: ctx.accounts.program_as_signer.to_account_info(),
+    };
+
+    let seeds = &[
+        "program".as_bytes(),
+        "signer".as_bytes(),
+        &[program_as_signer_bump],
+    ];
+
+    let signer = &[&seeds[..]];
+
+    let transfer_fee_vault_ctx = CpiContext::new_with_signer(
+        ctx.accounts.token_program.to_account_info(),
+        transfer_fee_vault_accounts,
+        signer,
+    );
+
+    transfer(transfer_fee_vault_ctx, amount)?;
+
+    emit!(FeesWithdrawn {
+        vault: ctx.accounts.protocol_fee_vault.key(),
+        recipient: ctx.accounts.fee_recipient.key(),
+        amount,
+    });
+
+    Ok(())
+}
@@ -0,0 +1,66 @@
+use crate::{error::ProgramError, events::FeesWithdrawn, state::PairAuthority, utils::*};
+use anchor_lang::prelude::*;
+
+#[derive(Accounts)]
+pub struct WithdrawProtocolSolFees<'info> {
+    #[account(
+        constraint = payer.key() == pair_authority.current_authority @ ProgramError::InvalidCurrentAuthority,
+    )]
+    pub payer: Signer<'info>,
+
+    pub pair_authority: Account<'info, PairAuthority>,
+
+    /// CHECK: only used as the recipient of the protocol fees
+    #[account(
+        mut,
+        constraint = fee_recipient.key() == pair_authority.fee_recipient @ ProgramError::InvalidFeeRecipient,
+    )]
+    pub fee_recipient: UncheckedAccount<'info>,
+
+    /// CHECK: system account PDA holding the protocol's fees in lamports
+    #[account(mut, seeds = [b"sol", b"protocol_fee", pair_authority.key().as_ref()], bump)]
+    pub protocol_sol_fee_vault: UncheckedAccount<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+impl<'info> WithdrawProtocolSolFees<'info> {
+    fn accounts(ctx: &Context<WithdrawProtocolSolFees>, amount: u64) -> Result<()> {
+        if sol_vault_balance(&ctx.accounts.protocol_sol_fee_vault)? < amount {
+            return Err(ProgramError::InsufficientBalance.into());
+        }
+
+        Ok(())
+    }
+}
+
+/// Handler to withdraw escrowed native sol protocol fees to the fee recipient
+#[access_control(WithdrawProtocolSolFees::accounts(&ctx, amount))]
+pub fn handler(ctx: Context<WithdrawProtocolSolFees>, amount: u64) -> Result<()> {
+    let protocol_sol_fee_vault_bump = *ctx.bumps.get("protocol_sol_fee_vault").unwrap();
+    let pair_authority_key = ctx.accounts.pair_authority.key();
+
+    let seeds = &[
+        b"sol".as_ref(),
+        b"protocol_fee".as_ref(),
+        pair_authority_key.as_ref(),
+        &[protocol_sol_fee_vault_bump],
+    ];
+    let signer = &[&seeds[..]];
+
+    transfer_sol(
+        &ctx.accounts.protocol_sol_fee_vault.to_account_info(),
+        &ctx.accounts.fee_recipient.to_account_info(),
+        &ctx.accounts.system_program.to_account_info(),
+        Some(signer),
+        amount,
+    )?;
+
+    emit!(FeesWithdrawn {
+        vault: ctx.accounts.protocol_sol_fee_vault.key(),
+        recipient: ctx.accounts.fee_recipient.key(),
+        amount,
+    });
+
+    Ok(())
+}
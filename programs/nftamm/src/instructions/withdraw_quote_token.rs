@@ -1,4 +1,4 @@
-use crate::{error::ProgramError, state::*};
+use crate::{error::ProgramError, events::QuoteWithdrawn, state::*, utils::next_nft_cost};
 use anchor_lang::prelude::*;
 use anchor_spl::{
     associated_token::AssociatedToken,
@@ -63,21 +63,9 @@ impl<'info> WithdrawQuoteToken<'info> {
 #[access_control(WithdrawQuoteToken::accounts(&ctx, amount_to_withdraw))]
 pub fn handler(ctx: Context<WithdrawQuoteToken>, amount_to_withdraw: u64) -> Result<()> {
     let pair = &mut ctx.accounts.pair;
-    let pair_authority = &mut ctx.accounts.pair_authority;
     let program_as_signer_bump = *ctx.bumps.get("program_as_signer").unwrap();
     let quote_token_vault = &mut ctx.accounts.quote_token_vault;
 
-    let pair_authority_fees = pair_authority.fees;
-
-    let pair_auth_fee_applied = pair
-        .spot_price
-        .checked_mul(pair_authority_fees as u64)
-        .unwrap()
-        .checked_div(10000)
-        .unwrap();
-
-    let current_spot_price = pair.spot_price;
-
     let transfer_quote_token_accounts = Transfer {
         from: quote_token_vault.to_account_info(),
         to: ctx.accounts.owner_quote_token_account.to_account_info(),
@@ -100,15 +88,19 @@ pub fn handler(ctx: Context<WithdrawQuoteToken>, amount_to_withdraw: u64) -> Res
 
     transfer(transfer_quote_token_accounts_ctx, amount_to_withdraw)?;
 
+    quote_token_vault.reload()?;
+
     if pair.pair_type == 0 {
-        if quote_token_vault.amount
-            < current_spot_price
-                .checked_add(pair_auth_fee_applied)
-                .unwrap()
-        {
+        if quote_token_vault.amount < next_nft_cost(pair) {
             pair.is_active = false;
         }
     }
 
+    emit!(QuoteWithdrawn {
+        pair: pair.key(),
+        amount: amount_to_withdraw,
+        is_active: pair.is_active,
+    });
+
     Ok(())
 }
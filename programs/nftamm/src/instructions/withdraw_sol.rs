@@ -0,0 +1,73 @@
+use crate::{error::ProgramError, events::QuoteWithdrawn, state::*, utils::*};
+use anchor_lang::prelude::*;
+
+#[derive(Accounts)]
+pub struct WithdrawSol<'info> {
+    #[account(
+        mut,
+        constraint = payer.key() == pair.owner @ ProgramError::InvalidOwner,
+    )]
+    pub payer: Signer<'info>,
+
+    #[account(constraint = pair_authority.key() == pair.pair_authority @ ProgramError::InvalidPairAuthority)]
+    pub pair_authority: Account<'info, PairAuthority>,
+
+    #[account(mut, constraint = pair.is_native @ ProgramError::NotNativePair)]
+    pub pair: Account<'info, Pair>,
+
+    /// CHECK: system account PDA holding the pair's lamports
+    #[account(
+        mut,
+        seeds = [b"sol", pair.key().as_ref()],
+        bump,
+        constraint = sol_vault.key() == pair.quote_token_vault @ ProgramError::InvalidQuoteTokenVault,
+    )]
+    pub sol_vault: UncheckedAccount<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+impl<'info> WithdrawSol<'info> {
+    fn accounts(ctx: &Context<WithdrawSol>, amount_to_withdraw: u64) -> Result<()> {
+        if sol_vault_balance(&ctx.accounts.sol_vault)? < amount_to_withdraw {
+            return Err(ProgramError::InsufficientBalance.into());
+        }
+
+        Ok(())
+    }
+}
+
+/// Handler to withdraw lamports from a native sol pair, leaving the vault's rent exempt reserve
+#[access_control(WithdrawSol::accounts(&ctx, amount_to_withdraw))]
+pub fn handler(ctx: Context<WithdrawSol>, amount_to_withdraw: u64) -> Result<()> {
+    let sol_vault_bump = *ctx.bumps.get("sol_vault").unwrap();
+    let pair_key = ctx.accounts.pair.key();
+
+    let seeds = &[b"sol".as_ref(), pair_key.as_ref(), &[sol_vault_bump]];
+    let signer = &[&seeds[..]];
+
+    transfer_sol(
+        &ctx.accounts.sol_vault.to_account_info(),
+        &ctx.accounts.payer.to_account_info(),
+        &ctx.accounts.system_program.to_account_info(),
+        Some(signer),
+        amount_to_withdraw,
+    )?;
+
+    let pair = &mut ctx.accounts.pair;
+
+    // If token pair can no longer buy nfts, deactivate it
+    if pair.pair_type == 0 {
+        if sol_vault_balance(&ctx.accounts.sol_vault)? < next_nft_cost(pair) {
+            pair.is_active = false;
+        }
+    }
+
+    emit!(QuoteWithdrawn {
+        pair: pair.key(),
+        amount: amount_to_withdraw,
+        is_active: pair.is_active,
+    });
+
+    Ok(())
+}
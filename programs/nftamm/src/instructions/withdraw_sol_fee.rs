@@ -0,0 +1,65 @@
+use crate::{error::ProgramError, events::FeesWithdrawn, state::Pair, utils::*};
+use anchor_lang::prelude::*;
+
+#[derive(Accounts)]
+pub struct WithdrawSolFee<'info> {
+    #[account(
+        mut,
+        constraint = payer.key() == pair.owner @ ProgramError::InvalidOwner,
+    )]
+    pub payer: Signer<'info>,
+
+    #[account(constraint = pair.is_native @ ProgramError::NotNativePair)]
+    pub pair: Account<'info, Pair>,
+
+    /// CHECK: system account PDA holding the pair's fees in lamports
+    #[account(
+        mut,
+        seeds = [b"sol", b"fee", pair.key().as_ref()],
+        bump,
+        constraint = sol_fee_vault.key() == pair.fee_vault @ ProgramError::InvalidFeeVault,
+    )]
+    pub sol_fee_vault: UncheckedAccount<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+impl<'info> WithdrawSolFee<'info> {
+    fn accounts(ctx: &Context<WithdrawSolFee>, amount: u64) -> Result<()> {
+        if sol_vault_balance(&ctx.accounts.sol_fee_vault)? < amount {
+            return Err(ProgramError::InsufficientBalance.into());
+        }
+
+        Ok(())
+    }
+}
+
+#[access_control(WithdrawSolFee::accounts(&ctx, amount))]
+pub fn handler(ctx: Context<WithdrawSolFee>, amount: u64) -> Result<()> {
+    let sol_fee_vault_bump = *ctx.bumps.get("sol_fee_vault").unwrap();
+    let pair_key = ctx.accounts.pair.key();
+
+    let seeds = &[
+        b"sol".as_ref(),
+        b"fee".as_ref(),
+        pair_key.as_ref(),
+        &[sol_fee_vault_bump],
+    ];
+    let signer = &[&seeds[..]];
+
+    transfer_sol(
+        &ctx.accounts.sol_fee_vault.to_account_info(),
+        &ctx.accounts.payer.to_account_info(),
+        &ctx.accounts.system_program.to_account_info(),
+        Some(signer),
+        amount,
+    )?;
+
+    emit!(FeesWithdrawn {
+        vault: ctx.accounts.sol_fee_vault.key(),
+        recipient: ctx.accounts.payer.key(),
+        amount,
+    });
+
+    Ok(())
+}
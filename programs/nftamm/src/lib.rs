@@ -4,8 +4,11 @@
 
 use anchor_lang::prelude::*;
 
-mod error;
+pub mod curve;
+pub mod error;
+pub mod events;
 mod instructions;
+pub mod quote;
 pub mod state;
 mod utils;
 
@@ -21,7 +24,7 @@ pub mod nftamm {
     /// Initialize a new [state::PairAuthority]
     pub fn initialize_pair_authority(
         ctx: Context<InitializePairAuthority>,
-        fees: u16,
+        fees: [u16; 3],
     ) -> Result<()> {
         instructions::initialize_pair_authority::handler(ctx, fees)
     }
@@ -36,7 +39,83 @@ pub mod nftamm {
         instructions::accept_pair_authority::handler(ctx)
     }
 
+    /// Change the protocol fee charged on one pair type of a [state::PairAuthority]. Applies
+    /// instantly, so it is rejected once the pair authority has a timelock
+    pub fn change_protocol_fee(
+        ctx: Context<ChangeProtocolFee>,
+        pair_type: u8,
+        new_fee: u16,
+    ) -> Result<()> {
+        instructions::change_protocol_fee::handler(ctx, pair_type, new_fee)
+    }
+
+    /// Change the fee_recipient of a [state::PairAuthority]. Applies instantly, so it is rejected
+    /// once the pair authority has a timelock
+    pub fn change_fee_recipient(ctx: Context<ChangeFeeRecipient>) -> Result<()> {
+        instructions::change_fee_recipient::handler(ctx)
+    }
+
+    /// Raise the timelock on protocol fee changes of a [state::PairAuthority]
+    pub fn set_protocol_fee_timelock(
+        ctx: Context<SetProtocolFeeTimelock>,
+        new_timelock_slots: u64,
+    ) -> Result<()> {
+        instructions::set_protocol_fee_timelock::handler(ctx, new_timelock_slots)
+    }
+
+    /// Queue a protocol fee change on a timelocked [state::PairAuthority]
+    pub fn queue_protocol_fee_change(
+        ctx: Context<QueueProtocolFeeChange>,
+        new_fees: Option<[u16; 3]>,
+        new_fee_recipient: Option<Pubkey>,
+        new_timelock_slots: Option<u64>,
+    ) -> Result<()> {
+        instructions::queue_protocol_fee_change::handler(
+            ctx,
+            new_fees,
+            new_fee_recipient,
+            new_timelock_slots,
+        )
+    }
+
+    /// Apply the pending protocol fee change of a [state::PairAuthority] once its timelock has
+    /// elapsed
+    pub fn apply_protocol_fee_change(ctx: Context<ApplyProtocolFeeChange>) -> Result<()> {
+        instructions::apply_protocol_fee_change::handler(ctx)
+    }
+
+    /// Cancel the pending protocol fee change of a [state::PairAuthority]
+    pub fn cancel_protocol_fee_change(ctx: Context<CancelProtocolFeeChange>) -> Result<()> {
+        instructions::cancel_protocol_fee_change::handler(ctx)
+    }
+
+    /// Initialize the [state::PairAuthority] vault escrowing protocol fees in a quote token
+    pub fn initialize_protocol_fee_vault(ctx: Context<InitializeProtocolFeeVault>) -> Result<()> {
+        instructions::initialize_protocol_fee_vault::handler(ctx)
+    }
+
+    /// Initialize the [state::PairAuthority] vault escrowing protocol fees in native sol
+    pub fn initialize_protocol_sol_fee_vault(
+        ctx: Context<InitializeProtocolSolFeeVault>,
+    ) -> Result<()> {
+        instructions::initialize_protocol_sol_fee_vault::handler(ctx)
+    }
+
+    /// Withdraw escrowed protocol fees in a quote token to the fee_recipient
+    pub fn withdraw_protocol_fees(ctx: Context<WithdrawProtocolFees>, amount: u64) -> Result<()> {
+        instructions::withdraw_protocol_fees::handler(ctx, amount)
+    }
+
+    /// Withdraw escrowed protocol fees in native sol to the fee_recipient
+    pub fn withdraw_protocol_sol_fees(
+        ctx: Context<WithdrawProtocolSolFees>,
+        amount: u64,
+    ) -> Result<()> {
+        instructions::withdraw_protocol_sol_fees::handler(ctx, amount)
+    }
+
     /// Initialize a new [state::Pair]
+    #[allow(clippy::too_many_arguments)]
     pub fn initialize_pair(
         ctx: Context<InitializePair>,
         pair_type: u8,
@@ -44,7 +123,10 @@ pub mod nftamm {
         delta: u64,
         fee: u16,
         spot_price: u64,
-        honor_royalties: bool,
+        royalty_policy: u8,
+        royalty_bps: u16,
+        mint_allowlist_root: Option<[u8; 32]>,
+        collection_mode: u8,
     ) -> Result<()> {
         instructions::initialize_pair::handler(
             ctx,
@@ -53,7 +135,38 @@ pub mod nftamm {
             delta,
             fee,
             spot_price,
-            honor_royalties,
+            royalty_policy,
+            royalty_bps,
+            mint_allowlist_root,
+            collection_mode,
+        )
+    }
+
+    /// Initialize a new [state::Pair] whose quote vaults hold native sol
+    #[allow(clippy::too_many_arguments)]
+    pub fn initialize_sol_pair(
+        ctx: Context<InitializeSolPair>,
+        pair_type: u8,
+        bonding_curve: u8,
+        delta: u64,
+        fee: u16,
+        spot_price: u64,
+        royalty_policy: u8,
+        royalty_bps: u16,
+        mint_allowlist_root: Option<[u8; 32]>,
+        collection_mode: u8,
+    ) -> Result<()> {
+        instructions::initialize_sol_pair::handler(
+            ctx,
+            pair_type,
+            bonding_curve,
+            delta,
+            fee,
+            spot_price,
+            royalty_policy,
+            royalty_bps,
+            mint_allowlist_root,
+            collection_mode,
         )
     }
 
@@ -62,25 +175,111 @@ pub mod nftamm {
         instructions::fund_token_pair::handler(ctx, amount_to_send)
     }
 
+    /// Fund a native sol token or trade [state::Pair] with lamports
+    pub fn fund_sol_pair(ctx: Context<FundSolPair>, amount_to_send: u64) -> Result<()> {
+        instructions::fund_sol_pair::handler(ctx, amount_to_send)
+    }
+
     /// Fund a nft [state::Pair]
-    pub fn fund_nft_pair(ctx: Context<FundNftPair>) -> Result<()> {
-        instructions::fund_nft_pair::handler(ctx)
+    pub fn fund_nft_pair(ctx: Context<FundNftPair>, proof: Vec<[u8; 32]>) -> Result<()> {
+        instructions::fund_nft_pair::handler(ctx, proof)
+    }
+
+    pub fn trade_token_pair<'info>(
+        ctx: Context<'_, '_, '_, 'info, TradeTokenPair<'info>>,
+        min_price: u64,
+        expected_trade_count: Option<u64>,
+        proof: Vec<[u8; 32]>,
+    ) -> Result<()> {
+        instructions::trade_token_pair::handler(ctx, min_price, expected_trade_count, proof)
+    }
+
+    pub fn trade_nft_pair<'info>(
+        ctx: Context<'_, '_, '_, 'info, TradeNftPair<'info>>,
+        max_price: u64,
+        expected_trade_count: Option<u64>,
+    ) -> Result<()> {
+        instructions::trade_nft_pair::handler(ctx, max_price, expected_trade_count)
     }
 
-    pub fn trade_token_pair(ctx: Context<TradeTokenPair>) -> Result<()> {
-        instructions::trade_token_pair::handler(ctx)
+    pub fn swap_token_trade_pair<'info>(
+        ctx: Context<'_, '_, '_, 'info, SwapTokenTradePair<'info>>,
+        max_price: u64,
+        expected_trade_count: Option<u64>,
+    ) -> Result<()> {
+        instructions::swap_token_trade_pair::handler(ctx, max_price, expected_trade_count)
+    }
+
+    pub fn swap_nft_trade_pair<'info>(
+        ctx: Context<'_, '_, '_, 'info, SwapNftTradePair<'info>>,
+        min_price: u64,
+        expected_trade_count: Option<u64>,
+        proof: Vec<[u8; 32]>,
+    ) -> Result<()> {
+        instructions::swap_nft_trade_pair::handler(ctx, min_price, expected_trade_count, proof)
+    }
+
+    /// Buy every nft passed through remaining_accounts from a nft or trade [state::Pair]
+    pub fn buy_nfts<'info>(
+        ctx: Context<'_, '_, '_, 'info, BuyNfts<'info>>,
+        max_total_input: u64,
+    ) -> Result<()> {
+        instructions::buy_nfts::handler(ctx, max_total_input)
+    }
+
+    /// Sell every nft passed through remaining_accounts into a token or trade [state::Pair]
+    pub fn sell_nfts<'info>(
+        ctx: Context<'_, '_, '_, 'info, SellNfts<'info>>,
+        min_total_output: u64,
+        proofs: Vec<Vec<[u8; 32]>>,
+    ) -> Result<()> {
+        instructions::sell_nfts::handler(ctx, min_total_output, proofs)
+    }
+
+    /// Buy a nft from a native sol nft or trade [state::Pair]
+    pub fn buy_nft_with_sol<'info>(
+        ctx: Context<'_, '_, '_, 'info, BuyNftWithSol<'info>>,
+        max_price: u64,
+        expected_trade_count: Option<u64>,
+    ) -> Result<()> {
+        instructions::buy_nft_with_sol::handler(ctx, max_price, expected_trade_count)
     }
 
-    pub fn trade_nft_pair(ctx: Context<TradeNftPair>) -> Result<()> {
-        instructions::trade_nft_pair::handler(ctx)
+    /// Sell a nft into a native sol token or trade [state::Pair]
+    pub fn sell_nft_for_sol<'info>(
+        ctx: Context<'_, '_, '_, 'info, SellNftForSol<'info>>,
+        min_price: u64,
+        expected_trade_count: Option<u64>,
+        proof: Vec<[u8; 32]>,
+    ) -> Result<()> {
+        instructions::sell_nft_for_sol::handler(ctx, min_price, expected_trade_count, proof)
     }
 
-    pub fn swap_token_trade_pair(ctx: Context<SwapTokenTradePair>) -> Result<()> {
-        instructions::swap_token_trade_pair::handler(ctx)
+    /// Quote buying n nfts from a [state::Pair], returned as a [quote::Quote] in the return data.
+    /// n is capped at [quote::MAX_QUOTE_NFTS] and the nfts held by the pair
+    pub fn quote_buy(ctx: Context<QuoteBuy>, n: u64, royalty_bps: u16) -> Result<()> {
+        instructions::quote_buy::handler(ctx, n, royalty_bps)
     }
 
-    pub fn swap_nft_trade_pair(ctx: Context<SwapNftTradePair>) -> Result<()> {
-        instructions::swap_nft_trade_pair::handler(ctx)
+    /// Quote selling n nfts into a [state::Pair], returned as a [quote::Quote] in the return data.
+    /// n is capped at [quote::MAX_QUOTE_NFTS]
+    pub fn quote_sell(ctx: Context<QuoteSell>, n: u64, royalty_bps: u16) -> Result<()> {
+        instructions::quote_sell::handler(ctx, n, royalty_bps)
+    }
+
+    /// Set the bonding_curve of a [state::Pair] together with a matching delta and spot_price
+    pub fn change_bonding_curve(
+        ctx: Context<ChangeBondingCurve>,
+        new_bonding_curve: u8,
+        new_delta: u64,
+        new_spot_price: u64,
+    ) -> Result<()> {
+        instructions::change_bonding_curve::handler(
+            ctx,
+            new_bonding_curve,
+            new_delta,
+            new_spot_price,
+        )
     }
 
     /// Set the delta of a [state::Pair] to new_delta
@@ -93,16 +292,114 @@ pub mod nftamm {
         instructions::change_fee::handler(ctx, new_fee)
     }
 
+    /// Set the mint_allowlist_root of a [state::Pair], or None to accept the whole collection
+    pub fn change_mint_allowlist_root(
+        ctx: Context<ChangeMintAllowlistRoot>,
+        new_mint_allowlist_root: Option<[u8; 32]>,
+    ) -> Result<()> {
+        instructions::change_mint_allowlist_root::handler(ctx, new_mint_allowlist_root)
+    }
+
+    /// Update any subset of the bonding_curve, delta, fee, spot_price and royalty policy of a
+    /// [state::Pair] in one instruction
+    pub fn update_pair_params(
+        ctx: Context<UpdatePairParams>,
+        new_bonding_curve: Option<u8>,
+        new_delta: Option<u64>,
+        new_fee: Option<u16>,
+        new_spot_price: Option<u64>,
+        new_royalty_policy: Option<u8>,
+        new_royalty_bps: Option<u16>,
+    ) -> Result<()> {
+        instructions::update_pair_params::handler(
+            ctx,
+            new_bonding_curve,
+            new_delta,
+            new_fee,
+            new_spot_price,
+            new_royalty_policy,
+            new_royalty_bps,
+        )
+    }
+
+    /// Set the royalty_policy and royalty_bps of a [state::Pair]
+    pub fn change_royalty_policy(
+        ctx: Context<ChangeRoyaltyPolicy>,
+        new_royalty_policy: u8,
+        new_royalty_bps: u16,
+    ) -> Result<()> {
+        instructions::change_royalty_policy::handler(ctx, new_royalty_policy, new_royalty_bps)
+    }
+
     /// Set the spot_price of a [state::Pair] to new_spot_price
     pub fn change_spot_price(ctx: Context<ChangeSpotPrice>, new_spot_price: u64) -> Result<()> {
         instructions::change_spot_price::handler(ctx, new_spot_price)
     }
 
+    /// Raise the timelock_slots of a [state::Pair] and set whether it halts trades while a
+    /// parameter change is pending
+    pub fn set_pair_timelock(
+        ctx: Context<SetPairTimelock>,
+        new_timelock_slots: u64,
+        halt_trades_while_pending: bool,
+    ) -> Result<()> {
+        instructions::set_pair_timelock::handler(ctx, new_timelock_slots, halt_trades_while_pending)
+    }
+
+    /// Set whether linear sells on a [state::Pair] stop its spot_price at zero instead of failing
+    pub fn set_spot_price_clamp(
+        ctx: Context<SetSpotPriceClamp>,
+        clamp_spot_price: bool,
+    ) -> Result<()> {
+        instructions::set_spot_price_clamp::handler(ctx, clamp_spot_price)
+    }
+
+    /// Queue a parameter change on a timelocked [state::Pair]
+    #[allow(clippy::too_many_arguments)]
+    pub fn queue_pair_change(
+        ctx: Context<QueuePairChange>,
+        new_bonding_curve: Option<u8>,
+        new_delta: Option<u64>,
+        new_fee: Option<u16>,
+        new_spot_price: Option<u64>,
+        new_royalty_policy: Option<u8>,
+        new_royalty_bps: Option<u16>,
+        new_mint_allowlist_root: Option<Option<[u8; 32]>>,
+        new_timelock_slots: Option<u64>,
+    ) -> Result<()> {
+        instructions::queue_pair_change::handler(
+            ctx,
+            new_bonding_curve,
+            new_delta,
+            new_fee,
+            new_spot_price,
+            new_royalty_policy,
+            new_royalty_bps,
+            new_mint_allowlist_root,
+            new_timelock_slots,
+        )
+    }
+
+    /// Apply the pending parameter change of a [state::Pair] once its timelock has elapsed
+    pub fn apply_pair_change(ctx: Context<ApplyPairChange>) -> Result<()> {
+        instructions::apply_pair_change::handler(ctx)
+    }
+
+    /// Cancel the pending parameter change of a [state::Pair]
+    pub fn cancel_pair_change(ctx: Context<CancelPairChange>) -> Result<()> {
+        instructions::cancel_pair_change::handler(ctx)
+    }
+
     /// Close a token_pair
     pub fn close_pair(ctx: Context<ClosePair>) -> Result<()> {
         instructions::close_pair::handler(ctx)
     }
 
+    /// Close a native sol [state::Pair], returning both sol vaults to the owner - Pair creator only
+    pub fn close_sol_pair(ctx: Context<CloseSolPair>) -> Result<()> {
+        instructions::close_sol_pair::handler(ctx)
+    }
+
     /// Withdraw an nft from a [state::PairMetadata] and close the account - Pair creator only
     pub fn withdraw_nft(ctx: Context<WithdrawNft>) -> Result<()> {
         instructions::withdraw_nft::handler(ctx)
@@ -120,4 +417,14 @@ pub mod nftamm {
     pub fn withdraw_fee(ctx: Context<WithdrawFee>, amount: u64) -> Result<()> {
         instructions::withdraw_fee::handler(ctx, amount)
     }
+
+    /// Withdraw lamports from a native sol [state::Pair]'s sol vault - Pair creator only
+    pub fn withdraw_sol(ctx: Context<WithdrawSol>, amount_to_withdraw: u64) -> Result<()> {
+        instructions::withdraw_sol::handler(ctx, amount_to_withdraw)
+    }
+
+    /// Withdraw lamports from a native sol [state::Pair]'s fee vault - Pair creator only
+    pub fn withdraw_sol_fee(ctx: Context<WithdrawSolFee>, amount: u64) -> Result<()> {
+        instructions::withdraw_sol_fee::handler(ctx, amount)
+    }
 }
@@ -0,0 +1,217 @@
+//! Price quotes for trading several NFTs against a pair in sequence.
+//!
+//! These functions are used by the trade instructions themselves and are exported so that
+//! Rust clients (through the `no-entrypoint` / `cpi` builds) can price trades without
+//! re-implementing the bonding curves.
+
+use crate::{
+    curve::{bonding_curve, mul_div, Reserves, Rounding, BASIS_POINTS},
+    error::ProgramError,
+    state::{Pair, PairAuthority},
+    utils::{apply_royalty_policy, protocol_fee_bps},
+};
+use anchor_lang::prelude::*;
+
+/// Largest number of NFTs a single quote is priced for
+///
+/// Quotes walk the bonding curve once per NFT, so the count is capped to keep an on-chain quote
+/// within the compute budget. Batch trades fit far fewer NFTs than this in one transaction.
+pub const MAX_QUOTE_NFTS: u64 = 100;
+
+/// Breakdown of the quote tokens moved by a sequence of trades
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct Quote {
+    /// Sum of the bonding curve prices, before fees and royalties
+    pub price: u64,
+    /// Fee paid to the [PairAuthority]
+    pub protocol_fee: u64,
+    /// Fee kept by the pair (trade pairs only)
+    pub pair_fee: u64,
+    /// Royalties paid to the NFT creators
+    pub royalty: u64,
+    /// Quote tokens paid by the trader when buying, or received by the trader when selling
+    pub total: u64,
+    /// Spot price of the pair once all trades have been executed
+    pub new_spot_price: u64,
+}
+
+/// Quote buying `n` NFTs out of `pair`
+///
+/// `reserves` are the pair's quote vault balance and nfts held, which only the constant product
+/// curve reads.
+///
+/// `royalty_bps` is the `seller_fee_basis_points` of the NFTs being bought and is scaled by
+/// the pair's royalty policy. The trader pays `total`, which covers the curve
+/// price, the protocol fee, the pair fee and royalties.
+///
+/// `n` can be at most [MAX_QUOTE_NFTS] and the pair's `nfts_held`.
+pub fn quote_buy(
+    pair: &Pair,
+    pair_authority: &PairAuthority,
+    reserves: Reserves,
+    royalty_bps: u16,
+    n: u64,
+) -> Result<Quote> {
+    check_quote_size(n)?;
+
+    if n > pair.nfts_held as u64 {
+        return Err(ProgramError::InsufficientReserves.into());
+    }
+
+    let mut curve = bonding_curve(
+        pair.bonding_curve,
+        pair.delta,
+        pair.clamp_spot_price,
+        reserves,
+    )?;
+    let mut quote = Quote {
+        new_spot_price: pair.spot_price,
+        ..Quote::default()
+    };
+
+    for _ in 0..n {
+        let price = curve.buy_price(quote.new_spot_price)?;
+        let step = Step::new(pair, pair_authority, royalty_bps, price)?;
+
+        let total = price
+            .checked_add(step.protocol_fee)
+            .and_then(|total| total.checked_add(step.pair_fee))
+            .and_then(|total| total.checked_add(step.royalty))
+            .ok_or(ProgramError::NumericalOverflow)?;
+
+        quote.add(&step, total)?;
+        quote.new_spot_price = curve.spot_price_after_buy(quote.new_spot_price)?;
+    }
+
+    Ok(quote)
+}
+
+/// Quote selling `n` NFTs into `pair`
+///
+/// `reserves` are the pair's quote vault balance and nfts held, which only the constant product
+/// curve reads.
+///
+/// `royalty_bps` is the `seller_fee_basis_points` of the NFTs being sold and is scaled by the
+/// pair's royalty policy. The trader receives `total`, which is the curve price less the pair
+/// fee, royalties and the protocol fee. The pair pays out the curve price less its own fee.
+///
+/// `n` can be at most [MAX_QUOTE_NFTS].
+pub fn quote_sell(
+    pair: &Pair,
+    pair_authority: &PairAuthority,
+    reserves: Reserves,
+    royalty_bps: u16,
+    n: u64,
+) -> Result<Quote> {
+    check_quote_size(n)?;
+
+    let mut curve = bonding_curve(
+        pair.bonding_curve,
+        pair.delta,
+        pair.clamp_spot_price,
+        reserves,
+    )?;
+    let mut quote = Quote {
+        new_spot_price: pair.spot_price,
+        ..Quote::default()
+    };
+
+    for _ in 0..n {
+        let price = curve.sell_price(quote.new_spot_price)?;
+        let step = Step::new(pair, pair_authority, royalty_bps, price)?;
+
+        let total = price
+            .checked_sub(step.pair_fee)
+            .and_then(|total| total.checked_sub(step.royalty))
+            .and_then(|total| total.checked_sub(step.protocol_fee))
+            .ok_or(ProgramError::NumericalOverflow)?;
+
+        // The vault pays the trader, royalties and the protocol fee, and keeps the pair fee
+        let vault_outflow = total
+            .checked_add(step.royalty)
+            .and_then(|outflow| outflow.checked_add(step.protocol_fee))
+            .ok_or(ProgramError::NumericalOverflow)?;
+
+        quote.add(&step, total)?;
+        quote.new_spot_price = curve.spot_price_after_sell(quote.new_spot_price, vault_outflow)?;
+    }
+
+    Ok(quote)
+}
+
+fn check_quote_size(n: u64) -> Result<()> {
+    if n > MAX_QUOTE_NFTS {
+        return Err(ProgramError::QuoteTooLarge.into());
+    }
+
+    Ok(())
+}
+
+/// Fees and royalties of a single trade at `price`
+struct Step {
+    price: u64,
+    protocol_fee: u64,
+    pair_fee: u64,
+    royalty: u64,
+}
+
+impl Step {
+    fn new(
+        pair: &Pair,
+        pair_authority: &PairAuthority,
+        royalty_bps: u16,
+        price: u64,
+    ) -> Result<Self> {
+        // Only trade pairs are allowed to charge a fee
+        let pair_fee_bps = if pair.pair_type == 2 { pair.fee } else { 0 };
+        let royalty_bps = apply_royalty_policy(pair, royalty_bps)?;
+
+        Ok(Step {
+            price,
+            protocol_fee: mul_div(
+                price,
+                protocol_fee_bps(pair_authority, pair.pair_type)? as u64,
+                BASIS_POINTS,
+                Rounding::Down,
+            )?,
+            pair_fee: mul_div(price, pair_fee_bps as u64, BASIS_POINTS, Rounding::Down)?,
+            royalty: mul_div(price, royalty_bps as u64, BASIS_POINTS, Rounding::Down)?,
+        })
+    }
+}
+
+impl Quote {
+    /// Append a quote for the trades that follow this one
+    pub fn merge(&mut self, next: &Quote) -> Result<()> {
+        self.price = checked_sum(self.price, next.price)?;
+        self.protocol_fee = checked_sum(self.protocol_fee, next.protocol_fee)?;
+        self.pair_fee = checked_sum(self.pair_fee, next.pair_fee)?;
+        self.royalty = checked_sum(self.royalty, next.royalty)?;
+        self.total = checked_sum(self.total, next.total)?;
+        self.new_spot_price = next.new_spot_price;
+
+        Ok(())
+    }
+
+    /// Quote tokens a sell quote takes out of the pair's quote vault: the trader's total,
+    /// royalties and the protocol fee
+    pub fn sell_vault_outflow(&self) -> Result<u64> {
+        checked_sum(self.total, self.royalty)
+            .and_then(|outflow| checked_sum(outflow, self.protocol_fee))
+    }
+
+    fn add(&mut self, step: &Step, total: u64) -> Result<()> {
+        self.price = checked_sum(self.price, step.price)?;
+        self.protocol_fee = checked_sum(self.protocol_fee, step.protocol_fee)?;
+        self.pair_fee = checked_sum(self.pair_fee, step.pair_fee)?;
+        self.royalty = checked_sum(self.royalty, step.royalty)?;
+        self.total = checked_sum(self.total, total)?;
+
+        Ok(())
+    }
+}
+
+fn checked_sum(a: u64, b: u64) -> Result<u64> {
+    a.checked_add(b)
+        .ok_or_else(|| ProgramError::NumericalOverflow.into())
+}
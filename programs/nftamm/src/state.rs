@@ -7,7 +7,19 @@ use anchor_lang::prelude::*;
 pub struct PairAuthority {
     pub current_authority: Pubkey,
     pub pending_authority: Pubkey,
-    pub fees: u16,
+    pub fee_recipient: Pubkey, // Owner of the token accounts and lamports receiving protocol fees
+    pub fees: [u16; 3],        // Protocol fee in basis points, indexed by pair type
+    pub timelock_slots: u64,   // 0 for fee changes that apply instantly
+    pub pending_fee_change: Option<PendingFeeChange>,
+}
+
+/// Protocol fee change queued on a timelocked PairAuthority
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct PendingFeeChange {
+    pub fees: Option<[u16; 3]>,
+    pub fee_recipient: Option<Pubkey>,
+    pub timelock_slots: Option<u64>,
+    pub apply_after_slot: u64,
 }
 
 /// Keeps track of Pairs
@@ -15,19 +27,41 @@ pub struct PairAuthority {
 pub struct Pair {
     pub pair_authority: Pubkey,
     pub owner: Pubkey,
-    pub collection_mint: Pubkey,
+    pub collection_mint: Pubkey, // Collection mint, or creator address for creator collection modes
+    pub collection_mode: u8, // 0 for verified collection, 1 for first verified creator, 2 for any verified creator
+    pub mint_allowlist_root: Option<[u8; 32]>, // Merkle root of allowed nft mints, None for any
     pub quote_token_mint: Pubkey,
     pub quote_token_vault: Pubkey,
+    pub is_native: bool,   // Quote vaults hold lamports instead of quote tokens
     pub pair_type: u8,     // 0 for TokenPair, 1 for NFTPair, 2 for TradePair
-    pub bonding_curve: u8, // 0 for linear, 1 for exponential
+    pub bonding_curve: u8, // 0 for linear, 1 for exponential, 2 for fixed, 3 for xyk
     pub delta: u64,
     pub fee: u16,
     pub fee_vault: Pubkey,
     pub spot_price: u64,
-    pub honor_royalties: bool,
+    pub clamp_spot_price: bool, // Linear sells stop the spot price at zero instead of failing
+    pub royalty_policy: u8, // 0 for none, 1 for full, 2 for percentage of royalties, 3 for capped
+    pub royalty_bps: u16,   // Percentage (policy 2) or cap (policy 3) in basis points, 0 otherwise
     pub trade_count: u64,
     pub is_active: bool, // Set to true after first deposit has been made to the pair
     pub nfts_held: u32,
+    pub timelock_slots: u64, // 0 for parameter changes that apply instantly
+    pub halt_trades_while_pending: bool,
+    pub pending_change: Option<PendingChange>,
+}
+
+/// Parameter change queued on a timelocked Pair
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct PendingChange {
+    pub bonding_curve: Option<u8>,
+    pub delta: Option<u64>,
+    pub fee: Option<u16>,
+    pub spot_price: Option<u64>,
+    pub royalty_policy: Option<u8>,
+    pub royalty_bps: Option<u16>,
+    pub mint_allowlist_root: Option<Option<[u8; 32]>>, // Some(None) clears the allowlist
+    pub timelock_slots: Option<u64>,
+    pub apply_after_slot: u64,
 }
 
 /// Keeps track of NFTs in a Pair
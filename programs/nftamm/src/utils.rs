@@ -1,14 +1,19 @@
-use crate::error::ProgramError;
+use crate::{
+    curve::{mul_div, Reserves, Rounding, BASIS_POINTS},
+    error::ProgramError,
+    events::RoyaltyPaid,
+    state::{Pair, PairAuthority},
+};
 use anchor_lang::{
     prelude::*,
-    solana_program::{program_memory::sol_memcmp, pubkey::PUBKEY_BYTES},
+    solana_program::{keccak, program_memory::sol_memcmp, pubkey::PUBKEY_BYTES},
+    system_program,
 };
 use anchor_spl::{
-    associated_token::create,
-    associated_token::Create,
-    token::{transfer, Mint, Transfer},
+    associated_token::{create, get_associated_token_address, Create},
+    token::{self, transfer, Mint, Transfer},
 };
-use mpl_token_metadata::state::{Metadata, TokenMetadataAccount};
+use mpl_token_metadata::state::{Creator, Metadata, TokenMetadataAccount};
 use std::slice::Iter;
 
 // Function taken from auction house contract
@@ -47,11 +52,14 @@ pub fn assert_keys_equal(key1: Pubkey, key2: Pubkey) -> Result<()> {
     }
 }
 
-// Custom function to validate nft belongs to a specified collection and is verified
+// Custom function to validate nft belongs to a specified collection and is verified. In the
+// creator collection modes collection_mint holds the creator address and collection_metadata
+// is not read.
 pub fn validate_nft(
+    collection_mode: u8,
     nft_token_mint: Account<Mint>,
     nft_token_metadata: UncheckedAccount,
-    collection_mint: Account<Mint>,
+    collection_mint: UncheckedAccount,
     collection_metadata: UncheckedAccount,
 ) -> Result<()> {
     assert_metadata_valid(&nft_token_metadata, &nft_token_mint.key())?;
@@ -59,6 +67,12 @@ pub fn validate_nft(
     let token_metadata: Metadata =
         Metadata::from_account_info(&nft_token_metadata.to_account_info())?;
 
+    match collection_mode {
+        0 => {}
+        1 | 2 => return validate_creator(collection_mode, &token_metadata, collection_mint.key),
+        _ => return Err(ProgramError::InvalidCollectionMode.into()),
+    }
+
     let token_collection = token_metadata.collection;
     let token_collection_details = token_metadata.collection_details;
 
@@ -74,50 +88,241 @@ pub fn validate_nft(
         return Err(ProgramError::InvalidCollectionMint.into());
     }
 
-    if collection_mint.to_account_info().owner != &anchor_spl::token::ID {
-        return Err(ProgramError::InvalidCollectionMint.into());
-    }
-
     if token_collection.unwrap().verified != true {
         return Err(ProgramError::NftNotVerified.into());
     }
 
-    assert_metadata_valid(&collection_metadata, &collection_mint.key())?;
+    validate_collection(&collection_mint, &collection_metadata)
+}
+
+// Custom function to validate nft is verified by the creator identifying a pair's collection,
+// either as its first creator (collection_mode 1) or anywhere in its creators (collection_mode 2)
+fn validate_creator(
+    collection_mode: u8,
+    token_metadata: &Metadata,
+    creator: &Pubkey,
+) -> Result<()> {
+    let creators = token_metadata.data.creators.clone().unwrap_or_default();
+
+    let candidates = if collection_mode == 1 {
+        &creators[..creators.len().min(1)]
+    } else {
+        &creators[..]
+    };
+
+    if !candidates
+        .iter()
+        .any(|candidate| candidate.verified && candidate.address == *creator)
+    {
+        return Err(ProgramError::CreatorNotVerified.into());
+    }
+
+    Ok(())
+}
+
+// Custom function to validate a collection nft is a sized collection parent
+pub fn validate_collection(
+    collection_mint: &UncheckedAccount,
+    collection_metadata: &UncheckedAccount,
+) -> Result<()> {
+    if collection_mint.owner != &anchor_spl::token::ID {
+        return Err(ProgramError::InvalidCollectionMint.into());
+    }
+
+    assert_metadata_valid(collection_metadata, &collection_mint.key())?;
 
     let collection_metadata: Metadata =
         Metadata::from_account_info(&collection_metadata.to_account_info())?;
 
-    let collection_collection = collection_metadata.collection;
-    let collection_collection_details = collection_metadata.collection_details;
-
-    if collection_collection.is_some() {
+    // metadata.collection must be None
+    if collection_metadata.collection.is_some() {
         return Err(ProgramError::InvalidCollection.into());
     }
 
-    if collection_collection_details.is_none() {
+    // metadata.collection_details must be Some(V1 {...})
+    if collection_metadata.collection_details.is_none() {
         return Err(ProgramError::InvalidCollectionDetails.into());
     }
 
     Ok(())
 }
 
-// Helper function to calculate the total royalty fees for a given nft
-pub fn calculate_royalty_fee<'info>(
-    metadata_account_info: &AccountInfo<'info>,
-    size: u64,
-) -> Result<u64> {
+// Helper function to verify a mint belongs to a pair's allowlist. Leaves are the keccak hash of
+// the mint and each pair of nodes is hashed in sorted order. Pairs without a root accept any mint.
+pub fn verify_mint_allowlist(pair: &Pair, mint: &Pubkey, proof: &[[u8; 32]]) -> Result<()> {
+    let root = match pair.mint_allowlist_root {
+        Some(root) => root,
+        None => return Ok(()),
+    };
+
+    let leaf = keccak::hashv(&[mint.as_ref()]).0;
+
+    let computed_root = proof.iter().fold(leaf, |node, sibling| {
+        if node <= *sibling {
+            keccak::hashv(&[&node, sibling]).0
+        } else {
+            keccak::hashv(&[sibling, &node]).0
+        }
+    });
+
+    if computed_root != root {
+        return Err(ProgramError::MintNotAllowed.into());
+    }
+
+    Ok(())
+}
+
+// Helper function to read the reserves of a pair from its quote vault, which holds quote tokens
+// or lamports for native pairs
+pub fn pair_reserves(pair: &Pair, quote_vault: &AccountInfo) -> Result<Reserves> {
+    let quote = if pair.is_native {
+        sol_vault_balance(quote_vault)?
+    } else {
+        token::accessor::amount(quote_vault)?
+    };
+
+    Ok(Reserves {
+        quote,
+        nfts: pair.nfts_held as u64,
+    })
+}
+
+// Helper function to reject trades on pairs halted by a pending parameter change
+pub fn assert_no_pending_change(pair: &Pair) -> Result<()> {
+    if pair.halt_trades_while_pending && pair.pending_change.is_some() {
+        return Err(ProgramError::PendingPairChange.into());
+    }
+
+    Ok(())
+}
+
+// Helper function to read the royalty in basis points owed on trades of a given nft
+pub fn royalty_basis_points(metadata_account_info: &AccountInfo) -> Result<u16> {
     let metadata: Metadata = Metadata::from_account_info(metadata_account_info)?;
-    let fees = metadata.data.seller_fee_basis_points;
-    let total_fee = (fees as u128)
-        .checked_mul(size as u128)
-        .ok_or(ProgramError::NumericalOverflow)?
-        .checked_div(10000)
-        .ok_or(ProgramError::NumericalOverflow)? as u64;
 
-    Ok(total_fee)
+    // Without creators there is nobody to pay royalties to
+    match metadata.data.creators {
+        Some(creators) if !creators.is_empty() => Ok(metadata.data.seller_fee_basis_points),
+        _ => Ok(0),
+    }
 }
 
-// Helper function to honor NFT royalties for a given NFT
+// Helper function to validate the invariants between pair type, bonding curve, delta and fee.
+// Shared by pair creation and every instruction that updates these parameters.
+pub fn validate_pair_params(pair_type: u8, bonding_curve: u8, delta: u64, fee: u16) -> Result<()> {
+    if pair_type > 2 {
+        return Err(ProgramError::InvalidPairType.into());
+    }
+
+    if bonding_curve > 3 {
+        return Err(ProgramError::InvalidBondingCurve.into());
+    }
+
+    // Constant product pricing needs both quote tokens and nfts in the pair
+    if bonding_curve == 3 && pair_type != 2 {
+        return Err(ProgramError::InvalidBondingCurve.into());
+    }
+
+    // If bonding curve is exponential, then enforce delta to be represented in basis points
+    if bonding_curve == 1 && delta > BASIS_POINTS {
+        return Err(ProgramError::InvalidDelta.into());
+    }
+
+    if fee as u64 > BASIS_POINTS {
+        return Err(ProgramError::InvalidFee.into());
+    }
+
+    // Only trade pairs can charge a fee
+    if pair_type != 2 && fee != 0 {
+        return Err(ProgramError::InvalidFee.into());
+    }
+
+    Ok(())
+}
+
+// Helper function to look up the protocol fee tier of a pair type
+pub fn protocol_fee_bps(pair_authority: &PairAuthority, pair_type: u8) -> Result<u16> {
+    pair_authority
+        .fees
+        .get(pair_type as usize)
+        .copied()
+        .ok_or_else(|| ProgramError::InvalidPairType.into())
+}
+
+// Helper function to compute the quote tokens a pair needs to buy one nft at its spot price.
+// The protocol fee is taken out of that price, so the seller pays it.
+pub fn next_nft_cost(pair: &Pair) -> u64 {
+    pair.spot_price
+}
+
+// Helper function to validate protocol fee tiers
+pub fn validate_protocol_fees(fees: &[u16]) -> Result<()> {
+    // Enforce basis points convention for fees
+    if fees.iter().any(|fee| *fee as u64 > BASIS_POINTS) {
+        return Err(ProgramError::InvalidFees.into());
+    }
+
+    Ok(())
+}
+
+// Helper function to validate a royalty policy and its basis points
+pub fn validate_royalty_policy(royalty_policy: u8, royalty_bps: u16) -> Result<()> {
+    match royalty_policy {
+        // None and full royalties take no basis points
+        0 | 1 if royalty_bps == 0 => Ok(()),
+        2 | 3 if royalty_bps as u64 <= BASIS_POINTS => Ok(()),
+        _ => Err(ProgramError::InvalidRoyaltyPolicy.into()),
+    }
+}
+
+// Helper function to apply a pair's royalty policy to the seller_fee_basis_points of an nft
+pub fn apply_royalty_policy(pair: &Pair, seller_fee_basis_points: u16) -> Result<u16> {
+    match pair.royalty_policy {
+        0 => Ok(0),
+        1 => Ok(seller_fee_basis_points),
+        2 => {
+            let royalty_bps = mul_div(
+                seller_fee_basis_points as u64,
+                pair.royalty_bps as u64,
+                BASIS_POINTS,
+                Rounding::Down,
+            )?;
+
+            // Never more than seller_fee_basis_points since royalty_bps <= BASIS_POINTS
+            Ok(royalty_bps as u16)
+        }
+        3 => Ok(seller_fee_basis_points.min(pair.royalty_bps)),
+        _ => Err(ProgramError::InvalidRoyaltyPolicy.into()),
+    }
+}
+
+// Helper function to split a royalty between creators by share. The rounding dust goes to the
+// first creator with a non zero share so that exactly size is paid out.
+fn split_royalty(creators: &[Creator], size: u64) -> Result<Vec<u64>> {
+    let mut creator_fees = creators
+        .iter()
+        .map(|creator| mul_div(size, creator.share as u64, 100, Rounding::Down))
+        .collect::<Result<Vec<u64>>>()?;
+
+    let distributed = creator_fees
+        .iter()
+        .try_fold(0u64, |sum, fee| sum.checked_add(*fee))
+        .ok_or(ProgramError::NumericalOverflow)?;
+    let dust = size
+        .checked_sub(distributed)
+        .ok_or(ProgramError::NumericalOverflow)?;
+
+    if let Some(index) = creators.iter().position(|creator| creator.share > 0) {
+        creator_fees[index] = creator_fees[index]
+            .checked_add(dust)
+            .ok_or(ProgramError::NumericalOverflow)?;
+    }
+
+    Ok(creator_fees)
+}
+
+// Helper function to honor NFT royalties for a given NFT. Creator token accounts are created
+// at the expense of payer if needed.
 pub fn honor_royalties<'info>(
     is_pair_paying: bool, // if true, CpiContext::new_with_signer is used, otherwise CpiContext::new is used
     program_as_signer_bump: Option<u8>,
@@ -127,6 +332,7 @@ pub fn honor_royalties<'info>(
     associated_token_program: &AccountInfo<'info>,
     payer: &AccountInfo<'info>,
     payer_token_account: &AccountInfo<'info>,
+    authority: &AccountInfo<'info>,
     mint: &AccountInfo<'info>,
     system_program: &AccountInfo<'info>,
     token_program: &AccountInfo<'info>,
@@ -134,95 +340,219 @@ pub fn honor_royalties<'info>(
 ) -> Result<()> {
     let metadata: Metadata = Metadata::from_account_info(metadata_account_info)?;
 
-    let total_fee = size as u128;
+    let creators = match metadata.data.creators {
+        Some(creators) => creators,
+        None => {
+            msg!("No creators found in metadata");
+            return Ok(());
+        }
+    };
 
-    match metadata.data.creators {
-        Some(creators) => {
-            for creator in creators {
-                let pct = creator.share as u128;
-                let creator_fee =
-                    pct.checked_mul(total_fee as u128)
-                        .ok_or(ProgramError::NumericalOverflow)?
-                        .checked_div(100)
-                        .ok_or(ProgramError::NumericalOverflow)? as u64;
-
-                let current_creator_info = next_account_info(remaining_accounts)?;
-                assert_keys_equal(creator.address, *current_creator_info.key)?;
-
-                let current_creator_token_account_info = next_account_info(remaining_accounts)?;
-
-                if current_creator_token_account_info.data_is_empty() {
-                    // If pair_is_paying, then the payer account param must be program_as_signer. Otherwise, payer is signer
-                    let create_ata_accounts = Create {
-                        payer: payer.clone(),
-                        associated_token: associated_token_program.clone(),
-                        authority: payer.clone(),
-                        mint: mint.clone(),
-                        system_program: system_program.clone(),
-                        rent: rent_sysvar.clone(),
-                        token_program: token_program.clone(),
-                    };
-
-                    if is_pair_paying {
-                        let seeds = &[
-                            "program".as_bytes(),
-                            "signer".as_bytes(),
-                            &[program_as_signer_bump.unwrap()],
-                        ];
-
-                        let signer = &[&seeds[..]];
-
-                        let create_ctx = CpiContext::new_with_signer(
-                            associated_token_program.clone(),
-                            create_ata_accounts,
-                            signer,
-                        );
-
-                        create(create_ctx)?;
-                    } else {
-                        let create_ctx =
-                            CpiContext::new(associated_token_program.clone(), create_ata_accounts);
-
-                        create(create_ctx)?;
-                    }
-                }
-
-                if creator_fee > 0 {
-                    let transfer_accounts = Transfer {
-                        from: payer_token_account.clone(),
-                        to: current_creator_token_account_info.clone(),
-                        authority: payer.clone(),
-                    };
-
-                    if is_pair_paying {
-                        let seeds = &[
-                            "program".as_bytes(),
-                            "signer".as_bytes(),
-                            &[program_as_signer_bump.unwrap()],
-                        ];
-
-                        let signer = &[&seeds[..]];
-
-                        let transfer_ctx = CpiContext::new_with_signer(
-                            token_program.clone(),
-                            transfer_accounts,
-                            signer,
-                        );
-
-                        transfer(transfer_ctx, creator_fee)?;
-                    } else {
-                        let transfer_ctx =
-                            CpiContext::new(token_program.clone(), transfer_accounts);
-
-                        transfer(transfer_ctx, creator_fee)?;
-                    }
-                }
-            }
+    let creator_fees = split_royalty(&creators, size)?;
+
+    let seeds: &[&[u8]] = &[
+        "program".as_bytes(),
+        "signer".as_bytes(),
+        &[program_as_signer_bump.unwrap_or_default()],
+    ];
+
+    let signer = &[seeds];
+
+    for (creator, creator_fee) in creators.iter().zip(creator_fees) {
+        let current_creator_info = next_account_info(remaining_accounts)?;
+        assert_keys_equal(creator.address, *current_creator_info.key)?;
+
+        let current_creator_token_account_info = next_account_info(remaining_accounts)?;
+        assert_keys_equal(
+            get_associated_token_address(&creator.address, mint.key),
+            *current_creator_token_account_info.key,
+        )?;
+
+        if creator_fee == 0 {
+            continue;
+        }
+
+        if current_creator_token_account_info.data_is_empty() {
+            // Rent for the creator's token account is always paid by the trader
+            let create_ata_accounts = Create {
+                payer: payer.clone(),
+                associated_token: current_creator_token_account_info.clone(),
+                authority: current_creator_info.clone(),
+                mint: mint.clone(),
+                system_program: system_program.clone(),
+                rent: rent_sysvar.clone(),
+                token_program: token_program.clone(),
+            };
+
+            let create_ctx = CpiContext::new(associated_token_program.clone(), create_ata_accounts);
+
+            create(create_ctx)?;
+        }
+
+        let transfer_accounts = Transfer {
+            from: payer_token_account.clone(),
+            to: current_creator_token_account_info.clone(),
+            authority: authority.clone(),
+        };
+
+        if is_pair_paying {
+            let transfer_ctx =
+                CpiContext::new_with_signer(token_program.clone(), transfer_accounts, signer);
+
+            transfer(transfer_ctx, creator_fee)?;
+        } else {
+            let transfer_ctx = CpiContext::new(token_program.clone(), transfer_accounts);
+
+            transfer(transfer_ctx, creator_fee)?;
         }
+
+        emit!(RoyaltyPaid {
+            mint: metadata.mint,
+            creator: creator.address,
+            amount: creator_fee,
+        });
+    }
+
+    Ok(())
+}
+
+// Helper function to honor NFT royalties for a given NFT in native sol. remaining_accounts holds
+// one creator account per creator in the metadata, and signer must be set when from is a sol vault.
+pub fn honor_royalties_sol<'info>(
+    remaining_accounts: &mut Iter<AccountInfo<'info>>,
+    metadata_account_info: &AccountInfo<'info>,
+    size: u64,
+    from: &AccountInfo<'info>,
+    signer: Option<&[&[&[u8]]]>,
+    system_program: &AccountInfo<'info>,
+) -> Result<()> {
+    let metadata: Metadata = Metadata::from_account_info(metadata_account_info)?;
+
+    let creators = match metadata.data.creators {
+        Some(creators) => creators,
         None => {
             msg!("No creators found in metadata");
+            return Ok(());
+        }
+    };
+
+    let creator_fees = split_royalty(&creators, size)?;
+
+    for (creator, creator_fee) in creators.iter().zip(creator_fees) {
+        let current_creator_info = next_account_info(remaining_accounts)?;
+        assert_keys_equal(creator.address, *current_creator_info.key)?;
+
+        if creator_fee == 0 {
+            continue;
         }
+
+        transfer_sol(
+            from,
+            current_creator_info,
+            system_program,
+            signer,
+            creator_fee,
+        )?;
+
+        emit!(RoyaltyPaid {
+            mint: metadata.mint,
+            creator: creator.address,
+            amount: creator_fee,
+        });
     }
 
     Ok(())
 }
+
+// Helper function to move lamports with the system program. signer must be set when from is a
+// sol vault, which only the program can sign for.
+pub fn transfer_sol<'info>(
+    from: &AccountInfo<'info>,
+    to: &AccountInfo<'info>,
+    system_program: &AccountInfo<'info>,
+    signer: Option<&[&[&[u8]]]>,
+    amount: u64,
+) -> Result<()> {
+    if amount == 0 {
+        return Ok(());
+    }
+
+    let transfer_accounts = system_program::Transfer {
+        from: from.clone(),
+        to: to.clone(),
+    };
+
+    match signer {
+        Some(signer) => system_program::transfer(
+            CpiContext::new_with_signer(system_program.clone(), transfer_accounts, signer),
+            amount,
+        ),
+        None => system_program::transfer(
+            CpiContext::new(system_program.clone(), transfer_accounts),
+            amount,
+        ),
+    }
+}
+
+// Helper function to create a PDA the way anchor's init does. create_account fails on an address
+// that already holds lamports, which anyone can send to a PDA, so such an account is topped up to
+// rent exemption and then allocated and assigned instead.
+pub fn create_pda_account<'info>(
+    payer: &AccountInfo<'info>,
+    account: &AccountInfo<'info>,
+    system_program: &AccountInfo<'info>,
+    signer: &[&[&[u8]]],
+    space: usize,
+    owner: &Pubkey,
+) -> Result<()> {
+    let rent_exempt_lamports = Rent::get()?.minimum_balance(space);
+    let current_lamports = account.lamports();
+
+    if current_lamports == 0 {
+        let create_accounts = system_program::CreateAccount {
+            from: payer.clone(),
+            to: account.clone(),
+        };
+
+        return system_program::create_account(
+            CpiContext::new_with_signer(system_program.clone(), create_accounts, signer),
+            rent_exempt_lamports,
+            space as u64,
+            owner,
+        );
+    }
+
+    transfer_sol(
+        payer,
+        account,
+        system_program,
+        None,
+        rent_exempt_lamports.saturating_sub(current_lamports),
+    )?;
+
+    let allocate_accounts = system_program::Allocate {
+        account_to_allocate: account.clone(),
+    };
+
+    system_program::allocate(
+        CpiContext::new_with_signer(system_program.clone(), allocate_accounts, signer),
+        space as u64,
+    )?;
+
+    let assign_accounts = system_program::Assign {
+        account_to_assign: account.clone(),
+    };
+
+    system_program::assign(
+        CpiContext::new_with_signer(system_program.clone(), assign_accounts, signer),
+        owner,
+    )
+}
+
+// Helper function to read the lamports a sol vault can spend. The vault always keeps its rent
+// exempt reserve, which is only released when the pair is closed.
+pub fn sol_vault_balance(sol_vault: &AccountInfo) -> Result<u64> {
+    let reserve = Rent::get()?.minimum_balance(0);
+
+    Ok(sol_vault.lamports().saturating_sub(reserve))
+}
@@ -0,0 +1,654 @@
+//! Shared harness for the nftamm integration tests.
+//!
+//! The suite runs under `cargo test-bpf`, which builds nftamm to BPF and enables the `test-bpf`
+//! feature. Metaplex token metadata is loaded from the fixture in `tests/deps`, so every test runs
+//! offline.
+#![allow(dead_code)]
+
+use anchor_lang::{AccountDeserialize, InstructionData, ToAccountMetas};
+use anchor_spl::token::spl_token;
+use mpl_token_metadata::{
+    instruction as mpl_instruction,
+    pda::find_master_edition_account,
+    state::{Collection, CollectionDetails, Creator},
+};
+use nftamm_client::{
+    instructions, pda,
+    state::{Pair, PairAuthority},
+};
+use solana_program_test::{processor, ProgramTest, ProgramTestContext};
+use solana_sdk::{
+    account::Account,
+    bpf_loader,
+    clock::Clock,
+    instruction::{Instruction, InstructionError},
+    program_pack::Pack,
+    pubkey::Pubkey,
+    rent::Rent,
+    signature::{Keypair, Signer},
+    system_instruction,
+    transaction::{Transaction, TransactionError},
+    transport::TransportError,
+};
+use std::path::PathBuf;
+
+pub use nftamm::error::ProgramError as NftammError;
+
+pub const LAMPORTS_PER_SOL: u64 = 1_000_000_000;
+
+/// Protocol fee charged on every pair type by [setup_protocol], in basis points
+pub const PROTOCOL_FEE: u16 = 100;
+
+pub type TestResult = Result<(), TransportError>;
+
+/// An nft mint with its Metaplex accounts
+#[derive(Clone, Copy, Debug)]
+pub struct Nft {
+    pub mint: Pubkey,
+    pub metadata: Pubkey,
+    pub edition: Pubkey,
+}
+
+/// The pair authority of a test, with its admin
+pub struct Protocol {
+    pub admin: Keypair,
+    pub fee_recipient: Keypair,
+    pub pair_authority: Pubkey,
+}
+
+/// A collection and its creator, who is also its update and collection authority
+pub struct TestCollection {
+    pub authority: Keypair,
+    pub nft: Nft,
+}
+
+pub fn program_test() -> ProgramTest {
+    // `cargo test-bpf` points BPF_OUT_DIR at the nftamm shared object, which is then preferred
+    // over the native processor
+    let mut program_test = ProgramTest::new("nftamm", nftamm::ID, processor!(nftamm::entry));
+
+    let fixture =
+        PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("../../tests/deps/mpl_token_metadata.so");
+    let data = std::fs::read(&fixture).expect("missing Metaplex token metadata fixture");
+
+    program_test.add_account(
+        mpl_token_metadata::ID,
+        Account {
+            lamports: Rent::default().minimum_balance(data.len()),
+            data,
+            owner: bpf_loader::ID,
+            executable: true,
+            rent_epoch: 0,
+        },
+    );
+
+    program_test
+}
+
+pub async fn start() -> ProgramTestContext {
+    program_test().start_with_context().await
+}
+
+/// Send `instructions` in one transaction paid by the context payer
+pub async fn process(
+    context: &mut ProgramTestContext,
+    instructions: &[Instruction],
+    signers: &[&Keypair],
+) -> TestResult {
+    let mut all_signers = vec![&context.payer];
+    all_signers.extend_from_slice(signers);
+
+    let transaction = Transaction::new_signed_with_payer(
+        instructions,
+        Some(&context.payer.pubkey()),
+        &all_signers,
+        context.last_blockhash,
+    );
+
+    context.banks_client.process_transaction(transaction).await
+}
+
+/// Assert a transaction failed with an nftamm error
+pub fn assert_nftamm_error(result: TestResult, expected: NftammError) {
+    assert_custom_error(result, u32::from(expected));
+}
+
+/// Assert a transaction failed with an anchor framework error
+pub fn assert_anchor_error(result: TestResult, expected: anchor_lang::error::ErrorCode) {
+    assert_custom_error(result, u32::from(expected));
+}
+
+pub fn assert_custom_error(result: TestResult, expected: u32) {
+    match result {
+        Err(TransportError::TransactionError(TransactionError::InstructionError(
+            _,
+            InstructionError::Custom(code),
+        ))) => assert_eq!(code, expected, "unexpected error code"),
+        other => panic!("expected custom error {}, got {:?}", expected, other),
+    }
+}
+
+/// Build an nftamm instruction from raw accounts and data, for calls the client does not cover
+pub fn raw_instruction(accounts: impl ToAccountMetas, data: impl InstructionData) -> Instruction {
+    Instruction {
+        program_id: nftamm::ID,
+        accounts: accounts.to_account_metas(None),
+        data: data.data(),
+    }
+}
+
+pub async fn get_account(context: &mut ProgramTestContext, address: &Pubkey) -> Option<Account> {
+    context.banks_client.get_account(*address).await.unwrap()
+}
+
+pub async fn get_anchor_account<T: AccountDeserialize>(
+    context: &mut ProgramTestContext,
+    address: &Pubkey,
+) -> T {
+    let account = get_account(context, address)
+        .await
+        .expect("account does not exist");
+
+    T::try_deserialize(&mut &account.data[..]).unwrap()
+}
+
+pub async fn get_pair(context: &mut ProgramTestContext, pair: &Pubkey) -> Pair {
+    get_anchor_account(context, pair).await
+}
+
+pub async fn lamports(context: &mut ProgramTestContext, address: &Pubkey) -> u64 {
+    get_account(context, address)
+        .await
+        .map(|account| account.lamports)
+        .unwrap_or_default()
+}
+
+pub async fn token_balance(context: &mut ProgramTestContext, address: &Pubkey) -> u64 {
+    let account = get_account(context, address)
+        .await
+        .expect("token account does not exist");
+
+    spl_token::state::Account::unpack(&account.data)
+        .unwrap()
+        .amount
+}
+
+/// A new keypair holding `sol` SOL
+pub async fn funded_keypair(context: &mut ProgramTestContext, sol: u64) -> Keypair {
+    let keypair = Keypair::new();
+
+    transfer_lamports(context, &keypair.pubkey(), sol * LAMPORTS_PER_SOL).await;
+
+    keypair
+}
+
+pub async fn transfer_lamports(context: &mut ProgramTestContext, to: &Pubkey, lamports: u64) {
+    let instruction = system_instruction::transfer(&context.payer.pubkey(), to, lamports);
+
+    process(context, &[instruction], &[]).await.unwrap();
+}
+
+pub async fn create_mint(
+    context: &mut ProgramTestContext,
+    authority: &Pubkey,
+    decimals: u8,
+) -> Pubkey {
+    let mint = Keypair::new();
+    let rent = context.banks_client.get_rent().await.unwrap();
+
+    let instructions = [
+        system_instruction::create_account(
+            &context.payer.pubkey(),
+            &mint.pubkey(),
+            rent.minimum_balance(spl_token::state::Mint::LEN),
+            spl_token::state::Mint::LEN as u64,
+            &spl_token::ID,
+        ),
+        spl_token::instruction::initialize_mint(
+            &spl_token::ID,
+            &mint.pubkey(),
+            authority,
+            Some(authority),
+            decimals,
+        )
+        .unwrap(),
+    ];
+
+    process(context, &instructions, &[&mint]).await.unwrap();
+
+    mint.pubkey()
+}
+
+pub async fn create_associated_token_account(
+    context: &mut ProgramTestContext,
+    owner: &Pubkey,
+    mint: &Pubkey,
+) -> Pubkey {
+    // The associated token program shipped with solana 1.9 still expects the rent sysvar
+    #[allow(deprecated)]
+    let instruction = spl_associated_token_account::create_associated_token_account(
+        &context.payer.pubkey(),
+        owner,
+        mint,
+    );
+
+    process(context, &[instruction], &[]).await.unwrap();
+
+    pda::associated_token_address(owner, mint)
+}
+
+pub async fn mint_to(
+    context: &mut ProgramTestContext,
+    mint: &Pubkey,
+    authority: &Keypair,
+    account: &Pubkey,
+    amount: u64,
+) {
+    let instruction = spl_token::instruction::mint_to(
+        &spl_token::ID,
+        mint,
+        account,
+        &authority.pubkey(),
+        &[],
+        amount,
+    )
+    .unwrap();
+
+    process(context, &[instruction], &[authority])
+        .await
+        .unwrap();
+}
+
+/// A quote token mint, with `amount` tokens minted to the ATA of every holder
+pub async fn create_quote_mint(
+    context: &mut ProgramTestContext,
+    holders: &[&Pubkey],
+    amount: u64,
+) -> Pubkey {
+    let authority = Keypair::new();
+    let mint = create_mint(context, &authority.pubkey(), 6).await;
+
+    for holder in holders {
+        let account = create_associated_token_account(context, holder, &mint).await;
+        mint_to(context, &mint, &authority, &account, amount).await;
+    }
+
+    mint
+}
+
+/// Mint an nft to `owner`, with `creators` verified by the update authority when it is one of
+/// them
+#[allow(clippy::too_many_arguments)]
+async fn mint_nft(
+    context: &mut ProgramTestContext,
+    update_authority: &Keypair,
+    owner: &Pubkey,
+    creators: Option<Vec<Creator>>,
+    seller_fee_basis_points: u16,
+    collection: Option<Collection>,
+    collection_details: Option<CollectionDetails>,
+) -> Nft {
+    let mint = create_mint(context, &update_authority.pubkey(), 0).await;
+    let account = create_associated_token_account(context, owner, &mint).await;
+    mint_to(context, &mint, update_authority, &account, 1).await;
+
+    let metadata = pda::find_metadata(&mint).0;
+    let edition = find_master_edition_account(&mint).0;
+    let payer = context.payer.pubkey();
+
+    let instructions = [
+        mpl_instruction::create_metadata_accounts_v3(
+            mpl_token_metadata::ID,
+            metadata,
+            mint,
+            update_authority.pubkey(),
+            payer,
+            update_authority.pubkey(),
+            "nftamm".to_string(),
+            "AMM".to_string(),
+            "https://nftamm.test".to_string(),
+            creators,
+            seller_fee_basis_points,
+            true,
+            true,
+            collection,
+            None,
+            collection_details,
+        ),
+        mpl_instruction::create_master_edition_v3(
+            mpl_token_metadata::ID,
+            edition,
+            mint,
+            update_authority.pubkey(),
+            update_authority.pubkey(),
+            metadata,
+            payer,
+            Some(0),
+        ),
+    ];
+
+    process(context, &instructions, &[update_authority])
+        .await
+        .unwrap();
+
+    Nft {
+        mint,
+        metadata,
+        edition,
+    }
+}
+
+/// A sized collection parent nft, owned by its authority
+pub async fn create_collection(context: &mut ProgramTestContext) -> TestCollection {
+    let authority = funded_keypair(context, 10).await;
+    let creators = vec![Creator {
+        address: authority.pubkey(),
+        verified: true,
+        share: 100,
+    }];
+
+    let nft = mint_nft(
+        context,
+        &authority,
+        &authority.pubkey(),
+        Some(creators),
+        0,
+        None,
+        Some(CollectionDetails::V1 { size: 0 }),
+    )
+    .await;
+
+    TestCollection { authority, nft }
+}
+
+/// An nft of `collection` owned by `owner`, verified in the collection and by its creator
+pub async fn create_collection_nft(
+    context: &mut ProgramTestContext,
+    collection: &TestCollection,
+    owner: &Pubkey,
+    seller_fee_basis_points: u16,
+) -> Nft {
+    let creators = vec![Creator {
+        address: collection.authority.pubkey(),
+        verified: true,
+        share: 100,
+    }];
+
+    let nft = mint_nft(
+        context,
+        &collection.authority,
+        owner,
+        Some(creators),
+        seller_fee_basis_points,
+        Some(Collection {
+            verified: false,
+            key: collection.nft.mint,
+        }),
+        None,
+    )
+    .await;
+
+    let instruction = mpl_instruction::verify_sized_collection_item(
+        mpl_token_metadata::ID,
+        nft.metadata,
+        collection.authority.pubkey(),
+        context.payer.pubkey(),
+        collection.nft.mint,
+        collection.nft.metadata,
+        collection.nft.edition,
+        None,
+    );
+
+    process(context, &[instruction], &[&collection.authority])
+        .await
+        .unwrap();
+
+    nft
+}
+
+/// An nft claiming to be part of `collection` without being verified in it
+pub async fn create_unverified_nft(
+    context: &mut ProgramTestContext,
+    collection: &TestCollection,
+    owner: &Pubkey,
+) -> Nft {
+    mint_nft(
+        context,
+        &collection.authority,
+        owner,
+        None,
+        0,
+        Some(Collection {
+            verified: false,
+            key: collection.nft.mint,
+        }),
+        None,
+    )
+    .await
+}
+
+/// A pair authority charging [PROTOCOL_FEE] on every pair type, with protocol fee vaults for
+/// native sol and every quote mint in `quote_token_mints`
+pub async fn setup_protocol(
+    context: &mut ProgramTestContext,
+    quote_token_mints: &[Pubkey],
+) -> Protocol {
+    let admin = funded_keypair(context, 10).await;
+    let fee_recipient = Keypair::new();
+    let pair_authority = Keypair::new();
+
+    let mut ixs = vec![
+        instructions::initialize_pair_authority(
+            admin.pubkey(),
+            pair_authority.pubkey(),
+            fee_recipient.pubkey(),
+            [PROTOCOL_FEE; 3],
+        ),
+        instructions::initialize_protocol_sol_fee_vault(admin.pubkey(), pair_authority.pubkey()),
+    ];
+
+    for quote_token_mint in quote_token_mints {
+        ixs.push(instructions::initialize_protocol_fee_vault(
+            admin.pubkey(),
+            pair_authority.pubkey(),
+            *quote_token_mint,
+        ));
+    }
+
+    process(context, &ixs, &[&admin, &pair_authority])
+        .await
+        .unwrap();
+
+    Protocol {
+        admin,
+        fee_recipient,
+        pair_authority: pair_authority.pubkey(),
+    }
+}
+
+/// Create a pair quoted in `quote_token_mint`, or in native sol when it is None
+pub async fn create_pair(
+    context: &mut ProgramTestContext,
+    owner: &Keypair,
+    protocol: &Protocol,
+    collection: &TestCollection,
+    quote_token_mint: Option<Pubkey>,
+    params: instructions::PairParams,
+) -> Result<Pubkey, TransportError> {
+    let pair = Keypair::new();
+
+    let instruction = match quote_token_mint {
+        Some(quote_token_mint) => instructions::initialize_pair(
+            owner.pubkey(),
+            pair.pubkey(),
+            protocol.pair_authority,
+            collection.nft.mint,
+            quote_token_mint,
+            params,
+        ),
+        None => instructions::initialize_sol_pair(
+            owner.pubkey(),
+            pair.pubkey(),
+            protocol.pair_authority,
+            collection.nft.mint,
+            params,
+        ),
+    };
+
+    process(context, &[instruction], &[owner, &pair]).await?;
+
+    Ok(pair.pubkey())
+}
+
+/// Linear curve parameters for a pair of `pair_type`
+pub fn linear_params(pair_type: u8, spot_price: u64, delta: u64) -> instructions::PairParams {
+    instructions::PairParams {
+        pair_type,
+        bonding_curve: 0,
+        delta,
+        fee: if pair_type == 2 { 200 } else { 0 },
+        spot_price,
+        ..Default::default()
+    }
+}
+
+/// Slot seen by the program, which can be ahead of the root slot
+pub async fn current_slot(context: &mut ProgramTestContext) -> u64 {
+    let clock: Clock = context.banks_client.get_sysvar().await.unwrap();
+
+    clock.slot
+}
+
+/// Refresh the blockhash so an identical transaction can be sent again
+pub async fn refresh_blockhash(context: &mut ProgramTestContext) {
+    let slot = current_slot(context).await;
+    context.warp_to_slot(slot + 2).unwrap();
+}
+
+/// Everything a trading test needs: a collection, a pair authority, a pair owner and a trader
+/// both holding [QUOTE_BALANCE] of a quote token
+pub struct Market {
+    pub context: ProgramTestContext,
+    pub collection: TestCollection,
+    pub protocol: Protocol,
+    pub owner: Keypair,
+    pub trader: Keypair,
+    pub quote_token_mint: Pubkey,
+}
+
+pub const QUOTE_BALANCE: u64 = 1_000_000_000;
+
+pub async fn setup_market() -> Market {
+    let mut context = start().await;
+    let collection = create_collection(&mut context).await;
+    let owner = funded_keypair(&mut context, 10).await;
+    let trader = funded_keypair(&mut context, 10).await;
+    let quote_token_mint = create_quote_mint(
+        &mut context,
+        &[&owner.pubkey(), &trader.pubkey()],
+        QUOTE_BALANCE,
+    )
+    .await;
+    let protocol = setup_protocol(&mut context, &[quote_token_mint]).await;
+
+    Market {
+        context,
+        collection,
+        protocol,
+        owner,
+        trader,
+        quote_token_mint,
+    }
+}
+
+impl Market {
+    /// A pair owned by [Market::owner], quoted in the market's quote token or native sol
+    pub async fn create_pair(
+        &mut self,
+        native: bool,
+        params: instructions::PairParams,
+    ) -> Result<Pubkey, TransportError> {
+        let quote_token_mint = if native {
+            None
+        } else {
+            Some(self.quote_token_mint)
+        };
+
+        create_pair(
+            &mut self.context,
+            &self.owner,
+            &self.protocol,
+            &self.collection,
+            quote_token_mint,
+            params,
+        )
+        .await
+    }
+
+    /// A verified nft of the market's collection held by `owner`
+    pub async fn mint_nft(&mut self, owner: &Pubkey) -> Nft {
+        create_collection_nft(&mut self.context, &self.collection, owner, 0).await
+    }
+
+    pub async fn pair(&mut self, pair: &Pubkey) -> Pair {
+        get_pair(&mut self.context, pair).await
+    }
+
+    /// Send instructions signed by the admin of the pair authority
+    pub async fn as_admin(&mut self, instructions: &[Instruction]) -> TestResult {
+        process(&mut self.context, instructions, &[&self.protocol.admin]).await
+    }
+
+    /// Send instructions signed by the pair owner
+    pub async fn as_owner(&mut self, instructions: &[Instruction]) -> TestResult {
+        process(&mut self.context, instructions, &[&self.owner]).await
+    }
+
+    /// Send instructions signed by the trader
+    pub async fn as_trader(&mut self, instructions: &[Instruction]) -> TestResult {
+        process(&mut self.context, instructions, &[&self.trader]).await
+    }
+
+    /// Deposit quote tokens, or lamports for native pairs, from the pair owner
+    pub async fn fund_pair(&mut self, pair_key: &Pubkey, amount: u64) -> TestResult {
+        let pair = self.pair(pair_key).await;
+        let owner = self.owner.pubkey();
+
+        let instruction = if pair.is_native {
+            instructions::fund_sol_pair(owner, *pair_key, &pair, amount)
+        } else {
+            instructions::fund_token_pair(owner, *pair_key, &pair, amount)
+        };
+
+        self.as_owner(&[instruction]).await
+    }
+
+    /// Mint an nft to the pair owner and deposit it in the pair
+    pub async fn deposit_nft(&mut self, pair_key: &Pubkey) -> Result<Nft, TransportError> {
+        let nft = self.mint_nft(&self.owner.pubkey()).await;
+        let pair = self.pair(pair_key).await;
+        let instruction =
+            instructions::fund_nft_pair(self.owner.pubkey(), *pair_key, &pair, nft.mint, vec![]);
+
+        self.as_owner(&[instruction]).await?;
+
+        Ok(nft)
+    }
+
+    pub async fn quote_balance(&mut self, owner: &Pubkey) -> u64 {
+        let account = pda::associated_token_address(owner, &self.quote_token_mint);
+
+        token_balance(&mut self.context, &account).await
+    }
+
+    pub async fn protocol_fees(&mut self) -> u64 {
+        let vault =
+            pda::find_protocol_fee_vault(&self.protocol.pair_authority, &self.quote_token_mint).0;
+
+        token_balance(&mut self.context, &vault).await
+    }
+
+    pub async fn pair_authority(&mut self) -> PairAuthority {
+        get_anchor_account(&mut self.context, &self.protocol.pair_authority).await
+    }
+}
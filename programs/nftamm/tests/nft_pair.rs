@@ -0,0 +1,244 @@
+#![cfg(feature = "test-bpf")]
+
+mod common;
+
+use common::*;
+use nftamm_client::{instructions, pda, royalty};
+use solana_program_test::tokio;
+use solana_sdk::{pubkey::Pubkey, signature::Signer};
+
+const SPOT_PRICE: u64 = 1_000_000;
+const DELTA: u64 = 100_000;
+
+async fn nft_pair(market: &mut Market, params: instructions::PairParams) -> (Pubkey, Nft) {
+    let pair = market.create_pair(false, params).await.unwrap();
+    let nft = market.deposit_nft(&pair).await.unwrap();
+
+    (pair, nft)
+}
+
+#[tokio::test]
+async fn deposit_activates_nft_pair() {
+    let mut market = setup_market().await;
+    let pair = market
+        .create_pair(false, linear_params(1, SPOT_PRICE, DELTA))
+        .await
+        .unwrap();
+
+    assert!(!market.pair(&pair).await.is_active);
+
+    let nft = market.deposit_nft(&pair).await.unwrap();
+
+    let pair_account = market.pair(&pair).await;
+    assert!(pair_account.is_active);
+    assert_eq!(pair_account.nfts_held, 1);
+
+    let nft_vault = pda::find_nft_token_vault(&pair, &nft.mint).0;
+    assert_eq!(token_balance(&mut market.context, &nft_vault).await, 1);
+}
+
+#[tokio::test]
+async fn buy_nft_from_nft_pair() {
+    let mut market = setup_market().await;
+    let (pair, nft) = nft_pair(&mut market, linear_params(1, SPOT_PRICE, DELTA)).await;
+
+    let trader = market.trader.pubkey();
+    let owner = market.owner.pubkey();
+    let pair_account = market.pair(&pair).await;
+
+    // A buy is charged the spot price it moves the pair to
+    let price = SPOT_PRICE + DELTA;
+    let protocol_fee = price * PROTOCOL_FEE as u64 / 10_000;
+
+    let instruction = instructions::trade_nft_pair(
+        trader,
+        pair,
+        &pair_account,
+        nft.mint,
+        owner,
+        price + protocol_fee,
+        Some(0),
+        vec![],
+    );
+    market.as_trader(&[instruction]).await.unwrap();
+
+    assert_eq!(
+        market.quote_balance(&trader).await,
+        QUOTE_BALANCE - price - protocol_fee
+    );
+    assert_eq!(market.protocol_fees().await, protocol_fee);
+    assert_eq!(
+        token_balance(&mut market.context, &pair_account.quote_token_vault).await,
+        price
+    );
+
+    let trader_nft_account = pda::associated_token_address(&trader, &nft.mint);
+    assert_eq!(
+        token_balance(&mut market.context, &trader_nft_account).await,
+        1
+    );
+
+    // The pair metadata is closed back to the depositor
+    let pair_metadata = pda::find_pair_metadata(&pair, &nft.mint).0;
+    assert!(get_account(&mut market.context, &pair_metadata)
+        .await
+        .is_none());
+
+    let pair_account = market.pair(&pair).await;
+    assert_eq!(pair_account.spot_price, SPOT_PRICE + DELTA);
+    assert_eq!(pair_account.nfts_held, 0);
+    assert_eq!(pair_account.trade_count, 1);
+
+    // An empty nft pair has nothing left to sell
+    assert!(!pair_account.is_active);
+}
+
+#[tokio::test]
+async fn trade_nft_pair_rejects_slippage() {
+    let mut market = setup_market().await;
+    let (pair, nft) = nft_pair(&mut market, linear_params(1, SPOT_PRICE, DELTA)).await;
+
+    let trader = market.trader.pubkey();
+    let owner = market.owner.pubkey();
+    let pair_account = market.pair(&pair).await;
+
+    // The protocol fee comes on top of the spot price
+    let instruction = instructions::trade_nft_pair(
+        trader,
+        pair,
+        &pair_account,
+        nft.mint,
+        owner,
+        SPOT_PRICE,
+        None,
+        vec![],
+    );
+    let result = market.as_trader(&[instruction]).await;
+
+    assert_nftamm_error(result, NftammError::SlippageExceeded);
+}
+
+#[tokio::test]
+async fn buy_nft_pays_royalties_to_creators() {
+    let mut market = setup_market().await;
+    let params = instructions::PairParams {
+        royalty_policy: 1,
+        ..linear_params(1, SPOT_PRICE, 0)
+    };
+    let pair = market.create_pair(false, params).await.unwrap();
+
+    let owner = market.owner.pubkey();
+    let nft = create_collection_nft(&mut market.context, &market.collection, &owner, 500).await;
+    let pair_account = market.pair(&pair).await;
+    let instruction = instructions::fund_nft_pair(owner, pair, &pair_account, nft.mint, vec![]);
+    market.as_owner(&[instruction]).await.unwrap();
+
+    let metadata = get_account(&mut market.context, &nft.metadata)
+        .await
+        .unwrap();
+    let creators = royalty::metadata_creators(&metadata.data).unwrap();
+    let royalty_accounts = royalty::creator_accounts(&creators, &market.quote_token_mint);
+
+    // Royalties must be passed for every creator
+    let trader = market.trader.pubkey();
+    let instruction = instructions::trade_nft_pair(
+        trader,
+        pair,
+        &pair_account,
+        nft.mint,
+        owner,
+        u64::MAX,
+        None,
+        vec![],
+    );
+    let result = market.as_trader(&[instruction]).await;
+    assert!(result.is_err());
+
+    let instruction = instructions::trade_nft_pair(
+        trader,
+        pair,
+        &pair_account,
+        nft.mint,
+        owner,
+        u64::MAX,
+        None,
+        royalty_accounts,
+    );
+    market.as_trader(&[instruction]).await.unwrap();
+
+    let royalty = SPOT_PRICE * 500 / 10_000;
+    let protocol_fee = SPOT_PRICE * PROTOCOL_FEE as u64 / 10_000;
+    let creator = market.collection.authority.pubkey();
+
+    assert_eq!(market.quote_balance(&creator).await, royalty);
+    assert_eq!(
+        market.quote_balance(&trader).await,
+        QUOTE_BALANCE - SPOT_PRICE - protocol_fee - royalty
+    );
+}
+
+#[tokio::test]
+async fn withdrawing_last_nft_deactivates_nft_pair() {
+    let mut market = setup_market().await;
+    let (pair, nft) = nft_pair(&mut market, linear_params(1, SPOT_PRICE, DELTA)).await;
+
+    let owner = market.owner.pubkey();
+    let pair_account = market.pair(&pair).await;
+    let instruction = instructions::withdraw_nft(owner, pair, &pair_account, nft.mint, owner);
+    market.as_owner(&[instruction]).await.unwrap();
+
+    let pair_account = market.pair(&pair).await;
+    assert_eq!(pair_account.nfts_held, 0);
+    assert!(!pair_account.is_active);
+
+    let owner_nft_account = pda::associated_token_address(&owner, &nft.mint);
+    assert_eq!(
+        token_balance(&mut market.context, &owner_nft_account).await,
+        1
+    );
+}
+
+#[tokio::test]
+async fn withdraw_nft_requires_pair_owner() {
+    let mut market = setup_market().await;
+    let (pair, nft) = nft_pair(&mut market, linear_params(1, SPOT_PRICE, DELTA)).await;
+
+    let owner = market.owner.pubkey();
+    let trader = market.trader.pubkey();
+    let pair_account = market.pair(&pair).await;
+    let instruction = instructions::withdraw_nft(trader, pair, &pair_account, nft.mint, owner);
+    let result = market.as_trader(&[instruction]).await;
+
+    assert_nftamm_error(result, NftammError::InvalidOwner);
+}
+
+#[tokio::test]
+async fn close_nft_pair_requires_withdrawing_nfts() {
+    let mut market = setup_market().await;
+    let (pair, nft) = nft_pair(&mut market, linear_params(1, SPOT_PRICE, DELTA)).await;
+
+    let owner = market.owner.pubkey();
+    let pair_account = market.pair(&pair).await;
+
+    let instruction = instructions::close_pair(owner, pair, &pair_account);
+    let result = market.as_owner(&[instruction]).await;
+    assert_nftamm_error(result, NftammError::StillHoldsNfts);
+
+    let instructions = [
+        instructions::withdraw_nft(owner, pair, &pair_account, nft.mint, owner),
+        instructions::close_pair(owner, pair, &pair_account),
+    ];
+    market.as_owner(&instructions).await.unwrap();
+
+    assert!(get_account(&mut market.context, &pair).await.is_none());
+}
+
+#[tokio::test]
+async fn nft_pair_rejects_quote_deposits() {
+    let mut market = setup_market().await;
+    let (pair, _) = nft_pair(&mut market, linear_params(1, SPOT_PRICE, DELTA)).await;
+
+    let result = market.fund_pair(&pair, SPOT_PRICE).await;
+
+    assert_nftamm_error(result, NftammError::InvalidPairType);
+}
@@ -0,0 +1,345 @@
+#![cfg(feature = "test-bpf")]
+
+mod common;
+
+use common::*;
+use nftamm_client::{instructions, pda};
+use solana_program_test::tokio;
+use solana_sdk::signature::{Keypair, Signer};
+
+#[tokio::test]
+async fn initialize_pair_authority_rejects_invalid_fees() {
+    let mut context = start().await;
+    let admin = funded_keypair(&mut context, 1).await;
+    let pair_authority = Keypair::new();
+
+    let instruction = instructions::initialize_pair_authority(
+        admin.pubkey(),
+        pair_authority.pubkey(),
+        Keypair::new().pubkey(),
+        [100, 10_001, 100],
+    );
+    let result = process(&mut context, &[instruction], &[&admin, &pair_authority]).await;
+
+    assert_nftamm_error(result, NftammError::InvalidFees);
+}
+
+#[tokio::test]
+async fn transfer_and_accept_pair_authority() {
+    let mut market = setup_market().await;
+    let pair_authority = market.protocol.pair_authority;
+    let admin = market.protocol.admin.pubkey();
+    let new_admin = funded_keypair(&mut market.context, 1).await;
+
+    // Only the current authority can hand the pair authority over
+    let instruction = instructions::transfer_pair_authority(
+        new_admin.pubkey(),
+        pair_authority,
+        new_admin.pubkey(),
+    );
+    let result = process(&mut market.context, &[instruction], &[&new_admin]).await;
+    assert_nftamm_error(result, NftammError::InvalidCurrentAuthority);
+
+    let instruction =
+        instructions::transfer_pair_authority(admin, pair_authority, new_admin.pubkey());
+    market.as_admin(&[instruction]).await.unwrap();
+    assert_eq!(
+        market.pair_authority().await.pending_authority,
+        new_admin.pubkey()
+    );
+
+    // Only the pending authority can accept it
+    let instruction = instructions::accept_pair_authority(admin, pair_authority);
+    let result = market.as_admin(&[instruction]).await;
+    assert_nftamm_error(result, NftammError::InvalidPendingAuthority);
+
+    let instruction = instructions::accept_pair_authority(new_admin.pubkey(), pair_authority);
+    process(&mut market.context, &[instruction], &[&new_admin])
+        .await
+        .unwrap();
+    assert_eq!(
+        market.pair_authority().await.current_authority,
+        new_admin.pubkey()
+    );
+}
+
+#[tokio::test]
+async fn change_protocol_fee_and_fee_recipient() {
+    let mut market = setup_market().await;
+    let pair_authority = market.protocol.pair_authority;
+    let admin = market.protocol.admin.pubkey();
+
+    let instruction = instructions::change_protocol_fee(admin, pair_authority, 1, 250);
+    market.as_admin(&[instruction]).await.unwrap();
+    assert_eq!(
+        market.pair_authority().await.fees,
+        [PROTOCOL_FEE, 250, PROTOCOL_FEE]
+    );
+
+    let instruction = instructions::change_protocol_fee(admin, pair_authority, 3, 250);
+    let result = market.as_admin(&[instruction]).await;
+    assert_nftamm_error(result, NftammError::InvalidPairType);
+
+    let instruction = instructions::change_protocol_fee(admin, pair_authority, 0, 10_001);
+    let result = market.as_admin(&[instruction]).await;
+    assert_nftamm_error(result, NftammError::InvalidFees);
+
+    let instruction =
+        instructions::change_protocol_fee(market.trader.pubkey(), pair_authority, 0, 0);
+    let result = market.as_trader(&[instruction]).await;
+    assert_nftamm_error(result, NftammError::InvalidCurrentAuthority);
+
+    let new_fee_recipient = Keypair::new().pubkey();
+    let instruction = instructions::change_fee_recipient(admin, pair_authority, new_fee_recipient);
+    market.as_admin(&[instruction]).await.unwrap();
+    assert_eq!(
+        market.pair_authority().await.fee_recipient,
+        new_fee_recipient
+    );
+}
+
+#[tokio::test]
+async fn timelocked_pair_authority_queues_fee_changes() {
+    let mut market = setup_market().await;
+    let pair_authority = market.protocol.pair_authority;
+    let admin = market.protocol.admin.pubkey();
+    let timelock_slots = 100;
+
+    let instruction =
+        instructions::set_protocol_fee_timelock(admin, pair_authority, timelock_slots);
+    market.as_admin(&[instruction]).await.unwrap();
+
+    // Fee changes apply to every pair at once, so they have to wait out the timelock
+    let instruction = instructions::change_protocol_fee(admin, pair_authority, 1, 250);
+    let result = market.as_admin(&[instruction]).await;
+    assert_nftamm_error(result, NftammError::TimelockActive);
+
+    let new_fee_recipient = Keypair::new().pubkey();
+    let instruction = instructions::change_fee_recipient(admin, pair_authority, new_fee_recipient);
+    let result = market.as_admin(&[instruction]).await;
+    assert_nftamm_error(result, NftammError::TimelockActive);
+
+    let instruction = instructions::set_protocol_fee_timelock(admin, pair_authority, 0);
+    let result = market.as_admin(&[instruction]).await;
+    assert_nftamm_error(result, NftammError::TimelockActive);
+
+    let instruction = instructions::queue_protocol_fee_change(
+        admin,
+        pair_authority,
+        Some([0, 10_001, 0]),
+        None,
+        None,
+    );
+    let result = market.as_admin(&[instruction]).await;
+    assert_nftamm_error(result, NftammError::InvalidFees);
+
+    let instruction = instructions::queue_protocol_fee_change(
+        admin,
+        pair_authority,
+        Some([PROTOCOL_FEE, 250, PROTOCOL_FEE]),
+        Some(new_fee_recipient),
+        Some(0),
+    );
+    market.as_admin(&[instruction]).await.unwrap();
+
+    let instruction = instructions::apply_protocol_fee_change(admin, pair_authority);
+    let result = market.as_admin(&[instruction]).await;
+    assert_nftamm_error(result, NftammError::TimelockNotElapsed);
+
+    let slot = current_slot(&mut market.context).await;
+    market
+        .context
+        .warp_to_slot(slot + timelock_slots + 1)
+        .unwrap();
+
+    let instruction = instructions::apply_protocol_fee_change(admin, pair_authority);
+    market.as_admin(&[instruction]).await.unwrap();
+
+    let pair_authority_account = market.pair_authority().await;
+    assert_eq!(
+        pair_authority_account.fees,
+        [PROTOCOL_FEE, 250, PROTOCOL_FEE]
+    );
+    assert_eq!(pair_authority_account.fee_recipient, new_fee_recipient);
+    assert_eq!(pair_authority_account.timelock_slots, 0);
+    assert!(pair_authority_account.pending_fee_change.is_none());
+}
+
+#[tokio::test]
+async fn cancel_queued_protocol_fee_change() {
+    let mut market = setup_market().await;
+    let pair_authority = market.protocol.pair_authority;
+    let admin = market.protocol.admin.pubkey();
+
+    let instruction = instructions::cancel_protocol_fee_change(admin, pair_authority);
+    let result = market.as_admin(&[instruction]).await;
+    assert_nftamm_error(result, NftammError::NoPendingChange);
+
+    let instruction = instructions::set_protocol_fee_timelock(admin, pair_authority, 100);
+    market.as_admin(&[instruction]).await.unwrap();
+
+    let instruction =
+        instructions::queue_protocol_fee_change(admin, pair_authority, Some([0; 3]), None, None);
+    market.as_admin(&[instruction]).await.unwrap();
+    assert!(market.pair_authority().await.pending_fee_change.is_some());
+
+    // Only the current authority can cancel it
+    let instruction =
+        instructions::cancel_protocol_fee_change(market.trader.pubkey(), pair_authority);
+    let result = market.as_trader(&[instruction]).await;
+    assert_nftamm_error(result, NftammError::InvalidCurrentAuthority);
+
+    refresh_blockhash(&mut market.context).await;
+    let instruction = instructions::cancel_protocol_fee_change(admin, pair_authority);
+    market.as_admin(&[instruction]).await.unwrap();
+
+    let pair_authority_account = market.pair_authority().await;
+    assert!(pair_authority_account.pending_fee_change.is_none());
+    assert_eq!(pair_authority_account.fees, [PROTOCOL_FEE; 3]);
+}
+
+#[tokio::test]
+async fn withdraw_escrowed_protocol_fees() {
+    let mut market = setup_market().await;
+    let pair = market
+        .create_pair(false, linear_params(0, 1_000_000, 0))
+        .await
+        .unwrap();
+    market.fund_pair(&pair, 10_000_000).await.unwrap();
+
+    // A sale into the token pair escrows the protocol fee
+    let trader = market.trader.pubkey();
+    let nft = market.mint_nft(&trader).await;
+    let pair_account = market.pair(&pair).await;
+    let instruction = instructions::trade_token_pair(
+        trader,
+        pair,
+        &pair_account,
+        nft.mint,
+        0,
+        None,
+        vec![],
+        vec![],
+    );
+    market.as_trader(&[instruction]).await.unwrap();
+
+    let escrowed = market.protocol_fees().await;
+    assert_eq!(escrowed, 10_000);
+
+    let admin = market.protocol.admin.pubkey();
+    let pair_authority = market.protocol.pair_authority;
+    let fee_recipient = market.protocol.fee_recipient.pubkey();
+    let quote_token_mint = market.quote_token_mint;
+
+    let instruction = instructions::withdraw_protocol_fees(
+        admin,
+        pair_authority,
+        Keypair::new().pubkey(),
+        quote_token_mint,
+        escrowed,
+    );
+    let result = market.as_admin(&[instruction]).await;
+    assert_nftamm_error(result, NftammError::InvalidFeeRecipient);
+
+    let instruction = instructions::withdraw_protocol_fees(
+        admin,
+        pair_authority,
+        fee_recipient,
+        quote_token_mint,
+        escrowed + 1,
+    );
+    let result = market.as_admin(&[instruction]).await;
+    assert_nftamm_error(result, NftammError::InsufficientBalance);
+
+    let instruction = instructions::withdraw_protocol_fees(
+        admin,
+        pair_authority,
+        fee_recipient,
+        quote_token_mint,
+        escrowed,
+    );
+    market.as_admin(&[instruction]).await.unwrap();
+
+    assert_eq!(market.protocol_fees().await, 0);
+    assert_eq!(market.quote_balance(&fee_recipient).await, escrowed);
+}
+
+#[tokio::test]
+async fn withdraw_escrowed_protocol_sol_fees() {
+    let mut market = setup_market().await;
+    let pair = market
+        .create_pair(true, linear_params(0, LAMPORTS_PER_SOL, 0))
+        .await
+        .unwrap();
+    market.fund_pair(&pair, 5 * LAMPORTS_PER_SOL).await.unwrap();
+
+    let trader = market.trader.pubkey();
+    let nft = market.mint_nft(&trader).await;
+    let pair_account = market.pair(&pair).await;
+    let instruction = instructions::sell_nft_for_sol(
+        trader,
+        pair,
+        &pair_account,
+        nft.mint,
+        0,
+        None,
+        vec![],
+        vec![],
+    );
+    market.as_trader(&[instruction]).await.unwrap();
+
+    let pair_authority = market.protocol.pair_authority;
+    let vault = pda::find_protocol_sol_fee_vault(&pair_authority).0;
+    let rent = market.context.banks_client.get_rent().await.unwrap();
+    let escrowed = lamports(&mut market.context, &vault).await - rent.minimum_balance(0);
+    assert_eq!(escrowed, LAMPORTS_PER_SOL / 100);
+
+    let admin = market.protocol.admin.pubkey();
+    let fee_recipient = market.protocol.fee_recipient.pubkey();
+
+    // The vault keeps its rent exempt reserve
+    let instruction = instructions::withdraw_protocol_sol_fees(
+        admin,
+        pair_authority,
+        fee_recipient,
+        escrowed + 1,
+    );
+    let result = market.as_admin(&[instruction]).await;
+    assert_nftamm_error(result, NftammError::InsufficientBalance);
+
+    let instruction =
+        instructions::withdraw_protocol_sol_fees(admin, pair_authority, fee_recipient, escrowed);
+    market.as_admin(&[instruction]).await.unwrap();
+
+    assert_eq!(
+        lamports(&mut market.context, &fee_recipient).await,
+        escrowed
+    );
+}
+
+#[tokio::test]
+async fn first_pair_in_a_quote_token_creates_its_protocol_fee_vault() {
+    let mut market = setup_market().await;
+
+    // A quote token the admin never created a protocol fee vault for
+    let owner = market.owner.pubkey();
+    let quote_token_mint = create_quote_mint(&mut market.context, &[&owner], QUOTE_BALANCE).await;
+    let vault = pda::find_protocol_fee_vault(&market.protocol.pair_authority, &quote_token_mint).0;
+    assert!(get_account(&mut market.context, &vault).await.is_none());
+
+    // Later pairs reuse the vault
+    for _ in 0..2 {
+        create_pair(
+            &mut market.context,
+            &market.owner,
+            &market.protocol,
+            &market.collection,
+            Some(quote_token_mint),
+            linear_params(0, LAMPORTS_PER_SOL, 0),
+        )
+        .await
+        .unwrap();
+    }
+
+    assert_eq!(token_balance(&mut market.context, &vault).await, 0);
+}
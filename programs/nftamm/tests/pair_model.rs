@@ -0,0 +1,398 @@
+//! A model of a pair driven through random sequences of instructions.
+//!
+//! The model restates the checks and transfers of the quote token instructions by hand and
+//! prices trades with the program's own quote functions, so it runs on the host with
+//! `cargo test`. It is a check of the pricing code and of the accounting the handlers are meant
+//! to follow, not of the handlers themselves: a handler that drifts from the model goes
+//! unnoticed here and is covered by the program tests instead.
+//!
+//! Every instruction either applies in full or leaves the model untouched, like a failed
+//! transaction.
+
+mod strategies;
+
+use anchor_lang::prelude::Result;
+use nftamm::{
+    curve::Reserves,
+    error::ProgramError,
+    quote::{quote_buy, quote_sell, Quote},
+    state::{Pair, PairAuthority},
+};
+use proptest::{prelude::*, test_runner::TestCaseError};
+use strategies::*;
+
+/// Quote tokens initially held by the pair owner and by the trader
+const STARTING_BALANCE: u64 = 1 << 62;
+/// Nfts initially held by the pair owner and by the trader
+const STARTING_NFTS: u32 = 8;
+
+#[derive(Clone, Copy, Debug)]
+enum Instruction {
+    /// fund_token_pair
+    Fund(u64),
+    /// fund_nft_pair
+    Deposit,
+    /// withdraw_quote_token
+    Withdraw(u64),
+    /// withdraw_nft
+    WithdrawNft,
+    /// withdraw_fee
+    WithdrawFee(u64),
+    /// buy_nfts
+    Buy { n: u32, royalty_bps: u16 },
+    /// sell_nfts
+    Sell { n: u32, royalty_bps: u16 },
+}
+
+#[derive(Clone)]
+struct Model {
+    pair: Pair,
+    pair_authority: PairAuthority,
+    quote_token_vault: u64,
+    quote_fee_vault: u64,
+    protocol_fee_vault: u64,
+    royalties: u64,
+    owner: u64,
+    trader: u64,
+    pair_nfts: Vec<u32>,
+    owner_nfts: Vec<u32>,
+    trader_nfts: Vec<u32>,
+}
+
+fn debit(balance: &mut u64, amount: u64) -> Result<()> {
+    *balance = balance
+        .checked_sub(amount)
+        .ok_or(ProgramError::InsufficientBalance)?;
+
+    Ok(())
+}
+
+fn credit(balance: &mut u64, amount: u64) -> Result<()> {
+    *balance = balance
+        .checked_add(amount)
+        .ok_or(ProgramError::NumericalOverflow)?;
+
+    Ok(())
+}
+
+fn move_nfts(from: &mut Vec<u32>, to: &mut Vec<u32>, n: u32) -> Result<()> {
+    if from.len() < n as usize {
+        return Err(ProgramError::InsufficientBalance.into());
+    }
+
+    to.extend(from.drain(from.len() - n as usize..));
+
+    Ok(())
+}
+
+impl Model {
+    fn new(params: PairParams, fees: [u16; 3]) -> Self {
+        Model {
+            pair: pair(params),
+            pair_authority: pair_authority(fees),
+            quote_token_vault: 0,
+            quote_fee_vault: 0,
+            protocol_fee_vault: 0,
+            royalties: 0,
+            owner: STARTING_BALANCE,
+            trader: STARTING_BALANCE,
+            pair_nfts: vec![],
+            owner_nfts: (0..STARTING_NFTS).collect(),
+            trader_nfts: (STARTING_NFTS..2 * STARTING_NFTS).collect(),
+        }
+    }
+
+    fn reserves(&self) -> Reserves {
+        Reserves {
+            quote: self.quote_token_vault,
+            nfts: self.pair.nfts_held as u64,
+        }
+    }
+
+    /// Quote tokens held by the pair, in its vault and its fee vault
+    fn pair_quote(&self) -> u128 {
+        self.quote_token_vault as u128 + self.quote_fee_vault as u128
+    }
+
+    /// Whether a token pair can pay for one more nft. The seller pays the protocol fee out of
+    /// the price.
+    fn can_buy_nft(&self) -> Result<bool> {
+        Ok(self.quote_token_vault >= self.pair.spot_price)
+    }
+
+    fn require_pair_type(&self, pair_types: &[u8]) -> Result<()> {
+        if !pair_types.contains(&self.pair.pair_type) {
+            return Err(ProgramError::InvalidPairType.into());
+        }
+
+        Ok(())
+    }
+
+    fn require_active(&self) -> Result<()> {
+        if !self.pair.is_active {
+            return Err(ProgramError::PairNotActive.into());
+        }
+
+        Ok(())
+    }
+
+    /// Apply `instruction` the way its handler is expected to, returning the quote of a trade
+    fn apply(&mut self, instruction: Instruction) -> Result<Option<Quote>> {
+        match instruction {
+            Instruction::Fund(amount) => {
+                self.require_pair_type(&[0, 2])?;
+                debit(&mut self.owner, amount)?;
+                credit(&mut self.quote_token_vault, amount)?;
+
+                self.pair.is_active = self.pair.pair_type == 2 || self.can_buy_nft()?;
+            }
+            Instruction::Deposit => {
+                self.require_pair_type(&[1, 2])?;
+                move_nfts(&mut self.owner_nfts, &mut self.pair_nfts, 1)?;
+
+                self.pair.nfts_held += 1;
+                self.pair.is_active = true;
+            }
+            Instruction::Withdraw(amount) => {
+                debit(&mut self.quote_token_vault, amount)?;
+                credit(&mut self.owner, amount)?;
+
+                if self.pair.pair_type == 0 && !self.can_buy_nft()? {
+                    self.pair.is_active = false;
+                }
+            }
+            Instruction::WithdrawNft => {
+                move_nfts(&mut self.pair_nfts, &mut self.owner_nfts, 1)?;
+
+                self.pair.nfts_held -= 1;
+
+                if self.pair.pair_type == 1 && self.pair.nfts_held == 0 {
+                    self.pair.is_active = false;
+                }
+            }
+            Instruction::WithdrawFee(amount) => {
+                debit(&mut self.quote_fee_vault, amount)?;
+                credit(&mut self.owner, amount)?;
+            }
+            Instruction::Buy { n, royalty_bps } => {
+                self.require_pair_type(&[1, 2])?;
+                self.require_active()?;
+
+                let quote = quote_buy(
+                    &self.pair,
+                    &self.pair_authority,
+                    self.reserves(),
+                    royalty_bps,
+                    n as u64,
+                )?;
+
+                // The trader pays the curve price to the pair and every fee on top of it
+                debit(&mut self.trader, quote.total)?;
+                credit(&mut self.quote_token_vault, quote.price)?;
+                credit(&mut self.quote_fee_vault, quote.pair_fee)?;
+                credit(&mut self.protocol_fee_vault, quote.protocol_fee)?;
+                credit(&mut self.royalties, quote.royalty)?;
+                move_nfts(&mut self.pair_nfts, &mut self.trader_nfts, n)?;
+
+                self.pair.spot_price = quote.new_spot_price;
+                self.pair.nfts_held -= n;
+                self.pair.trade_count += n as u64;
+
+                if self.pair.pair_type == 1 && self.pair.nfts_held == 0 {
+                    self.pair.is_active = false;
+                }
+
+                return Ok(Some(quote));
+            }
+            Instruction::Sell { n, royalty_bps } => {
+                self.require_pair_type(&[0, 2])?;
+                self.require_active()?;
+
+                let quote = quote_sell(
+                    &self.pair,
+                    &self.pair_authority,
+                    self.reserves(),
+                    royalty_bps,
+                    n as u64,
+                )?;
+
+                // The vault pays the trader, the protocol fee and royalties out of the price. The
+                // pair fee is never paid out and stays in the vault.
+                debit(&mut self.quote_token_vault, quote.total)?;
+                debit(&mut self.quote_token_vault, quote.protocol_fee)?;
+                debit(&mut self.quote_token_vault, quote.royalty)?;
+                credit(&mut self.trader, quote.total)?;
+                credit(&mut self.protocol_fee_vault, quote.protocol_fee)?;
+                credit(&mut self.royalties, quote.royalty)?;
+                move_nfts(&mut self.trader_nfts, &mut self.pair_nfts, n)?;
+
+                self.pair.spot_price = quote.new_spot_price;
+                self.pair.nfts_held += n;
+                self.pair.trade_count += n as u64;
+
+                if self.pair.pair_type == 0 && !self.can_buy_nft()? {
+                    self.pair.is_active = false;
+                }
+
+                return Ok(Some(quote));
+            }
+        }
+
+        Ok(None)
+    }
+
+    fn check_invariants(&self) -> std::result::Result<(), TestCaseError> {
+        // Quote tokens only ever move between accounts
+        let held = self.owner as u128
+            + self.trader as u128
+            + self.quote_token_vault as u128
+            + self.quote_fee_vault as u128
+            + self.protocol_fee_vault as u128
+            + self.royalties as u128;
+        prop_assert_eq!(held, 2 * STARTING_BALANCE as u128);
+
+        // So do nfts, and the pair accounts for every nft in its vaults
+        prop_assert_eq!(self.pair.nfts_held as usize, self.pair_nfts.len());
+        prop_assert_eq!(
+            self.pair_nfts.len() + self.owner_nfts.len() + self.trader_nfts.len(),
+            2 * STARTING_NFTS as usize
+        );
+
+        // Trades are only possible while the pair has something to trade with
+        if self.pair.is_active {
+            match self.pair.pair_type {
+                0 => prop_assert!(self.can_buy_nft().unwrap_or(false)),
+                1 => prop_assert!(self.pair.nfts_held > 0),
+                _ => {}
+            }
+        }
+
+        Ok(())
+    }
+}
+
+fn instruction() -> impl Strategy<Value = Instruction> {
+    let amount = prop_oneof![
+        3 => 0..=100_000_000_000u64,
+        1 => any::<u64>(),
+    ];
+    let trade = || (1..=4u32, basis_points());
+
+    prop_oneof![
+        amount.clone().prop_map(Instruction::Fund),
+        Just(Instruction::Deposit),
+        amount.clone().prop_map(Instruction::Withdraw),
+        Just(Instruction::WithdrawNft),
+        amount.prop_map(Instruction::WithdrawFee),
+        trade().prop_map(|(n, royalty_bps)| Instruction::Buy { n, royalty_bps }),
+        trade().prop_map(|(n, royalty_bps)| Instruction::Sell { n, royalty_bps }),
+    ]
+}
+
+/// Pair parameters biased towards prices that leave room for many trades
+fn model_pair_params() -> impl Strategy<Value = PairParams> {
+    (pair_params(), 0..=1_000_000_000u64, 0..=100_000_000u64).prop_map(
+        |(mut params, spot_price, delta)| {
+            params.spot_price = spot_price;
+
+            if params.bonding_curve == 0 {
+                params.delta = delta;
+            }
+
+            params
+        },
+    )
+}
+
+proptest! {
+    #[test]
+    fn pair_model_invariants_hold(
+        params in prop_oneof![model_pair_params(), pair_params()],
+        fees in protocol_fees(),
+        instructions in prop::collection::vec(instruction(), 1..64),
+    ) {
+        let mut model = Model::new(params, fees);
+        model.check_invariants()?;
+
+        for instruction in instructions {
+            // A failed instruction leaves every account untouched
+            let mut next = model.clone();
+
+            if let Ok(quote) = next.apply(instruction) {
+                if let Some(quote) = quote {
+                    check_quote(&model, &next, instruction, quote)?;
+                }
+
+                model = next;
+            }
+
+            model.check_invariants()?;
+        }
+    }
+}
+
+/// Properties of a single executed trade
+fn check_quote(
+    before: &Model,
+    after: &Model,
+    instruction: Instruction,
+    quote: Quote,
+) -> std::result::Result<(), TestCaseError> {
+    let (spot_before, spot_after) = (before.pair.spot_price, after.pair.spot_price);
+
+    match instruction {
+        Instruction::Buy { n, royalty_bps } => {
+            // The fee split adds up to what the trader was charged
+            prop_assert_eq!(before.trader - after.trader, quote.total);
+            prop_assert_eq!(
+                quote.total as u128,
+                quote.price as u128
+                    + quote.pair_fee as u128
+                    + quote.protocol_fee as u128
+                    + quote.royalty as u128
+            );
+
+            // Buying never lowers the spot price, except to rebalance a constant product pool
+            if before.pair.bonding_curve != 3 {
+                prop_assert!(spot_after >= spot_before);
+            }
+
+            // The pair never ends a buy and sell cycle with less quote than it started with, so
+            // trading nfts back and forth cannot drain it
+            let mut cycle = after.clone();
+            if cycle.apply(Instruction::Sell { n, royalty_bps }).is_ok() {
+                prop_assert!(cycle.pair_quote() >= before.pair_quote());
+            }
+        }
+        Instruction::Sell { .. } => {
+            prop_assert_eq!(after.trader - before.trader, quote.total);
+            prop_assert_eq!(
+                quote.price as u128,
+                quote.total as u128
+                    + quote.pair_fee as u128
+                    + quote.royalty as u128
+                    + quote.protocol_fee as u128
+            );
+
+            // The spot price moves down, without ever wrapping around
+            if before.pair.bonding_curve != 3 {
+                prop_assert!(spot_after <= spot_before);
+            }
+
+            if before.pair.bonding_curve == 0 {
+                let decrease = before.pair.delta as u128
+                    * (after.pair.nfts_held - before.pair.nfts_held) as u128;
+                prop_assert_eq!(
+                    spot_after as u128,
+                    (spot_before as u128).saturating_sub(decrease)
+                );
+
+                // Only pairs clamping the spot price may sell past zero
+                prop_assert!(before.pair.clamp_spot_price || decrease <= spot_before as u128);
+            }
+        }
+        _ => {}
+    }
+
+    Ok(())
+}
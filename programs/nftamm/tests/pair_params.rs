@@ -0,0 +1,352 @@
+#![cfg(feature = "test-bpf")]
+
+mod common;
+
+use common::*;
+use nftamm_client::instructions;
+use solana_program_test::tokio;
+use solana_sdk::{pubkey::Pubkey, signature::Signer};
+
+const SPOT_PRICE: u64 = 1_000_000;
+const DELTA: u64 = 100_000;
+const TIMELOCK_SLOTS: u64 = 100;
+
+/// A token pair whose parameter changes have to wait for [TIMELOCK_SLOTS]
+async fn timelocked_pair(market: &mut Market, halt_trades_while_pending: bool) -> Pubkey {
+    let pair = market
+        .create_pair(false, linear_params(0, SPOT_PRICE, DELTA))
+        .await
+        .unwrap();
+
+    let owner = market.owner.pubkey();
+    let instruction =
+        instructions::set_pair_timelock(owner, pair, TIMELOCK_SLOTS, halt_trades_while_pending);
+    market.as_owner(&[instruction]).await.unwrap();
+
+    pair
+}
+
+#[tokio::test]
+async fn owner_changes_pair_params() {
+    let mut market = setup_market().await;
+    let pair = market
+        .create_pair(false, linear_params(2, SPOT_PRICE, DELTA))
+        .await
+        .unwrap();
+
+    let owner = market.owner.pubkey();
+    let instructions = [
+        instructions::change_spot_price(owner, pair, 2 * SPOT_PRICE),
+        instructions::change_delta(owner, pair, 2 * DELTA),
+        instructions::change_fee(owner, pair, 500),
+        instructions::change_royalty_policy(owner, pair, 3, 250),
+    ];
+    market.as_owner(&instructions).await.unwrap();
+
+    let pair_account = market.pair(&pair).await;
+    assert_eq!(pair_account.spot_price, 2 * SPOT_PRICE);
+    assert_eq!(pair_account.delta, 2 * DELTA);
+    assert_eq!(pair_account.fee, 500);
+    assert_eq!(pair_account.royalty_policy, 3);
+    assert_eq!(pair_account.royalty_bps, 250);
+
+    let instruction = instructions::change_bonding_curve(owner, pair, 1, 500, SPOT_PRICE);
+    market.as_owner(&[instruction]).await.unwrap();
+
+    let instruction = instructions::update_pair_params(
+        owner,
+        pair,
+        None,
+        None,
+        Some(100),
+        None,
+        Some(0),
+        Some(0),
+    );
+    market.as_owner(&[instruction]).await.unwrap();
+
+    let pair_account = market.pair(&pair).await;
+    assert_eq!(pair_account.bonding_curve, 1);
+    assert_eq!(pair_account.delta, 500);
+    assert_eq!(pair_account.spot_price, SPOT_PRICE);
+    assert_eq!(pair_account.fee, 100);
+    assert_eq!(pair_account.royalty_policy, 0);
+}
+
+#[tokio::test]
+async fn pair_param_changes_are_validated() {
+    let mut market = setup_market().await;
+    let pair = market
+        .create_pair(false, linear_params(0, SPOT_PRICE, DELTA))
+        .await
+        .unwrap();
+    let owner = market.owner.pubkey();
+
+    // Only trade pairs can charge a fee
+    let instruction = instructions::change_fee(owner, pair, 100);
+    let result = market.as_owner(&[instruction]).await;
+    assert_nftamm_error(result, NftammError::InvalidFee);
+
+    // Exponential deltas are expressed in basis points
+    let instruction = instructions::change_bonding_curve(owner, pair, 1, 10_001, SPOT_PRICE);
+    let result = market.as_owner(&[instruction]).await;
+    assert_nftamm_error(result, NftammError::InvalidDelta);
+
+    let instruction = instructions::change_bonding_curve(owner, pair, 4, DELTA, SPOT_PRICE);
+    let result = market.as_owner(&[instruction]).await;
+    assert_nftamm_error(result, NftammError::InvalidBondingCurve);
+
+    let instruction = instructions::change_royalty_policy(owner, pair, 1, 100);
+    let result = market.as_owner(&[instruction]).await;
+    assert_nftamm_error(result, NftammError::InvalidRoyaltyPolicy);
+
+    let trader = market.trader.pubkey();
+    let instruction = instructions::change_spot_price(trader, pair, 0);
+    let result = market.as_trader(&[instruction]).await;
+    assert_nftamm_error(result, NftammError::InvalidOwner);
+}
+
+#[tokio::test]
+async fn timelocked_pair_queues_changes() {
+    let mut market = setup_market().await;
+    let pair = timelocked_pair(&mut market, false).await;
+    let owner = market.owner.pubkey();
+
+    let instruction = instructions::change_spot_price(owner, pair, 2 * SPOT_PRICE);
+    let result = market.as_owner(&[instruction]).await;
+    assert_nftamm_error(result, NftammError::TimelockActive);
+
+    // So do the royalty policy and the mint allowlist, which decide what a trade pays and
+    // which nfts it accepts
+    let instruction = instructions::change_royalty_policy(owner, pair, 2, 500);
+    let result = market.as_owner(&[instruction]).await;
+    assert_nftamm_error(result, NftammError::TimelockActive);
+
+    let instruction = instructions::change_mint_allowlist_root(owner, pair, Some([1; 32]));
+    let result = market.as_owner(&[instruction]).await;
+    assert_nftamm_error(result, NftammError::TimelockActive);
+
+    // Lowering the timelock has to be queued as well
+    let instruction = instructions::set_pair_timelock(owner, pair, 0, false);
+    let result = market.as_owner(&[instruction]).await;
+    assert_nftamm_error(result, NftammError::TimelockActive);
+
+    let instruction = instructions::apply_pair_change(owner, pair);
+    let result = market.as_owner(&[instruction]).await;
+    assert_nftamm_error(result, NftammError::NoPendingChange);
+
+    let instruction = instructions::queue_pair_change(
+        owner,
+        pair,
+        None,
+        None,
+        None,
+        Some(2 * SPOT_PRICE),
+        None,
+        None,
+        None,
+        Some(0),
+    );
+    market.as_owner(&[instruction]).await.unwrap();
+
+    refresh_blockhash(&mut market.context).await;
+    let instruction = instructions::apply_pair_change(owner, pair);
+    let result = market.as_owner(&[instruction]).await;
+    assert_nftamm_error(result, NftammError::TimelockNotElapsed);
+
+    let slot = current_slot(&mut market.context).await;
+    market
+        .context
+        .warp_to_slot(slot + TIMELOCK_SLOTS + 1)
+        .unwrap();
+
+    let instruction = instructions::apply_pair_change(owner, pair);
+    market.as_owner(&[instruction]).await.unwrap();
+
+    let pair_account = market.pair(&pair).await;
+    assert_eq!(pair_account.spot_price, 2 * SPOT_PRICE);
+    assert_eq!(pair_account.timelock_slots, 0);
+    assert!(pair_account.pending_change.is_none());
+
+    // Without a timelock changes apply instantly again
+    let instruction = instructions::change_spot_price(owner, pair, SPOT_PRICE);
+    market.as_owner(&[instruction]).await.unwrap();
+}
+
+#[tokio::test]
+async fn timelocked_pair_queues_curve_and_royalty_changes() {
+    let mut market = setup_market().await;
+    let pair = timelocked_pair(&mut market, false).await;
+    let owner = market.owner.pubkey();
+
+    // Queued changes go through the same checks as instant ones
+    let instruction = instructions::queue_pair_change(
+        owner,
+        pair,
+        Some(1),
+        Some(10_001),
+        None,
+        None,
+        None,
+        None,
+        None,
+        None,
+    );
+    let result = market.as_owner(&[instruction]).await;
+    assert_nftamm_error(result, NftammError::InvalidDelta);
+
+    let instruction = instructions::queue_pair_change(
+        owner,
+        pair,
+        None,
+        None,
+        None,
+        None,
+        Some(1),
+        Some(100),
+        None,
+        None,
+    );
+    let result = market.as_owner(&[instruction]).await;
+    assert_nftamm_error(result, NftammError::InvalidRoyaltyPolicy);
+
+    let instruction = instructions::queue_pair_change(
+        owner,
+        pair,
+        Some(1),
+        Some(500),
+        None,
+        Some(2 * SPOT_PRICE),
+        Some(3),
+        Some(250),
+        Some(Some([1; 32])),
+        None,
+    );
+    market.as_owner(&[instruction]).await.unwrap();
+
+    let slot = current_slot(&mut market.context).await;
+    market
+        .context
+        .warp_to_slot(slot + TIMELOCK_SLOTS + 1)
+        .unwrap();
+
+    let instruction = instructions::apply_pair_change(owner, pair);
+    market.as_owner(&[instruction]).await.unwrap();
+
+    let pair_account = market.pair(&pair).await;
+    assert_eq!(pair_account.bonding_curve, 1);
+    assert_eq!(pair_account.delta, 500);
+    assert_eq!(pair_account.spot_price, 2 * SPOT_PRICE);
+    assert_eq!(pair_account.royalty_policy, 3);
+    assert_eq!(pair_account.royalty_bps, 250);
+    assert_eq!(pair_account.mint_allowlist_root, Some([1; 32]));
+    assert_eq!(pair_account.timelock_slots, TIMELOCK_SLOTS);
+
+    // Clearing the allowlist is queued as well
+    let instruction = instructions::queue_pair_change(
+        owner,
+        pair,
+        None,
+        None,
+        None,
+        None,
+        None,
+        None,
+        Some(None),
+        None,
+    );
+    market.as_owner(&[instruction]).await.unwrap();
+
+    let slot = current_slot(&mut market.context).await;
+    market
+        .context
+        .warp_to_slot(slot + TIMELOCK_SLOTS + 1)
+        .unwrap();
+
+    refresh_blockhash(&mut market.context).await;
+    let instruction = instructions::apply_pair_change(owner, pair);
+    market.as_owner(&[instruction]).await.unwrap();
+
+    let pair_account = market.pair(&pair).await;
+    assert_eq!(pair_account.mint_allowlist_root, None);
+    assert_eq!(pair_account.royalty_policy, 3);
+}
+
+#[tokio::test]
+async fn cancel_queued_pair_change() {
+    let mut market = setup_market().await;
+    let pair = timelocked_pair(&mut market, false).await;
+    let owner = market.owner.pubkey();
+
+    let instruction = instructions::cancel_pair_change(owner, pair);
+    let result = market.as_owner(&[instruction]).await;
+    assert_nftamm_error(result, NftammError::NoPendingChange);
+
+    let instruction = instructions::queue_pair_change(
+        owner,
+        pair,
+        None,
+        Some(2 * DELTA),
+        None,
+        None,
+        None,
+        None,
+        None,
+        None,
+    );
+    market.as_owner(&[instruction]).await.unwrap();
+    assert!(market.pair(&pair).await.pending_change.is_some());
+
+    refresh_blockhash(&mut market.context).await;
+    let instruction = instructions::cancel_pair_change(owner, pair);
+    market.as_owner(&[instruction]).await.unwrap();
+
+    let pair_account = market.pair(&pair).await;
+    assert!(pair_account.pending_change.is_none());
+    assert_eq!(pair_account.delta, DELTA);
+}
+
+#[tokio::test]
+async fn queued_change_can_halt_trades() {
+    let mut market = setup_market().await;
+    let pair = timelocked_pair(&mut market, true).await;
+    market.fund_pair(&pair, 10 * SPOT_PRICE).await.unwrap();
+
+    let owner = market.owner.pubkey();
+    let instruction = instructions::queue_pair_change(
+        owner,
+        pair,
+        None,
+        None,
+        None,
+        Some(2 * SPOT_PRICE),
+        None,
+        None,
+        None,
+        None,
+    );
+    market.as_owner(&[instruction]).await.unwrap();
+
+    let trader = market.trader.pubkey();
+    let nft = market.mint_nft(&trader).await;
+    let pair_account = market.pair(&pair).await;
+    let instruction = instructions::trade_token_pair(
+        trader,
+        pair,
+        &pair_account,
+        nft.mint,
+        0,
+        None,
+        vec![],
+        vec![],
+    );
+    let result = market.as_trader(std::slice::from_ref(&instruction)).await;
+    assert_nftamm_error(result, NftammError::PendingPairChange);
+
+    let cancel = instructions::cancel_pair_change(owner, pair);
+    market.as_owner(&[cancel]).await.unwrap();
+
+    refresh_blockhash(&mut market.context).await;
+    market.as_trader(&[instruction]).await.unwrap();
+    assert_eq!(market.pair(&pair).await.nfts_held, 1);
+}
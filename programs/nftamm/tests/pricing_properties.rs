@@ -0,0 +1,387 @@
+//! Properties of the bonding curves and trade quotes, run on the host with `cargo test`.
+
+mod strategies;
+
+use anchor_lang::error::Error;
+use nftamm::{
+    curve::{bonding_curve, mul_div, Reserves, Rounding, BASIS_POINTS},
+    error::ProgramError,
+    quote::{quote_buy, quote_sell, Quote, MAX_QUOTE_NFTS},
+    state::Pair,
+};
+use proptest::prelude::*;
+use strategies::*;
+
+fn reserves() -> impl Strategy<Value = Reserves> {
+    (amount(), 0..=1_000u64).prop_map(|(quote, nfts)| Reserves { quote, nfts })
+}
+
+/// A pair holding enough nfts for every buy the properties quote
+fn stocked_pair(params: PairParams) -> Pair {
+    Pair {
+        nfts_held: MAX_QUOTE_NFTS as u32,
+        ..pair(params)
+    }
+}
+
+fn is_error(error: &Error, expected: ProgramError) -> bool {
+    match error {
+        Error::AnchorError(error) => error.error_code_number == u32::from(expected),
+        Error::ProgramError(_) => false,
+    }
+}
+
+fn is_overflow(error: &Error) -> bool {
+    is_error(error, ProgramError::NumericalOverflow)
+}
+
+/// Fee breakdown of a single trade at `price`, as charged by the quote functions
+fn fee(price: u64, bps: u64) -> u64 {
+    (price as u128 * bps as u128 / BASIS_POINTS as u128) as u64
+}
+
+proptest! {
+    #[test]
+    fn mul_div_matches_wide_arithmetic(
+        value in any::<u64>(),
+        numerator in any::<u64>(),
+        denominator in any::<u64>(),
+    ) {
+        let down = mul_div(value, numerator, denominator, Rounding::Down);
+        let up = mul_div(value, numerator, denominator, Rounding::Up);
+
+        if denominator == 0 {
+            prop_assert!(down.is_err());
+            prop_assert!(up.is_err());
+            return Ok(());
+        }
+
+        let product = value as u128 * numerator as u128;
+        let expected_down = product / denominator as u128;
+        let expected_up = expected_down + (expected_down * denominator as u128 != product) as u128;
+
+        match down {
+            Ok(down) => prop_assert_eq!(down as u128, expected_down),
+            Err(error) => {
+                prop_assert!(is_overflow(&error));
+                prop_assert!(expected_down > u64::MAX as u128);
+            }
+        }
+
+        match up {
+            Ok(up) => prop_assert_eq!(up as u128, expected_up),
+            Err(error) => {
+                prop_assert!(is_overflow(&error));
+                prop_assert!(expected_up > u64::MAX as u128);
+            }
+        }
+    }
+
+    #[test]
+    fn curves_never_panic(
+        curve in 0..=4u8,
+        delta in any::<u64>(),
+        clamp_spot_price in any::<bool>(),
+        spot_price in any::<u64>(),
+        reserves in reserves(),
+        vault_outflow in any::<u64>(),
+    ) {
+        if let Ok(mut curve) = bonding_curve(curve, delta, clamp_spot_price, reserves) {
+            let _ = curve.buy_price(spot_price);
+            let _ = curve.sell_price(spot_price);
+            let _ = curve.spot_price_after_buy(spot_price);
+            let _ = curve.spot_price_after_sell(spot_price, vault_outflow);
+        }
+    }
+
+    #[test]
+    fn spot_price_round_trips(
+        (bonding_curve_id, delta) in (0..=2u8).prop_flat_map(curve),
+        clamp_spot_price in any::<bool>(),
+        spot_price in amount(),
+    ) {
+        let mut curve =
+            bonding_curve(bonding_curve_id, delta, clamp_spot_price, Reserves::default()).unwrap();
+
+        // Rounding up after a buy and down after a sell lands back on the same spot price
+        if let Ok(after_buy) = curve.spot_price_after_buy(spot_price) {
+            prop_assert!(after_buy >= spot_price);
+            let price = curve.sell_price(after_buy).unwrap();
+            prop_assert_eq!(curve.spot_price_after_sell(after_buy, price).unwrap(), spot_price);
+        }
+    }
+
+    #[test]
+    fn linear_spot_price_never_underflows(
+        delta in amount(),
+        spot_price in amount(),
+        clamp_spot_price in any::<bool>(),
+        n in 0..=64u64,
+    ) {
+        let params = PairParams {
+            pair_type: 0,
+            bonding_curve: 0,
+            delta,
+            fee: 0,
+            spot_price,
+            clamp_spot_price,
+            royalty_policy: 0,
+            royalty_bps: 0,
+        };
+        let quote = quote_sell(&pair(params), &pair_authority([0; 3]), Reserves::default(), 0, n);
+
+        let decrease = delta as u128 * n as u128;
+        let underflows = decrease > spot_price as u128;
+
+        // Selling past a zero spot price either stops at zero or is rejected, while summed
+        // prices may overflow too
+        match quote {
+            Ok(quote) => {
+                prop_assert!(clamp_spot_price || !underflows);
+                prop_assert_eq!(
+                    quote.new_spot_price as u128,
+                    (spot_price as u128).saturating_sub(decrease)
+                );
+            }
+            Err(error) => prop_assert!(
+                is_overflow(&error)
+                    || (underflows
+                        && !clamp_spot_price
+                        && is_error(&error, ProgramError::SpotPriceUnderflow))
+            ),
+        }
+    }
+
+    #[test]
+    fn buy_quote_splits_sum_to_total(
+        params in pair_params(),
+        fees in protocol_fees(),
+        reserves in reserves(),
+        royalty_bps in basis_points(),
+        n in 0..=32u64,
+    ) {
+        let pair = stocked_pair(params);
+        let pair_authority = pair_authority(fees);
+
+        if let Ok(quote) = quote_buy(&pair, &pair_authority, reserves, royalty_bps, n) {
+            let charged = quote.price as u128
+                + quote.protocol_fee as u128
+                + quote.pair_fee as u128
+                + quote.royalty as u128;
+
+            prop_assert_eq!(quote.total as u128, charged);
+            prop_assert!(quote.protocol_fee <= fee(quote.price, fees[params.pair_type as usize] as u64));
+            prop_assert!(quote.royalty <= fee(quote.price, royalty_bps as u64));
+
+            // Only trade pairs are allowed to charge a fee
+            if params.pair_type != 2 {
+                prop_assert_eq!(quote.pair_fee, 0);
+            }
+        }
+    }
+
+    #[test]
+    fn selling_back_never_pays_more_than_buying(
+        params in pair_params(),
+        fees in protocol_fees(),
+        reserves in reserves(),
+        royalty_bps in basis_points(),
+        n in 1..=16u64,
+    ) {
+        let mut pair = stocked_pair(params);
+        let pair_authority = pair_authority(fees);
+
+        // A trader buying nfts and selling them straight back never gets more than they paid,
+        // whatever the curve and fees
+        if let Ok(buy) = quote_buy(&pair, &pair_authority, reserves, royalty_bps, n) {
+            pair.spot_price = buy.new_spot_price;
+            pair.nfts_held -= n as u32;
+
+            // The pair's vault keeps the price of the nfts it sold
+            if let Some(quote) = reserves.quote.checked_add(buy.price) {
+                let reserves = Reserves {
+                    quote,
+                    nfts: reserves.nfts.saturating_sub(n),
+                };
+
+                if let Ok(sell) = quote_sell(&pair, &pair_authority, reserves, royalty_bps, n) {
+                    prop_assert!(sell.total <= buy.total);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn sell_quote_splits_sum_to_price(
+        params in pair_params(),
+        fees in protocol_fees(),
+        reserves in reserves(),
+        royalty_bps in basis_points(),
+        n in 0..=32u64,
+    ) {
+        let pair = pair(params);
+        let pair_authority = pair_authority(fees);
+
+        if let Ok(quote) = quote_sell(&pair, &pair_authority, reserves, royalty_bps, n) {
+            let paid = quote.total as u128
+                + quote.pair_fee as u128
+                + quote.royalty as u128
+                + quote.protocol_fee as u128;
+
+            prop_assert_eq!(quote.price as u128, paid);
+            prop_assert!(quote.protocol_fee <= fee(quote.price, fees[params.pair_type as usize] as u64));
+
+            if params.pair_type != 2 {
+                prop_assert_eq!(quote.pair_fee, 0);
+            }
+        }
+    }
+
+    #[test]
+    fn sequential_quotes_compose(
+        params in (0..=2u8).prop_flat_map(pair_params_of).prop_filter(
+            "constant product quotes depend on the vault",
+            |params| params.bonding_curve != 3,
+        ),
+        fees in protocol_fees(),
+        royalty_bps in basis_points(),
+        first in 0..=16u64,
+        second in 0..=16u64,
+        buy in any::<bool>(),
+    ) {
+        let mut pair = stocked_pair(params);
+        let pair_authority = pair_authority(fees);
+        let reserves = Reserves::default();
+
+        let quote = |pair: &nftamm::state::Pair, n: u64| {
+            if buy {
+                quote_buy(pair, &pair_authority, reserves, royalty_bps, n)
+            } else {
+                quote_sell(pair, &pair_authority, reserves, royalty_bps, n)
+            }
+        };
+
+        // Quoting in two steps is the same as quoting every trade at once
+        if let Ok(whole) = quote(&pair, first + second) {
+            let mut parts: Quote = quote(&pair, first).unwrap();
+            pair.spot_price = parts.new_spot_price;
+            parts.merge(&quote(&pair, second).unwrap()).unwrap();
+
+            prop_assert_eq!(parts, whole);
+        }
+    }
+
+    #[test]
+    fn xyk_sell_quote_matches_single_sells(
+        params in pair_params_of(2).prop_map(|params| PairParams {
+            bonding_curve: 3,
+            delta: 0,
+            ..params
+        }),
+        fees in protocol_fees(),
+        reserves in reserves(),
+        royalty_bps in basis_points(),
+        n in 0..=16u64,
+    ) {
+        let mut pair = pair(params);
+        let pair_authority = pair_authority(fees);
+
+        // sell_nfts quotes one nft at a time and advances the reserves by what left the vault,
+        // which has to price the same as quoting every nft at once
+        if let Ok(whole) = quote_sell(&pair, &pair_authority, reserves, royalty_bps, n) {
+            let mut parts = Quote {
+                new_spot_price: pair.spot_price,
+                ..Quote::default()
+            };
+            let mut after = reserves;
+
+            for _ in 0..n {
+                let quote = quote_sell(&pair, &pair_authority, after, royalty_bps, 1).unwrap();
+                after = after.after_sell(quote.sell_vault_outflow().unwrap()).unwrap();
+                pair.spot_price = quote.new_spot_price;
+                parts.merge(&quote).unwrap();
+            }
+
+            prop_assert_eq!(parts, whole);
+
+            // And the reported spot price is the ratio of what the pair actually holds
+            prop_assert_eq!(
+                after.quote,
+                reserves.quote - whole.sell_vault_outflow().unwrap()
+            );
+            if n > 0 {
+                prop_assert_eq!(whole.new_spot_price, after.quote / after.nfts);
+            }
+        }
+    }
+
+    #[test]
+    fn prices_move_against_the_trader(
+        params in (0..=2u8).prop_flat_map(pair_params_of),
+        fees in protocol_fees(),
+        reserves in reserves(),
+        n in 1..=16u64,
+    ) {
+        let pair = stocked_pair(params);
+        let pair_authority = pair_authority(fees);
+
+        // Every further nft bought costs at least as much, every further nft sold pays at most
+        // as much
+        let mut last_price = None;
+        for i in 1..=n {
+            let quote = match quote_buy(&pair, &pair_authority, reserves, 0, i) {
+                Ok(quote) => quote,
+                Err(_) => break,
+            };
+            let previous = quote_buy(&pair, &pair_authority, reserves, 0, i - 1).unwrap();
+            let price = quote.price - previous.price;
+
+            if let Some(last_price) = last_price {
+                prop_assert!(price >= last_price);
+            }
+            last_price = Some(price);
+        }
+
+        let mut last_price = None;
+        for i in 1..=n {
+            let quote = match quote_sell(&pair, &pair_authority, reserves, 0, i) {
+                Ok(quote) => quote,
+                Err(_) => break,
+            };
+            let previous = quote_sell(&pair, &pair_authority, reserves, 0, i - 1).unwrap();
+            let price = quote.price - previous.price;
+
+            if let Some(last_price) = last_price {
+                prop_assert!(price <= last_price);
+            }
+            last_price = Some(price);
+        }
+    }
+
+    #[test]
+    fn quotes_reject_out_of_range_counts(
+        params in pair_params(),
+        nfts_held in 0..=MAX_QUOTE_NFTS as u32,
+        excess in 1..=u64::MAX - MAX_QUOTE_NFTS,
+    ) {
+        let pair = Pair {
+            nfts_held,
+            ..pair(params)
+        };
+        let pair_authority = pair_authority([0; 3]);
+        let reserves = Reserves::default();
+
+        // A pair never sells more nfts than it holds
+        let error = quote_buy(&pair, &pair_authority, reserves, 0, nfts_held as u64 + excess)
+            .unwrap_err();
+        prop_assert!(
+            is_error(&error, ProgramError::InsufficientReserves)
+                || is_error(&error, ProgramError::QuoteTooLarge)
+        );
+
+        // And no quote walks the curve further than MAX_QUOTE_NFTS
+        let error = quote_sell(&pair, &pair_authority, reserves, 0, MAX_QUOTE_NFTS + excess)
+            .unwrap_err();
+        prop_assert!(is_error(&error, ProgramError::QuoteTooLarge));
+    }
+}
@@ -0,0 +1,272 @@
+#![cfg(feature = "test-bpf")]
+
+mod common;
+
+use common::*;
+use nftamm_client::{instructions, pda};
+use solana_program_test::tokio;
+use solana_sdk::{pubkey::Pubkey, signature::Signer};
+
+const SPOT_PRICE: u64 = LAMPORTS_PER_SOL;
+const DELTA: u64 = LAMPORTS_PER_SOL / 10;
+const FUNDING: u64 = 5 * LAMPORTS_PER_SOL;
+
+/// Lamports held by a native sol vault above its rent exempt reserve
+async fn vault_balance(market: &mut Market, vault: &Pubkey) -> u64 {
+    let rent = market.context.banks_client.get_rent().await.unwrap();
+
+    lamports(&mut market.context, vault).await - rent.minimum_balance(0)
+}
+
+async fn protocol_sol_fees(market: &mut Market) -> u64 {
+    let vault = pda::find_protocol_sol_fee_vault(&market.protocol.pair_authority).0;
+
+    vault_balance(market, &vault).await
+}
+
+#[tokio::test]
+async fn sell_nft_into_sol_pair() {
+    let mut market = setup_market().await;
+    let pair = market
+        .create_pair(true, linear_params(0, SPOT_PRICE, DELTA))
+        .await
+        .unwrap();
+    market.fund_pair(&pair, FUNDING).await.unwrap();
+    assert!(market.pair(&pair).await.is_active);
+
+    let trader = market.trader.pubkey();
+    let nft = market.mint_nft(&trader).await;
+    let pair_account = market.pair(&pair).await;
+    let protocol_fee = SPOT_PRICE * PROTOCOL_FEE as u64 / 10_000;
+
+    let instruction = instructions::sell_nft_for_sol(
+        trader,
+        pair,
+        &pair_account,
+        nft.mint,
+        SPOT_PRICE - protocol_fee + 1,
+        None,
+        vec![],
+        vec![],
+    );
+    let result = market.as_trader(&[instruction]).await;
+    assert_nftamm_error(result, NftammError::SlippageExceeded);
+
+    let instruction = instructions::sell_nft_for_sol(
+        trader,
+        pair,
+        &pair_account,
+        nft.mint,
+        SPOT_PRICE - protocol_fee,
+        Some(0),
+        vec![],
+        vec![],
+    );
+    market.as_trader(&[instruction]).await.unwrap();
+
+    // The seller pays the protocol fee out of the price
+    assert_eq!(
+        vault_balance(&mut market, &pair_account.quote_token_vault).await,
+        FUNDING - SPOT_PRICE
+    );
+    assert_eq!(protocol_sol_fees(&mut market).await, protocol_fee);
+
+    let pair_account = market.pair(&pair).await;
+    assert_eq!(pair_account.spot_price, SPOT_PRICE - DELTA);
+    assert_eq!(pair_account.nfts_held, 1);
+}
+
+#[tokio::test]
+async fn buy_nft_from_sol_pair() {
+    let mut market = setup_market().await;
+    let pair = market
+        .create_pair(true, linear_params(1, SPOT_PRICE, DELTA))
+        .await
+        .unwrap();
+    let nft = market.deposit_nft(&pair).await.unwrap();
+
+    let trader = market.trader.pubkey();
+    let owner = market.owner.pubkey();
+    let pair_account = market.pair(&pair).await;
+
+    // A buy is charged the spot price it moves the pair to
+    let price = SPOT_PRICE + DELTA;
+    let protocol_fee = price * PROTOCOL_FEE as u64 / 10_000;
+
+    let instruction = instructions::buy_nft_with_sol(
+        trader,
+        pair,
+        &pair_account,
+        nft.mint,
+        owner,
+        price,
+        None,
+        vec![],
+    );
+    let result = market.as_trader(&[instruction]).await;
+    assert_nftamm_error(result, NftammError::SlippageExceeded);
+
+    let instruction = instructions::buy_nft_with_sol(
+        trader,
+        pair,
+        &pair_account,
+        nft.mint,
+        owner,
+        price + protocol_fee,
+        Some(0),
+        vec![],
+    );
+    market.as_trader(&[instruction]).await.unwrap();
+
+    assert_eq!(
+        vault_balance(&mut market, &pair_account.quote_token_vault).await,
+        price
+    );
+    assert_eq!(protocol_sol_fees(&mut market).await, protocol_fee);
+
+    let trader_nft_account = pda::associated_token_address(&trader, &nft.mint);
+    assert_eq!(
+        token_balance(&mut market.context, &trader_nft_account).await,
+        1
+    );
+
+    let pair_account = market.pair(&pair).await;
+    assert_eq!(pair_account.spot_price, SPOT_PRICE + DELTA);
+    assert_eq!(pair_account.nfts_held, 0);
+    assert!(!pair_account.is_active);
+}
+
+#[tokio::test]
+async fn withdraw_sol_keeps_rent_reserve() {
+    let mut market = setup_market().await;
+    let pair = market
+        .create_pair(true, linear_params(0, SPOT_PRICE, DELTA))
+        .await
+        .unwrap();
+    market.fund_pair(&pair, FUNDING).await.unwrap();
+
+    let owner = market.owner.pubkey();
+    let trader = market.trader.pubkey();
+    let pair_account = market.pair(&pair).await;
+
+    let instruction = instructions::withdraw_sol(owner, pair, &pair_account, FUNDING + 1);
+    let result = market.as_owner(&[instruction]).await;
+    assert_nftamm_error(result, NftammError::InsufficientBalance);
+
+    let instruction = instructions::withdraw_sol(trader, pair, &pair_account, FUNDING);
+    let result = market.as_trader(&[instruction]).await;
+    assert_nftamm_error(result, NftammError::InvalidOwner);
+
+    let owner_lamports = lamports(&mut market.context, &owner).await;
+    let instruction = instructions::withdraw_sol(owner, pair, &pair_account, FUNDING);
+    market.as_owner(&[instruction]).await.unwrap();
+
+    assert_eq!(
+        lamports(&mut market.context, &owner).await,
+        owner_lamports + FUNDING
+    );
+    assert_eq!(
+        vault_balance(&mut market, &pair_account.quote_token_vault).await,
+        0
+    );
+
+    // An empty token pair can no longer buy nfts
+    assert!(!market.pair(&pair).await.is_active);
+}
+
+#[tokio::test]
+async fn withdraw_sol_trade_pair_fees() {
+    let mut market = setup_market().await;
+    let pair = market
+        .create_pair(true, linear_params(2, SPOT_PRICE, DELTA))
+        .await
+        .unwrap();
+    let nft = market.deposit_nft(&pair).await.unwrap();
+
+    let trader = market.trader.pubkey();
+    let owner = market.owner.pubkey();
+    let pair_account = market.pair(&pair).await;
+    let instruction = instructions::buy_nft_with_sol(
+        trader,
+        pair,
+        &pair_account,
+        nft.mint,
+        owner,
+        u64::MAX,
+        None,
+        vec![],
+    );
+    market.as_trader(&[instruction]).await.unwrap();
+
+    let pair_fee = (SPOT_PRICE + DELTA) * 200 / 10_000;
+    let fee_vault = pda::find_sol_fee_vault(&pair).0;
+    assert_eq!(vault_balance(&mut market, &fee_vault).await, pair_fee);
+
+    let instruction = instructions::withdraw_sol_fee(owner, pair, pair_fee + 1);
+    let result = market.as_owner(&[instruction]).await;
+    assert_nftamm_error(result, NftammError::InsufficientBalance);
+
+    let owner_lamports = lamports(&mut market.context, &owner).await;
+    let instruction = instructions::withdraw_sol_fee(owner, pair, pair_fee);
+    market.as_owner(&[instruction]).await.unwrap();
+
+    assert_eq!(
+        lamports(&mut market.context, &owner).await,
+        owner_lamports + pair_fee
+    );
+    assert_eq!(vault_balance(&mut market, &fee_vault).await, 0);
+}
+
+#[tokio::test]
+async fn close_sol_pair_returns_vault_lamports() {
+    let mut market = setup_market().await;
+    let pair = market
+        .create_pair(true, linear_params(2, SPOT_PRICE, DELTA))
+        .await
+        .unwrap();
+    market.fund_pair(&pair, FUNDING).await.unwrap();
+    let nft = market.deposit_nft(&pair).await.unwrap();
+
+    let owner = market.owner.pubkey();
+    let instruction = instructions::close_sol_pair(owner, pair);
+    let result = market.as_owner(&[instruction]).await;
+    assert_nftamm_error(result, NftammError::StillHoldsNfts);
+
+    let pair_account = market.pair(&pair).await;
+    let instruction = instructions::withdraw_nft(owner, pair, &pair_account, nft.mint, owner);
+    market.as_owner(&[instruction]).await.unwrap();
+
+    let vault = pda::find_sol_vault(&pair).0;
+    let fee_vault = pda::find_sol_fee_vault(&pair).0;
+    let returned = lamports(&mut market.context, &vault).await
+        + lamports(&mut market.context, &fee_vault).await
+        + lamports(&mut market.context, &pair).await;
+    let owner_lamports = lamports(&mut market.context, &owner).await;
+
+    refresh_blockhash(&mut market.context).await;
+    let instruction = instructions::close_sol_pair(owner, pair);
+    market.as_owner(&[instruction]).await.unwrap();
+
+    assert_eq!(
+        lamports(&mut market.context, &owner).await,
+        owner_lamports + returned
+    );
+    assert!(get_account(&mut market.context, &pair).await.is_none());
+    assert!(get_account(&mut market.context, &vault).await.is_none());
+}
+
+#[tokio::test]
+async fn sol_instructions_require_native_pair() {
+    let mut market = setup_market().await;
+    let pair = market
+        .create_pair(false, linear_params(0, SPOT_PRICE, DELTA))
+        .await
+        .unwrap();
+
+    let owner = market.owner.pubkey();
+    let pair_account = market.pair(&pair).await;
+    let instruction = instructions::withdraw_sol(owner, pair, &pair_account, 0);
+    let result = market.as_owner(&[instruction]).await;
+
+    assert_nftamm_error(result, NftammError::NotNativePair);
+}
@@ -0,0 +1,148 @@
+//! Proptest strategies producing pairs that pass the program's parameter validation.
+//!
+//! Shared by the property suites, which exercise the pricing code on the host and do not need
+//! a BPF runtime.
+
+#![allow(dead_code)]
+
+use anchor_lang::prelude::Pubkey;
+use nftamm::{
+    curve::BASIS_POINTS,
+    state::{Pair, PairAuthority},
+};
+use proptest::prelude::*;
+
+/// Parameters accepted by initialize_pair
+#[derive(Clone, Copy, Debug)]
+pub struct PairParams {
+    pub pair_type: u8,
+    pub bonding_curve: u8,
+    pub delta: u64,
+    pub fee: u16,
+    pub spot_price: u64,
+    pub clamp_spot_price: bool,
+    pub royalty_policy: u8,
+    pub royalty_bps: u16,
+}
+
+/// Prices small enough to trade many times, mixed with values close to u64::MAX
+pub fn amount() -> impl Strategy<Value = u64> {
+    prop_oneof![
+        4 => 0..=1_000_000_000_000u64,
+        1 => any::<u64>(),
+    ]
+}
+
+pub fn basis_points() -> impl Strategy<Value = u16> {
+    0..=BASIS_POINTS as u16
+}
+
+/// Bonding curve and delta for a pair of `pair_type`
+pub fn curve(pair_type: u8) -> BoxedStrategy<(u8, u64)> {
+    let mut curves = vec![
+        amount().prop_map(|delta| (0, delta)).boxed(),
+        (0..=BASIS_POINTS).prop_map(|delta| (1, delta)).boxed(),
+        Just((2, 0)).boxed(),
+    ];
+
+    // Constant product pricing needs both quote tokens and nfts in the pair
+    if pair_type == 2 {
+        curves.push(Just((3, 0)).boxed());
+    }
+
+    proptest::strategy::Union::new(curves).boxed()
+}
+
+pub fn royalty_policy() -> impl Strategy<Value = (u8, u16)> {
+    prop_oneof![
+        Just((0, 0)),
+        Just((1, 0)),
+        basis_points().prop_map(|royalty_bps| (2, royalty_bps)),
+        basis_points().prop_map(|royalty_bps| (3, royalty_bps)),
+    ]
+}
+
+/// Valid parameters for a pair of `pair_type`
+pub fn pair_params_of(pair_type: u8) -> impl Strategy<Value = PairParams> {
+    // Only trade pairs can charge a fee
+    let fee = if pair_type == 2 {
+        basis_points().boxed()
+    } else {
+        Just(0).boxed()
+    };
+
+    (
+        curve(pair_type),
+        fee,
+        amount(),
+        any::<bool>(),
+        royalty_policy(),
+    )
+        .prop_map(
+            move |(
+                (bonding_curve, delta),
+                fee,
+                spot_price,
+                clamp_spot_price,
+                (royalty_policy, royalty_bps),
+            )| PairParams {
+                pair_type,
+                bonding_curve,
+                delta,
+                fee,
+                spot_price,
+                clamp_spot_price,
+                royalty_policy,
+                royalty_bps,
+            },
+        )
+}
+
+/// Valid parameters for a pair of any type
+pub fn pair_params() -> impl Strategy<Value = PairParams> {
+    (0..=2u8).prop_flat_map(pair_params_of)
+}
+
+/// Protocol fee tiers of a pair authority
+pub fn protocol_fees() -> impl Strategy<Value = [u16; 3]> {
+    [basis_points(), basis_points(), basis_points()]
+}
+
+pub fn pair(params: PairParams) -> Pair {
+    Pair {
+        pair_authority: Pubkey::default(),
+        owner: Pubkey::default(),
+        collection_mint: Pubkey::default(),
+        collection_mode: 0,
+        mint_allowlist_root: None,
+        quote_token_mint: Pubkey::default(),
+        quote_token_vault: Pubkey::default(),
+        is_native: false,
+        pair_type: params.pair_type,
+        bonding_curve: params.bonding_curve,
+        delta: params.delta,
+        fee: params.fee,
+        fee_vault: Pubkey::default(),
+        spot_price: params.spot_price,
+        clamp_spot_price: params.clamp_spot_price,
+        royalty_policy: params.royalty_policy,
+        royalty_bps: params.royalty_bps,
+        trade_count: 0,
+        is_active: false,
+        nfts_held: 0,
+        timelock_slots: 0,
+        halt_trades_while_pending: false,
+        pending_change: None,
+    }
+}
+
+pub fn pair_authority(fees: [u16; 3]) -> PairAuthority {
+    PairAuthority {
+        current_authority: Pubkey::default(),
+        pending_authority: Pubkey::default(),
+        fee_recipient: Pubkey::default(),
+        fees,
+        timelock_slots: 0,
+        pending_fee_change: None,
+    }
+}
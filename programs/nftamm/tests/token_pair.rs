@@ -0,0 +1,428 @@
+#![cfg(feature = "test-bpf")]
+
+mod common;
+
+use common::*;
+use nftamm_client::{instructions, pda};
+use solana_program_test::tokio;
+use solana_sdk::{
+    keccak,
+    signature::{Keypair, Signer},
+};
+
+const SPOT_PRICE: u64 = 1_000_000;
+const DELTA: u64 = 100_000;
+const FUNDING: u64 = 10_000_000;
+
+async fn funded_token_pair(market: &mut Market) -> solana_sdk::pubkey::Pubkey {
+    let pair = market
+        .create_pair(false, linear_params(0, SPOT_PRICE, DELTA))
+        .await
+        .unwrap();
+
+    market.fund_pair(&pair, FUNDING).await.unwrap();
+
+    pair
+}
+
+#[tokio::test]
+async fn funding_activates_token_pair() {
+    let mut market = setup_market().await;
+    let pair = market
+        .create_pair(false, linear_params(0, SPOT_PRICE, DELTA))
+        .await
+        .unwrap();
+
+    assert!(!market.pair(&pair).await.is_active);
+
+    // Not enough to buy one nft
+    market.fund_pair(&pair, SPOT_PRICE - 1).await.unwrap();
+    assert!(!market.pair(&pair).await.is_active);
+
+    market.fund_pair(&pair, 1).await.unwrap();
+    assert!(market.pair(&pair).await.is_active);
+}
+
+#[tokio::test]
+async fn sell_nft_into_token_pair() {
+    let mut market = setup_market().await;
+    let pair = funded_token_pair(&mut market).await;
+
+    let trader = market.trader.pubkey();
+    let nft = market.mint_nft(&trader).await;
+    let pair_account = market.pair(&pair).await;
+    let protocol_fee = SPOT_PRICE * PROTOCOL_FEE as u64 / 10_000;
+
+    let instruction = instructions::trade_token_pair(
+        trader,
+        pair,
+        &pair_account,
+        nft.mint,
+        SPOT_PRICE - protocol_fee,
+        Some(0),
+        vec![],
+        vec![],
+    );
+    market.as_trader(&[instruction]).await.unwrap();
+
+    let vault = pair_account.quote_token_vault;
+
+    // The seller pays the protocol fee out of the price
+    assert_eq!(
+        market.quote_balance(&trader).await,
+        QUOTE_BALANCE + SPOT_PRICE - protocol_fee
+    );
+    assert_eq!(market.protocol_fees().await, protocol_fee);
+    assert_eq!(
+        token_balance(&mut market.context, &vault).await,
+        FUNDING - SPOT_PRICE
+    );
+
+    let nft_vault = pda::find_nft_token_vault(&pair, &nft.mint).0;
+    assert_eq!(token_balance(&mut market.context, &nft_vault).await, 1);
+
+    let pair_account = market.pair(&pair).await;
+    assert_eq!(pair_account.spot_price, SPOT_PRICE - DELTA);
+    assert_eq!(pair_account.nfts_held, 1);
+    assert_eq!(pair_account.trade_count, 1);
+}
+
+#[tokio::test]
+async fn token_pair_deactivates_once_it_cannot_buy_another_nft() {
+    let mut market = setup_market().await;
+    let pair = market
+        .create_pair(false, linear_params(0, SPOT_PRICE, DELTA))
+        .await
+        .unwrap();
+
+    // Enough for one nft at each of the next two spot prices, less one token
+    let funding = SPOT_PRICE + (SPOT_PRICE - DELTA) - 1;
+    market.fund_pair(&pair, funding).await.unwrap();
+
+    let trader = market.trader.pubkey();
+    let nft = market.mint_nft(&trader).await;
+    let pair_account = market.pair(&pair).await;
+    let instruction = instructions::trade_token_pair(
+        trader,
+        pair,
+        &pair_account,
+        nft.mint,
+        0,
+        None,
+        vec![],
+        vec![],
+    );
+    market.as_trader(&[instruction]).await.unwrap();
+
+    // The balance left after paying the seller falls short of the next nft
+    let pair_account = market.pair(&pair).await;
+    assert_eq!(
+        token_balance(&mut market.context, &pair_account.quote_token_vault).await,
+        SPOT_PRICE - DELTA - 1
+    );
+    assert!(!pair_account.is_active);
+}
+
+#[tokio::test]
+async fn trade_token_pair_rejects_slippage_and_stale_trade_count() {
+    let mut market = setup_market().await;
+    let pair = funded_token_pair(&mut market).await;
+
+    let trader = market.trader.pubkey();
+    let nft = market.mint_nft(&trader).await;
+    let pair_account = market.pair(&pair).await;
+
+    let instruction = instructions::trade_token_pair(
+        trader,
+        pair,
+        &pair_account,
+        nft.mint,
+        SPOT_PRICE + 1,
+        None,
+        vec![],
+        vec![],
+    );
+    let result = market.as_trader(&[instruction]).await;
+    assert_nftamm_error(result, NftammError::SlippageExceeded);
+
+    let instruction = instructions::trade_token_pair(
+        trader,
+        pair,
+        &pair_account,
+        nft.mint,
+        0,
+        Some(1),
+        vec![],
+        vec![],
+    );
+    let result = market.as_trader(&[instruction]).await;
+    assert_nftamm_error(result, NftammError::TradeCountMismatch);
+}
+
+#[tokio::test]
+async fn linear_sell_below_zero_rejects_or_clamps() {
+    let mut market = setup_market().await;
+    let pair = market
+        .create_pair(false, linear_params(0, SPOT_PRICE, 2 * SPOT_PRICE))
+        .await
+        .unwrap();
+    market.fund_pair(&pair, FUNDING).await.unwrap();
+
+    let trader = market.trader.pubkey();
+    let nft = market.mint_nft(&trader).await;
+    let pair_account = market.pair(&pair).await;
+    let instruction = instructions::trade_token_pair(
+        trader,
+        pair,
+        &pair_account,
+        nft.mint,
+        0,
+        None,
+        vec![],
+        vec![],
+    );
+    let result = market.as_trader(std::slice::from_ref(&instruction)).await;
+    assert_nftamm_error(result, NftammError::SpotPriceUnderflow);
+
+    let owner = market.owner.pubkey();
+    let clamp = instructions::set_spot_price_clamp(owner, pair, true);
+    market.as_owner(&[clamp]).await.unwrap();
+
+    refresh_blockhash(&mut market.context).await;
+    market.as_trader(&[instruction]).await.unwrap();
+
+    let pair_account = market.pair(&pair).await;
+    assert!(pair_account.clamp_spot_price);
+    assert_eq!(pair_account.spot_price, 0);
+    assert_eq!(pair_account.nfts_held, 1);
+    assert_eq!(
+        market.quote_balance(&trader).await,
+        QUOTE_BALANCE + SPOT_PRICE - SPOT_PRICE * PROTOCOL_FEE as u64 / 10_000
+    );
+}
+
+#[tokio::test]
+async fn trade_token_pair_rejects_unverified_nft() {
+    let mut market = setup_market().await;
+    let pair = funded_token_pair(&mut market).await;
+
+    let trader = market.trader.pubkey();
+    let nft = create_unverified_nft(&mut market.context, &market.collection, &trader).await;
+    let pair_account = market.pair(&pair).await;
+    let instruction = instructions::trade_token_pair(
+        trader,
+        pair,
+        &pair_account,
+        nft.mint,
+        0,
+        None,
+        vec![],
+        vec![],
+    );
+    let result = market.as_trader(&[instruction]).await;
+
+    assert_nftamm_error(result, NftammError::NftNotVerified);
+}
+
+#[tokio::test]
+async fn trade_token_pair_enforces_mint_allowlist() {
+    let mut market = setup_market().await;
+    let pair = funded_token_pair(&mut market).await;
+
+    let trader = market.trader.pubkey();
+    let allowed = market.mint_nft(&trader).await;
+    let other = market.mint_nft(&trader).await;
+
+    // A single leaf tree, whose root is the leaf itself
+    let root = keccak::hashv(&[allowed.mint.as_ref()]).0;
+    let owner = market.owner.pubkey();
+    let instruction = instructions::change_mint_allowlist_root(owner, pair, Some(root));
+    market.as_owner(&[instruction]).await.unwrap();
+
+    let pair_account = market.pair(&pair).await;
+    let instruction = instructions::trade_token_pair(
+        trader,
+        pair,
+        &pair_account,
+        other.mint,
+        0,
+        None,
+        vec![],
+        vec![],
+    );
+    let result = market.as_trader(&[instruction]).await;
+    assert_nftamm_error(result, NftammError::MintNotAllowed);
+
+    let instruction = instructions::trade_token_pair(
+        trader,
+        pair,
+        &pair_account,
+        allowed.mint,
+        0,
+        None,
+        vec![],
+        vec![],
+    );
+    market.as_trader(&[instruction]).await.unwrap();
+}
+
+#[tokio::test]
+async fn withdraw_quote_token_from_token_pair() {
+    let mut market = setup_market().await;
+    let pair = funded_token_pair(&mut market).await;
+    let pair_account = market.pair(&pair).await;
+    let owner = market.owner.pubkey();
+
+    let instruction = instructions::withdraw_quote_token(owner, pair, &pair_account, FUNDING + 1);
+    let result = market.as_owner(&[instruction]).await;
+    assert_nftamm_error(result, NftammError::InsufficientBalance);
+
+    let trader = market.trader.pubkey();
+    let instruction = instructions::withdraw_quote_token(trader, pair, &pair_account, FUNDING);
+    let result = market.as_trader(&[instruction]).await;
+    assert_nftamm_error(result, NftammError::InvalidOwner);
+
+    let instruction = instructions::withdraw_quote_token(owner, pair, &pair_account, FUNDING);
+    market.as_owner(&[instruction]).await.unwrap();
+
+    assert_eq!(market.quote_balance(&owner).await, QUOTE_BALANCE);
+
+    // An empty token pair can no longer buy nfts
+    assert!(!market.pair(&pair).await.is_active);
+}
+
+#[tokio::test]
+async fn token_pair_rejects_nft_deposits() {
+    let mut market = setup_market().await;
+    let pair = funded_token_pair(&mut market).await;
+
+    let result = market.deposit_nft(&pair).await.map(|_| ());
+
+    assert_nftamm_error(result, NftammError::InvalidPairType);
+}
+
+#[tokio::test]
+async fn close_pair_requires_no_nfts() {
+    let mut market = setup_market().await;
+    let pair = funded_token_pair(&mut market).await;
+
+    let trader = market.trader.pubkey();
+    let nft = market.mint_nft(&trader).await;
+    let pair_account = market.pair(&pair).await;
+    let instruction = instructions::trade_token_pair(
+        trader,
+        pair,
+        &pair_account,
+        nft.mint,
+        0,
+        None,
+        vec![],
+        vec![],
+    );
+    market.as_trader(&[instruction]).await.unwrap();
+
+    let owner = market.owner.pubkey();
+    let pair_account = market.pair(&pair).await;
+    let instruction = instructions::close_pair(owner, pair, &pair_account);
+    let result = market.as_owner(&[instruction]).await;
+    assert_nftamm_error(result, NftammError::StillHoldsNfts);
+
+    let instruction = instructions::withdraw_nft(owner, pair, &pair_account, nft.mint, trader);
+    market.as_owner(&[instruction]).await.unwrap();
+
+    refresh_blockhash(&mut market.context).await;
+    let instruction = instructions::close_pair(owner, pair, &pair_account);
+    market.as_owner(&[instruction]).await.unwrap();
+
+    assert!(get_account(&mut market.context, &pair).await.is_none());
+}
+
+#[tokio::test]
+async fn creator_collection_mode_requires_verified_creator() {
+    let mut market = setup_market().await;
+
+    // Creator collection modes identify the collection by its creator's address
+    let pair = Keypair::new();
+    let owner = market.owner.pubkey();
+    let params = instructions::PairParams {
+        collection_mode: 1,
+        ..linear_params(0, SPOT_PRICE, DELTA)
+    };
+    let instruction = instructions::initialize_pair(
+        owner,
+        pair.pubkey(),
+        market.protocol.pair_authority,
+        market.collection.authority.pubkey(),
+        market.quote_token_mint,
+        params,
+    );
+    process(&mut market.context, &[instruction], &[&market.owner, &pair])
+        .await
+        .unwrap();
+
+    let pair = pair.pubkey();
+    market.fund_pair(&pair, FUNDING).await.unwrap();
+
+    let trader = market.trader.pubkey();
+    let verified = market.mint_nft(&trader).await;
+    let unverified = create_unverified_nft(&mut market.context, &market.collection, &trader).await;
+    let pair_account = market.pair(&pair).await;
+
+    let instruction = instructions::trade_token_pair(
+        trader,
+        pair,
+        &pair_account,
+        unverified.mint,
+        0,
+        None,
+        vec![],
+        vec![],
+    );
+    let result = market.as_trader(&[instruction]).await;
+    assert_nftamm_error(result, NftammError::CreatorNotVerified);
+
+    let instruction = instructions::trade_token_pair(
+        trader,
+        pair,
+        &pair_account,
+        verified.mint,
+        0,
+        None,
+        vec![],
+        vec![],
+    );
+    market.as_trader(&[instruction]).await.unwrap();
+}
+
+#[tokio::test]
+async fn initialize_pair_rejects_invalid_params() {
+    let mut market = setup_market().await;
+
+    let params = instructions::PairParams {
+        collection_mode: 3,
+        ..linear_params(0, SPOT_PRICE, DELTA)
+    };
+    let result = market.create_pair(false, params).await.map(|_| ());
+    assert_nftamm_error(result, NftammError::InvalidCollectionMode);
+
+    let result = market
+        .create_pair(false, linear_params(3, SPOT_PRICE, DELTA))
+        .await
+        .map(|_| ());
+    assert_nftamm_error(result, NftammError::InvalidPairType);
+
+    let params = instructions::PairParams {
+        fee: 100,
+        ..linear_params(0, SPOT_PRICE, DELTA)
+    };
+    let result = market.create_pair(false, params).await.map(|_| ());
+    assert_nftamm_error(result, NftammError::InvalidFee);
+
+    let params = instructions::PairParams {
+        royalty_policy: 2,
+        royalty_bps: 10_001,
+        ..linear_params(0, SPOT_PRICE, DELTA)
+    };
+    let result = market.create_pair(true, params).await.map(|_| ());
+    assert_nftamm_error(result, NftammError::InvalidRoyaltyPolicy);
+}
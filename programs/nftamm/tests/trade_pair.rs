@@ -0,0 +1,465 @@
+#![cfg(feature = "test-bpf")]
+
+mod common;
+
+use common::*;
+use nftamm_client::{
+    instructions::{self, BuyItem, SellItem},
+    pda,
+};
+use solana_program_test::tokio;
+use solana_sdk::{pubkey::Pubkey, rent::Rent, signature::Signer};
+
+const SPOT_PRICE: u64 = 1_000_000;
+const DELTA: u64 = 100_000;
+const FUNDING: u64 = 10_000_000;
+const PAIR_FEE: u64 = 200;
+
+fn fees(price: u64) -> (u64, u64) {
+    (
+        price * PROTOCOL_FEE as u64 / 10_000,
+        price * PAIR_FEE / 10_000,
+    )
+}
+
+/// A trade pair holding [FUNDING] quote tokens and `nfts` deposited nfts
+async fn trade_pair(
+    market: &mut Market,
+    params: instructions::PairParams,
+    nfts: usize,
+) -> (Pubkey, Vec<Nft>) {
+    let pair = market.create_pair(false, params).await.unwrap();
+    market.fund_pair(&pair, FUNDING).await.unwrap();
+
+    let mut deposited = vec![];
+    for _ in 0..nfts {
+        deposited.push(market.deposit_nft(&pair).await.unwrap());
+    }
+
+    (pair, deposited)
+}
+
+#[tokio::test]
+async fn buy_nft_from_trade_pair() {
+    let mut market = setup_market().await;
+    let (pair, nfts) = trade_pair(&mut market, linear_params(2, SPOT_PRICE, DELTA), 1).await;
+
+    let trader = market.trader.pubkey();
+    let owner = market.owner.pubkey();
+    let pair_account = market.pair(&pair).await;
+
+    // A buy is charged the spot price it moves the pair to
+    let price = SPOT_PRICE + DELTA;
+    let (protocol_fee, pair_fee) = fees(price);
+
+    let instruction = instructions::swap_token_trade_pair(
+        trader,
+        pair,
+        &pair_account,
+        nfts[0].mint,
+        owner,
+        price + protocol_fee + pair_fee,
+        Some(0),
+        vec![],
+    );
+    market.as_trader(&[instruction]).await.unwrap();
+
+    assert_eq!(
+        market.quote_balance(&trader).await,
+        QUOTE_BALANCE - price - protocol_fee - pair_fee
+    );
+    assert_eq!(market.protocol_fees().await, protocol_fee);
+    assert_eq!(
+        token_balance(&mut market.context, &pair_account.quote_token_vault).await,
+        FUNDING + price
+    );
+    assert_eq!(
+        token_balance(&mut market.context, &pair_account.fee_vault).await,
+        pair_fee
+    );
+
+    // A trade pair stays active to keep buying nfts
+    let pair_account = market.pair(&pair).await;
+    assert_eq!(pair_account.spot_price, SPOT_PRICE + DELTA);
+    assert_eq!(pair_account.nfts_held, 0);
+    assert!(pair_account.is_active);
+}
+
+#[tokio::test]
+async fn sell_nft_into_trade_pair() {
+    let mut market = setup_market().await;
+    let (pair, _) = trade_pair(&mut market, linear_params(2, SPOT_PRICE, DELTA), 0).await;
+
+    let trader = market.trader.pubkey();
+    let nft = market.mint_nft(&trader).await;
+    let pair_account = market.pair(&pair).await;
+    let (protocol_fee, pair_fee) = fees(SPOT_PRICE);
+
+    let instruction = instructions::swap_nft_trade_pair(
+        trader,
+        pair,
+        &pair_account,
+        nft.mint,
+        SPOT_PRICE - pair_fee - protocol_fee,
+        Some(0),
+        vec![],
+        vec![],
+    );
+    market.as_trader(&[instruction]).await.unwrap();
+
+    // The seller pays both fees out of the price, and the pair fee stays in the quote vault
+    assert_eq!(
+        market.quote_balance(&trader).await,
+        QUOTE_BALANCE + SPOT_PRICE - pair_fee - protocol_fee
+    );
+    assert_eq!(market.protocol_fees().await, protocol_fee);
+    assert_eq!(
+        token_balance(&mut market.context, &pair_account.quote_token_vault).await,
+        FUNDING - SPOT_PRICE + pair_fee
+    );
+
+    let pair_account = market.pair(&pair).await;
+    assert_eq!(pair_account.spot_price, SPOT_PRICE - DELTA);
+    assert_eq!(pair_account.nfts_held, 1);
+}
+
+#[tokio::test]
+async fn swaps_reject_slippage() {
+    let mut market = setup_market().await;
+    let (pair, nfts) = trade_pair(&mut market, linear_params(2, SPOT_PRICE, DELTA), 1).await;
+
+    let trader = market.trader.pubkey();
+    let owner = market.owner.pubkey();
+    let nft = market.mint_nft(&trader).await;
+    let pair_account = market.pair(&pair).await;
+
+    let instruction = instructions::swap_token_trade_pair(
+        trader,
+        pair,
+        &pair_account,
+        nfts[0].mint,
+        owner,
+        SPOT_PRICE,
+        None,
+        vec![],
+    );
+    let result = market.as_trader(&[instruction]).await;
+    assert_nftamm_error(result, NftammError::SlippageExceeded);
+
+    let instruction = instructions::swap_nft_trade_pair(
+        trader,
+        pair,
+        &pair_account,
+        nft.mint,
+        SPOT_PRICE,
+        None,
+        vec![],
+        vec![],
+    );
+    let result = market.as_trader(&[instruction]).await;
+    assert_nftamm_error(result, NftammError::SlippageExceeded);
+}
+
+#[tokio::test]
+async fn buy_several_nfts_from_trade_pair() {
+    let mut market = setup_market().await;
+    let (pair, nfts) = trade_pair(&mut market, linear_params(2, SPOT_PRICE, DELTA), 2).await;
+
+    let trader = market.trader.pubkey();
+    let owner = market.owner.pubkey();
+    let mut items = vec![];
+    for nft in &nfts {
+        create_associated_token_account(&mut market.context, &trader, &nft.mint).await;
+        items.push(BuyItem {
+            nft_token_mint: nft.mint,
+            pair_metadata_creator: owner,
+            royalty_accounts: vec![],
+        });
+    }
+
+    // The spot price moves up by delta after every nft, and each nft is charged the spot price
+    // it moves the pair to
+    let mut total = 0;
+    let mut pair_fees = 0;
+    for price in [SPOT_PRICE + DELTA, SPOT_PRICE + 2 * DELTA] {
+        let (protocol_fee, pair_fee) = fees(price);
+        total += price + protocol_fee + pair_fee;
+        pair_fees += pair_fee;
+    }
+
+    let pair_account = market.pair(&pair).await;
+    let instruction = instructions::buy_nfts(trader, pair, &pair_account, &items, total - 1);
+    let result = market.as_trader(&[instruction]).await;
+    assert_nftamm_error(result, NftammError::SlippageExceeded);
+
+    let instruction = instructions::buy_nfts(trader, pair, &pair_account, &items, total);
+    market.as_trader(&[instruction]).await.unwrap();
+
+    assert_eq!(market.quote_balance(&trader).await, QUOTE_BALANCE - total);
+    assert_eq!(
+        token_balance(&mut market.context, &pair_account.fee_vault).await,
+        pair_fees
+    );
+
+    let pair_account = market.pair(&pair).await;
+    assert_eq!(pair_account.spot_price, SPOT_PRICE + 2 * DELTA);
+    assert_eq!(pair_account.nfts_held, 0);
+    assert_eq!(pair_account.trade_count, 2);
+}
+
+#[tokio::test]
+async fn sell_several_nfts_into_trade_pair() {
+    let mut market = setup_market().await;
+    let (pair, _) = trade_pair(&mut market, linear_params(2, SPOT_PRICE, DELTA), 0).await;
+
+    let trader = market.trader.pubkey();
+    let mut items = vec![];
+    for _ in 0..2 {
+        let nft = market.mint_nft(&trader).await;
+        items.push(SellItem {
+            nft_token_mint: nft.mint,
+            proof: vec![],
+            royalty_accounts: vec![],
+        });
+    }
+
+    // The spot price moves down by delta after every nft
+    let mut total = 0;
+    let mut protocol_fees = 0;
+    for price in [SPOT_PRICE, SPOT_PRICE - DELTA] {
+        let (protocol_fee, pair_fee) = fees(price);
+        total += price - pair_fee - protocol_fee;
+        protocol_fees += protocol_fee;
+    }
+
+    let pair_account = market.pair(&pair).await;
+    let instruction = instructions::sell_nfts(trader, pair, &pair_account, &items, total + 1);
+    let result = market.as_trader(&[instruction]).await;
+    assert_nftamm_error(result, NftammError::SlippageExceeded);
+
+    let instruction = instructions::sell_nfts(trader, pair, &pair_account, &items, total);
+    market.as_trader(&[instruction]).await.unwrap();
+
+    assert_eq!(market.quote_balance(&trader).await, QUOTE_BALANCE + total);
+    assert_eq!(market.protocol_fees().await, protocol_fees);
+
+    let pair_account = market.pair(&pair).await;
+    assert_eq!(pair_account.spot_price, SPOT_PRICE - 2 * DELTA);
+    assert_eq!(pair_account.nfts_held, 2);
+    assert_eq!(pair_account.trade_count, 2);
+}
+
+#[tokio::test]
+async fn sell_nfts_into_prefunded_vault_addresses() {
+    let mut market = setup_market().await;
+    let (pair, _) = trade_pair(&mut market, linear_params(2, SPOT_PRICE, DELTA), 0).await;
+
+    let trader = market.trader.pubkey();
+    let nft = market.mint_nft(&trader).await;
+
+    // Anyone can send lamports to the addresses the sale creates accounts at
+    let nft_vault = pda::find_nft_token_vault(&pair, &nft.mint).0;
+    let pair_metadata = pda::find_pair_metadata(&pair, &nft.mint).0;
+    let lamports = Rent::default().minimum_balance(0);
+    transfer_lamports(&mut market.context, &nft_vault, lamports).await;
+    transfer_lamports(&mut market.context, &pair_metadata, lamports).await;
+
+    let items = [SellItem {
+        nft_token_mint: nft.mint,
+        proof: vec![],
+        royalty_accounts: vec![],
+    }];
+    let pair_account = market.pair(&pair).await;
+    let instruction = instructions::sell_nfts(trader, pair, &pair_account, &items, 0);
+    market.as_trader(&[instruction]).await.unwrap();
+
+    assert_eq!(token_balance(&mut market.context, &nft_vault).await, 1);
+    assert_eq!(market.pair(&pair).await.nfts_held, 1);
+}
+
+#[tokio::test]
+async fn withdraw_trade_pair_fees() {
+    let mut market = setup_market().await;
+    let (pair, nfts) = trade_pair(&mut market, linear_params(2, SPOT_PRICE, DELTA), 1).await;
+
+    let trader = market.trader.pubkey();
+    let owner = market.owner.pubkey();
+    let pair_account = market.pair(&pair).await;
+    let instruction = instructions::swap_token_trade_pair(
+        trader,
+        pair,
+        &pair_account,
+        nfts[0].mint,
+        owner,
+        u64::MAX,
+        None,
+        vec![],
+    );
+    market.as_trader(&[instruction]).await.unwrap();
+
+    let (_, pair_fee) = fees(SPOT_PRICE + DELTA);
+    let owner_balance = market.quote_balance(&owner).await;
+
+    let instruction = instructions::withdraw_fee(trader, pair, &pair_account, pair_fee);
+    let result = market.as_trader(&[instruction]).await;
+    assert_nftamm_error(result, NftammError::InvalidOwner);
+
+    let instruction = instructions::withdraw_fee(owner, pair, &pair_account, pair_fee + 1);
+    let result = market.as_owner(&[instruction]).await;
+    assert_nftamm_error(result, NftammError::InsufficientBalance);
+
+    let instruction = instructions::withdraw_fee(owner, pair, &pair_account, pair_fee);
+    market.as_owner(&[instruction]).await.unwrap();
+
+    assert_eq!(market.quote_balance(&owner).await, owner_balance + pair_fee);
+    assert_eq!(
+        token_balance(&mut market.context, &pair_account.fee_vault).await,
+        0
+    );
+}
+
+#[tokio::test]
+async fn xyk_trade_pair_prices_from_reserves() {
+    let mut market = setup_market().await;
+    let params = instructions::PairParams {
+        bonding_curve: 3,
+        ..linear_params(2, 0, 0)
+    };
+    let (pair, nfts) = trade_pair(&mut market, params, 2).await;
+
+    // Buying one of two nfts takes the whole quote reserve
+    let price = FUNDING;
+    let (protocol_fee, pair_fee) = fees(price);
+
+    let trader = market.trader.pubkey();
+    let owner = market.owner.pubkey();
+    let pair_account = market.pair(&pair).await;
+    let instruction = instructions::swap_token_trade_pair(
+        trader,
+        pair,
+        &pair_account,
+        nfts[0].mint,
+        owner,
+        price + protocol_fee + pair_fee,
+        None,
+        vec![],
+    );
+    market.as_trader(&[instruction]).await.unwrap();
+
+    assert_eq!(
+        market.quote_balance(&trader).await,
+        QUOTE_BALANCE - price - protocol_fee - pair_fee
+    );
+
+    // The last nft can never be bought out of the pool
+    let instruction = instructions::swap_token_trade_pair(
+        trader,
+        pair,
+        &pair_account,
+        nfts[1].mint,
+        owner,
+        u64::MAX,
+        None,
+        vec![],
+    );
+    let result = market.as_trader(&[instruction]).await;
+    assert_nftamm_error(result, NftammError::InsufficientReserves);
+}
+
+#[tokio::test]
+async fn xyk_trade_pair_without_nfts_rejects_sells() {
+    let mut market = setup_market().await;
+    let params = instructions::PairParams {
+        bonding_curve: 3,
+        ..linear_params(2, 0, 0)
+    };
+    let (pair, _) = trade_pair(&mut market, params, 0).await;
+
+    // Funding activates the pair, but an empty pool has no price to pay for an nft
+    let trader = market.trader.pubkey();
+    let nft = market.mint_nft(&trader).await;
+    let pair_account = market.pair(&pair).await;
+    assert!(pair_account.is_active);
+
+    let instruction = instructions::swap_nft_trade_pair(
+        trader,
+        pair,
+        &pair_account,
+        nft.mint,
+        0,
+        None,
+        vec![],
+        vec![],
+    );
+    let result = market.as_trader(&[instruction]).await;
+    assert_nftamm_error(result, NftammError::InsufficientReserves);
+
+    assert_eq!(
+        token_balance(&mut market.context, &pair_account.quote_token_vault).await,
+        FUNDING
+    );
+}
+
+#[tokio::test]
+async fn xyk_curve_requires_trade_pair() {
+    let mut market = setup_market().await;
+
+    for pair_type in [0, 1] {
+        let params = instructions::PairParams {
+            bonding_curve: 3,
+            ..linear_params(pair_type, SPOT_PRICE, 0)
+        };
+        let result = market.create_pair(false, params).await.map(|_| ());
+
+        assert_nftamm_error(result, NftammError::InvalidBondingCurve);
+    }
+}
+
+#[tokio::test]
+async fn swaps_require_trade_pair() {
+    let mut market = setup_market().await;
+    let pair = market
+        .create_pair(false, linear_params(0, SPOT_PRICE, DELTA))
+        .await
+        .unwrap();
+    market.fund_pair(&pair, FUNDING).await.unwrap();
+
+    let trader = market.trader.pubkey();
+    let nft = market.mint_nft(&trader).await;
+    let pair_account = market.pair(&pair).await;
+    let instruction = instructions::swap_nft_trade_pair(
+        trader,
+        pair,
+        &pair_account,
+        nft.mint,
+        0,
+        None,
+        vec![],
+        vec![],
+    );
+    let result = market.as_trader(&[instruction]).await;
+
+    assert_nftamm_error(result, NftammError::InvalidPairType);
+}
+
+#[tokio::test]
+async fn quote_instructions_price_trades() {
+    let mut market = setup_market().await;
+    let params = instructions::PairParams {
+        bonding_curve: 3,
+        ..linear_params(2, 0, 0)
+    };
+    let (pair, _) = trade_pair(&mut market, params, 1).await;
+    let pair_account = market.pair(&pair).await;
+
+    // Quotes are read only and need no signer besides the fee payer
+    let instruction = instructions::quote_sell(pair, &pair_account, 2, 0);
+    process(&mut market.context, &[instruction], &[])
+        .await
+        .unwrap();
+
+    // A constant product pair cannot quote buying its last nft
+    let instruction = instructions::quote_buy(pair, &pair_account, 1, 0);
+    let result = process(&mut market.context, &[instruction], &[]).await;
+    assert_nftamm_error(result, NftammError::InsufficientReserves);
+}
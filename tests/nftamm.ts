@@ -212,10 +212,15 @@ describe("nftamm", () => {
 
   it("Initialize pair authority", async () => {
     const tx = await program.methods
-      .initializePairAuthority(feeForPairAuthority)
+      .initializePairAuthority([
+        feeForPairAuthority,
+        feeForPairAuthority,
+        feeForPairAuthority,
+      ])
       .accounts({
         pairAuthority: pairAuthorityAccount.publicKey,
         payer: protocolAuthority.publicKey,
+        feeRecipient: protocolAuthority.publicKey,
         systemProgram: anchor.web3.SystemProgram.programId,
       })
       .signers([pairAuthorityAccount, protocolAuthority])
@@ -250,6 +255,17 @@ describe("nftamm", () => {
       program.programId
     );
 
+    const [protocolFeeVaultPubkey, protocolFeeVaultBump] =
+      await PublicKey.findProgramAddress(
+        [
+          Buffer.from("quote"),
+          Buffer.from("protocol_fee"),
+          pairAuthorityAccount.publicKey.toBuffer(),
+          collectionQuoteMint.toBuffer(),
+        ],
+        program.programId
+      );
+
     const [programAsSignerPubkey, programAsSignerBump] =
       await PublicKey.findProgramAddress(
         [Buffer.from("program"), Buffer.from("signer")],
@@ -257,7 +273,7 @@ describe("nftamm", () => {
       );
 
     const tx = await program.methods
-      .initializePair(1, 0, new anchor.BN(2), 0, new anchor.BN(100), false)
+      .initializePair(1, 0, new anchor.BN(2), 0, new anchor.BN(100), 0, 0, null, 0)
       .accounts({
         payer: poolCreator.publicKey,
         pair: nftPairLinearCurve.publicKey,
@@ -267,6 +283,7 @@ describe("nftamm", () => {
         quoteTokenMint: collectionQuoteMint,
         quoteTokenVault: quoteTokenVaultPubkey,
         feeVault: feeVaultPubkey,
+        protocolFeeVault: protocolFeeVaultPubkey,
         systemProgram: anchor.web3.SystemProgram.programId,
         tokenProgram: TokenProgram.publicKey,
         rent: anchor.web3.SYSVAR_RENT_PUBKEY,
@@ -294,6 +311,17 @@ describe("nftamm", () => {
       program.programId
     );
 
+    const [protocolFeeVaultPubkey, protocolFeeVaultBump] =
+      await PublicKey.findProgramAddress(
+        [
+          Buffer.from("quote"),
+          Buffer.from("protocol_fee"),
+          pairAuthorityAccount.publicKey.toBuffer(),
+          collectionQuoteMint.toBuffer(),
+        ],
+        program.programId
+      );
+
     const [programAsSignerPubkey, programAsSignerBump] =
       await PublicKey.findProgramAddress(
         [Buffer.from("program"), Buffer.from("signer")],
@@ -301,7 +329,7 @@ describe("nftamm", () => {
       );
 
     const tx = await program.methods
-      .initializePair(0, 0, new anchor.BN(2), 0, new anchor.BN(100), false)
+      .initializePair(0, 0, new anchor.BN(2), 0, new anchor.BN(100), 0, 0, null, 0)
       .accounts({
         payer: poolCreator.publicKey,
         pair: nftPairLinearCurve.publicKey,
@@ -311,6 +339,7 @@ describe("nftamm", () => {
         quoteTokenMint: collectionQuoteMint,
         quoteTokenVault: quoteTokenVaultPubkey,
         feeVault: feeVaultPubkey,
+        protocolFeeVault: protocolFeeVaultPubkey,
         systemProgram: anchor.web3.SystemProgram.programId,
         tokenProgram: TokenProgram.publicKey,
         rent: anchor.web3.SYSVAR_RENT_PUBKEY,
@@ -338,6 +367,17 @@ describe("nftamm", () => {
       program.programId
     );
 
+    const [protocolFeeVaultPubkey, protocolFeeVaultBump] =
+      await PublicKey.findProgramAddress(
+        [
+          Buffer.from("quote"),
+          Buffer.from("protocol_fee"),
+          pairAuthorityAccount.publicKey.toBuffer(),
+          collectionQuoteMint.toBuffer(),
+        ],
+        program.programId
+      );
+
     const [programAsSignerPubkey, programAsSignerBump] =
       await PublicKey.findProgramAddress(
         [Buffer.from("program"), Buffer.from("signer")],
@@ -345,7 +385,7 @@ describe("nftamm", () => {
       );
 
     const tx = await program.methods
-      .initializePair(2, 0, new anchor.BN(2), 0, new anchor.BN(100), false)
+      .initializePair(2, 0, new anchor.BN(2), 0, new anchor.BN(100), 0, 0, null, 0)
       .accounts({
         payer: poolCreator.publicKey,
         pair: nftPairLinearCurve.publicKey,
@@ -355,6 +395,7 @@ describe("nftamm", () => {
         quoteTokenMint: collectionQuoteMint,
         quoteTokenVault: quoteTokenVaultPubkey,
         feeVault: feeVaultPubkey,
+        protocolFeeVault: protocolFeeVaultPubkey,
         systemProgram: anchor.web3.SystemProgram.programId,
         tokenProgram: TokenProgram.publicKey,
         rent: anchor.web3.SYSVAR_RENT_PUBKEY,